@@ -0,0 +1,267 @@
+use super::elf::{self, ElfInfo};
+use super::rules::{Pairing, PathRules};
+use crate::circleci::artifacts::Artifact;
+use crate::circleci::client::Client;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{Error,ErrorKind};
+
+// Number of differing symbols listed per artifact, the rest is only counted.
+const MAX_LISTED_SYMBOLS: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct FileSummary {
+    pub path: String,
+    pub size: usize,
+    pub sha256: String,
+    pub elf: Option<ElfInfo>,
+}
+
+impl FileSummary {
+    pub fn from(path: &String, content: &[u8]) -> Result<FileSummary, Error> {
+        Ok(FileSummary {
+            path: String::from(path),
+            size: content.len(),
+            sha256: format!("{:x}", Sha256::digest(content)),
+            elf: elf::parse(content)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ArtifactComparison {
+    pub base: FileSummary,
+    pub migration: FileSummary,
+}
+
+impl ArtifactComparison {
+    pub fn identical(&self) -> bool {
+        self.base.sha256 == self.migration.sha256
+    }
+
+    /// Identical files, or ELF files with the same SONAME and exported symbols.
+    pub fn equivalent(&self) -> bool {
+        if self.identical() {
+            return true;
+        }
+        match (&self.base.elf, &self.migration.elf) {
+            (Some(base), Some(migration)) => base == migration,
+            _ => false,
+        }
+    }
+
+    pub fn base_only_symbols(&self) -> Vec<&String> {
+        match (&self.base.elf, &self.migration.elf) {
+            (Some(base), Some(migration)) => base.exported_symbols.difference(&migration.exported_symbols).collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn migration_only_symbols(&self) -> Vec<&String> {
+        match (&self.base.elf, &self.migration.elf) {
+            (Some(base), Some(migration)) => migration.exported_symbols.difference(&base.exported_symbols).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ArtifactParity {
+    pub base_job_number: u64,
+    pub migration_job_number: u64,
+    pub comparisons: Vec<ArtifactComparison>,
+    pub base_unmatched: Vec<String>,
+    pub migration_unmatched: Vec<String>,
+}
+
+impl ArtifactParity {
+    pub fn from(base_job_number: u64, migration_job_number: u64, pairing: Pairing, comparisons: Vec<ArtifactComparison>) -> ArtifactParity {
+        ArtifactParity {
+            base_job_number,
+            migration_job_number,
+            comparisons,
+            base_unmatched: pairing.base_unmatched,
+            migration_unmatched: pairing.migration_unmatched,
+        }
+    }
+
+    /// Every base artifact has an equivalent migration artifact.
+    pub fn is_equivalent(&self) -> bool {
+        self.base_unmatched.is_empty() && self.comparisons.iter().all(|c| c.equivalent())
+    }
+}
+
+/// Compare the artifacts of a base and a migration job, paired by the path rules.
+pub async fn artifact_parity(client: &Client, rules: &PathRules, base_job_number: u64, migration_job_number: u64) -> Result<ArtifactParity, Error> {
+    let base_artifacts = client.get_artifacts(base_job_number).await?;
+    let migration_artifacts = client.get_artifacts(migration_job_number).await?;
+    let pairing = rules.pair(&artifact_paths(&base_artifacts), &artifact_paths(&migration_artifacts));
+
+    let mut comparisons = vec![];
+    for (base_path, migration_path) in &pairing.pairs {
+        comparisons.push(ArtifactComparison {
+            base: summarize_artifact(client, &base_artifacts, base_path).await?,
+            migration: summarize_artifact(client, &migration_artifacts, migration_path).await?,
+        });
+    }
+    Ok(ArtifactParity::from(base_job_number, migration_job_number, pairing, comparisons))
+}
+
+fn artifact_paths(artifacts: &[Artifact]) -> Vec<String> {
+    artifacts.iter().map(|a| a.path.to_string()).collect()
+}
+
+async fn summarize_artifact(client: &Client, artifacts: &[Artifact], path: &String) -> Result<FileSummary, Error> {
+    let artifact = artifacts.iter().find(|a| &a.path == path)
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("failed to find artifact {}", path)))?;
+    let content = client.download_artifact(artifact).await?;
+    FileSummary::from(path, &content)
+}
+
+impl fmt::Display for ArtifactParity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let comparisons: String = self.comparisons.iter().map(format_comparison).collect();
+
+        let display = format!(r#"
+        Artifact parity analysis:
+
+        Details:
+
+            base job number: {}
+            migration job number: {}
+            equivalent: {}
+
+        Compared artifacts (base -> migration):
+{}
+        Only produced by the base job:
+{}
+        Only produced by the migration job:
+{}"#,
+        self.base_job_number,
+        self.migration_job_number,
+        self.is_equivalent(),
+        comparisons,
+        format_paths(&self.base_unmatched),
+        format_paths(&self.migration_unmatched));
+        write!(f, "{}", display)
+    }
+}
+
+fn format_comparison(comparison: &ArtifactComparison) -> String {
+    let status = if comparison.identical() {
+        "identical"
+    } else if comparison.equivalent() {
+        "equivalent"
+    } else {
+        "different"
+    };
+    let mut lines = format!(
+        "\n            {} -> {}: {}\n                size: {} -> {} bytes\n                sha256: {} -> {}",
+        comparison.base.path,
+        comparison.migration.path,
+        status,
+        comparison.base.size,
+        comparison.migration.size,
+        comparison.base.sha256,
+        comparison.migration.sha256);
+
+    if let (Some(base), Some(migration)) = (&comparison.base.elf, &comparison.migration.elf) {
+        if base.soname != migration.soname {
+            lines.push_str(&format!(
+                "\n                soname: {} -> {}",
+                base.soname.as_deref().unwrap_or("none"),
+                migration.soname.as_deref().unwrap_or("none")));
+        }
+        lines.push_str(&format_symbols("exported only by base", &comparison.base_only_symbols()));
+        lines.push_str(&format_symbols("exported only by migration", &comparison.migration_only_symbols()));
+    }
+    format!("{}\n", lines)
+}
+
+fn format_symbols(label: &str, symbols: &[&String]) -> String {
+    if symbols.is_empty() {
+        return String::new();
+    }
+    let listed: Vec<&str> = symbols.iter().take(MAX_LISTED_SYMBOLS).map(|s| s.as_str()).collect();
+    let mut line = format!("\n                {} ({}): {}", label, symbols.len(), listed.join(", "));
+    if symbols.len() > MAX_LISTED_SYMBOLS {
+        line.push_str(", ...");
+    }
+    line
+}
+
+fn format_paths(paths: &[String]) -> String {
+    if paths.is_empty() {
+        return String::from("\n            none\n");
+    }
+    let lines: String = paths.iter().map(|p| format!("\n            {}", p)).collect();
+    format!("{}\n", lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_LIBRARY: &[u8] = include_bytes!("../../testdata/elf/libbase.elf");
+    const MIGRATION_LIBRARY: &[u8] = include_bytes!("../../testdata/elf/libmigration.elf");
+
+    #[test]
+    fn identical_files_are_equivalent() {
+        let comparison = ArtifactComparison {
+            base: FileSummary::from(&String::from("build/file.txt"), b"content").unwrap(),
+            migration: FileSummary::from(&String::from("bazel-bin/file.txt"), b"content").unwrap(),
+        };
+
+        assert!(comparison.identical());
+        assert!(comparison.equivalent());
+    }
+
+    #[test]
+    fn different_non_elf_files_are_not_equivalent() {
+        let comparison = ArtifactComparison {
+            base: FileSummary::from(&String::from("build/file.txt"), b"content").unwrap(),
+            migration: FileSummary::from(&String::from("bazel-bin/file.txt"), b"other content").unwrap(),
+        };
+
+        assert!(!comparison.equivalent());
+    }
+
+    #[test]
+    fn elf_comparison_reports_differing_symbols_and_soname() {
+        let comparison = ArtifactComparison {
+            base: FileSummary::from(&String::from("build/libparity.so.1"), BASE_LIBRARY).unwrap(),
+            migration: FileSummary::from(&String::from("bazel-bin/libparity.so"), MIGRATION_LIBRARY).unwrap(),
+        };
+
+        assert!(!comparison.equivalent());
+        assert_eq!(comparison.base_only_symbols(), vec!["base_symbol"]);
+        assert_eq!(comparison.migration_only_symbols(), vec!["migration_symbol"]);
+
+        let display = format_comparison(&comparison);
+        assert!(display.contains("soname: libparity.so.1 -> libparity.so"));
+        assert!(display.contains("exported only by base (1): base_symbol"));
+    }
+
+    #[test]
+    fn artifact_parity_is_not_equivalent_when_base_artifacts_are_missing() {
+        let pairing = Pairing {
+            pairs: vec![],
+            base_unmatched: vec![String::from("build/libvsomeip3.so")],
+            migration_unmatched: vec![],
+        };
+
+        let parity = ArtifactParity::from(1, 2, pairing, vec![]);
+
+        assert!(!parity.is_equivalent());
+    }
+
+    #[tokio::test]
+    async fn summarize_artifact_fails_on_missing_path() {
+        let client = Client::from(&String::from("gh/org"), &String::from("repo"), &String::from("build"), &String::from("last-7-days"), "token").unwrap();
+        let artifacts = vec![Artifact { path: String::from("build/libvsomeip3.so"), node_index: 0, url: String::from("http://localhost/libvsomeip3.so") }];
+
+        let actual_err = summarize_artifact(&client, &artifacts, &String::from("build/libvsomeip3-sd.so")).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,65 @@
+use goblin::elf::sym::{STB_GLOBAL, STB_WEAK, STV_DEFAULT, STV_PROTECTED};
+use goblin::elf::Elf;
+use goblin::elf::section_header::SHN_UNDEF;
+use std::collections::BTreeSet;
+use std::io::{Error,ErrorKind};
+
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElfInfo {
+    pub soname: Option<String>,
+    pub exported_symbols: BTreeSet<String>,
+}
+
+/// Read the SONAME and the exported dynamic symbols, returns None for non ELF files.
+pub fn parse(content: &[u8]) -> Result<Option<ElfInfo>, Error> {
+    if !content.starts_with(ELF_MAGIC) {
+        return Ok(None);
+    }
+    let elf = Elf::parse(content)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("failed to parse ELF file: {}", e)))?;
+
+    let exported_symbols = elf.dynsyms.iter()
+        .filter(|s| s.st_shndx != SHN_UNDEF as usize)
+        .filter(|s| matches!(s.st_bind(), STB_GLOBAL | STB_WEAK))
+        .filter(|s| matches!(s.st_visibility(), STV_DEFAULT | STV_PROTECTED))
+        .filter_map(|s| elf.dynstrtab.get_at(s.st_name))
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect();
+
+    Ok(Some(ElfInfo {
+        soname: elf.soname.map(|soname| soname.to_string()),
+        exported_symbols,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_LIBRARY: &[u8] = include_bytes!("../../testdata/elf/libbase.elf");
+
+    #[test]
+    fn parse_returns_none_for_non_elf_files() {
+        assert_eq!(parse(b"not an elf file").unwrap(), None);
+    }
+
+    #[test]
+    fn parse_fails_on_truncated_elf_files() {
+        let actual_err = parse(&BASE_LIBRARY[..32]).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn parse_returns_soname_and_exported_symbols() {
+        let info = parse(BASE_LIBRARY).unwrap().unwrap();
+
+        assert_eq!(info.soname, Some(String::from("libparity.so.1")));
+        assert!(info.exported_symbols.contains("shared_symbol"));
+        assert!(info.exported_symbols.contains("base_symbol"));
+        assert!(!info.exported_symbols.contains("hidden_symbol"));
+    }
+}
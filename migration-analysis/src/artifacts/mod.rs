@@ -0,0 +1,3 @@
+pub mod comparison;
+pub mod elf;
+pub mod rules;
\ No newline at end of file
@@ -0,0 +1,140 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{Error,ErrorKind};
+
+/// Pairs base and migration artifacts whose paths match the respective regex.
+/// Capture groups form the key used to pair artifacts matched by the same rule,
+/// e.g. {"base": "^build/(libvsomeip3[^/]*)\\.so[.0-9]*$", "migration": "^bazel-bin/(libvsomeip3[^/]*)\\.so$"}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactRule {
+    pub base: String,
+    pub migration: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Pairing {
+    pub pairs: Vec<(String, String)>,
+    pub base_unmatched: Vec<String>,
+    pub migration_unmatched: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PathRules {
+    rules: Vec<(Regex, Regex)>,
+}
+
+impl PathRules {
+    /// Without rules artifacts are paired by file name.
+    pub fn from(rules: &[ArtifactRule]) -> Result<PathRules, Error> {
+        if rules.is_empty() {
+            return PathRules::from(&[ArtifactRule { base: String::from("([^/]+)$"), migration: String::from("([^/]+)$") }]);
+        }
+        let mut compiled = vec![];
+        for rule in rules {
+            compiled.push((compile(&rule.base)?, compile(&rule.migration)?));
+        }
+        Ok(PathRules { rules: compiled })
+    }
+
+    pub fn pair(&self, base_paths: &[String], migration_paths: &[String]) -> Pairing {
+        let mut base_remaining: Vec<String> = base_paths.to_vec();
+        let mut migration_remaining: Vec<String> = migration_paths.to_vec();
+        let mut pairs = vec![];
+
+        for (base_regex, migration_regex) in &self.rules {
+            let base_keys = group_by_key(base_regex, &base_remaining);
+            let migration_keys = group_by_key(migration_regex, &migration_remaining);
+            for (key, base_matches) in base_keys {
+                // Ambiguous keys are left unmatched rather than paired arbitrarily.
+                if let (Some(migration_matches), [base_path]) = (migration_keys.get(&key), base_matches.as_slice()) {
+                    if let [migration_path] = migration_matches.as_slice() {
+                        base_remaining.retain(|p| p != base_path);
+                        migration_remaining.retain(|p| p != migration_path);
+                        pairs.push((base_path.to_string(), migration_path.to_string()));
+                    }
+                }
+            }
+        }
+
+        Pairing {
+            pairs,
+            base_unmatched: base_remaining,
+            migration_unmatched: migration_remaining,
+        }
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, Error> {
+    Regex::new(pattern)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid artifact rule {}: {}", pattern, e)))
+}
+
+fn group_by_key(regex: &Regex, paths: &[String]) -> BTreeMap<String, Vec<String>> {
+    let mut keys: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in paths {
+        if let Some(captures) = regex.captures(path) {
+            let key: Vec<&str> = captures.iter().skip(1).flatten().map(|c| c.as_str()).collect();
+            keys.entry(key.join("/")).or_default().push(path.to_string());
+        }
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_rules_pair_by_file_name_by_default() {
+        let rules = PathRules::from(&[]).unwrap();
+
+        let pairing = rules.pair(
+            &paths(&["build/vsomeipd", "build/only_cmake"]),
+            &paths(&["bazel-bin/vsomeipd", "bazel-bin/only_bazel"]));
+
+        assert_eq!(pairing.pairs, vec![(String::from("build/vsomeipd"), String::from("bazel-bin/vsomeipd"))]);
+        assert_eq!(pairing.base_unmatched, paths(&["build/only_cmake"]));
+        assert_eq!(pairing.migration_unmatched, paths(&["bazel-bin/only_bazel"]));
+    }
+
+    #[test]
+    fn path_rules_pair_by_captured_key() {
+        let rules = PathRules::from(&[ArtifactRule {
+            base: String::from(r"^build/(libvsomeip3[^/]*)\.so[.0-9]*$"),
+            migration: String::from(r"^bazel-bin/(libvsomeip3[^/]*)\.so$"),
+        }]).unwrap();
+
+        let pairing = rules.pair(
+            &paths(&["build/libvsomeip3.so.3.3.0", "build/libvsomeip3-cfg.so.3.3.0"]),
+            &paths(&["bazel-bin/libvsomeip3.so", "bazel-bin/libvsomeip3-cfg.so"]));
+
+        assert_eq!(pairing.pairs, vec![
+            (String::from("build/libvsomeip3.so.3.3.0"), String::from("bazel-bin/libvsomeip3.so")),
+            (String::from("build/libvsomeip3-cfg.so.3.3.0"), String::from("bazel-bin/libvsomeip3-cfg.so")),
+        ]);
+        assert!(pairing.base_unmatched.is_empty());
+        assert!(pairing.migration_unmatched.is_empty());
+    }
+
+    #[test]
+    fn path_rules_leave_ambiguous_matches_unpaired() {
+        let rules = PathRules::from(&[]).unwrap();
+
+        let pairing = rules.pair(&paths(&["a/lib.so", "b/lib.so"]), &paths(&["bazel-bin/lib.so"]));
+
+        assert!(pairing.pairs.is_empty());
+        assert_eq!(pairing.base_unmatched.len(), 2);
+    }
+
+    #[test]
+    fn path_rules_fail_on_invalid_regex() {
+        let actual_err = PathRules::from(&[ArtifactRule { base: String::from("("), migration: String::from("x") }]).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+}
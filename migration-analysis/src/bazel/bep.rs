@@ -0,0 +1,495 @@
+use chrono::{DateTime, Utc};
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::Deserialize;
+use serde_json::Value;
+use crate::report::format_lines;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{Error,ErrorKind};
+
+// Names of the spawn runners counting the actions they executed, see runnerCount in build_event_stream.proto.
+const TOTAL_RUNNER: &str = "total";
+const REMOTE_CACHE_RUNNER: &str = "remote cache hit";
+const DISK_CACHE_RUNNER: &str = "disk cache hit";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildStarted {
+    command: Option<String>,
+    #[serde(default, deserialize_with = "int64")]
+    start_time_millis: i64,
+    start_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExitCode {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildFinished {
+    #[serde(default)]
+    overall_success: bool,
+    exit_code: Option<ExitCode>,
+    #[serde(default, deserialize_with = "int64")]
+    finish_time_millis: i64,
+    finish_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BuildMetrics {
+    action_summary: Option<ActionSummary>,
+    timing_metrics: Option<TimingMetrics>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionSummary {
+    #[serde(default, deserialize_with = "int64")]
+    actions_created: i64,
+    #[serde(default, deserialize_with = "int64")]
+    actions_executed: i64,
+    #[serde(default)]
+    action_data: Vec<ActionData>,
+    #[serde(default)]
+    runner_count: Vec<RunnerCount>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionData {
+    mnemonic: String,
+    #[serde(default, deserialize_with = "int64")]
+    actions_executed: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunnerCount {
+    name: String,
+    #[serde(default, deserialize_with = "int64")]
+    count: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TimingMetrics {
+    #[serde(default, deserialize_with = "int64")]
+    wall_time_in_ms: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionCompletedId {
+    #[serde(default)]
+    primary_output: String,
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActionExecuted {
+    #[serde(default)]
+    success: bool,
+    #[serde(rename = "type", default)]
+    mnemonic: String,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LabelId {
+    label: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TargetComplete {
+    #[serde(default)]
+    success: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TestSummaryPayload {
+    overall_status: Option<String>,
+    #[serde(default, deserialize_with = "int64")]
+    total_run_duration_millis: i64,
+}
+
+/// Number of actions of a mnemonic executed by the build, e.g. CppCompile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MnemonicCount {
+    pub mnemonic: String,
+    pub actions_executed: u64,
+}
+
+/// An action of the build, only failed ones are published without --build_event_publish_all_actions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActionTiming {
+    pub label: String,
+    pub mnemonic: String,
+    pub primary_output: String,
+    pub success: bool,
+    /// Seconds, actions served by a cache or failing before running have none
+    pub duration: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetSummary {
+    pub label: String,
+    pub success: bool,
+    /// Seconds spent in the published actions of the target
+    pub duration: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestSummary {
+    pub label: String,
+    /// e.g. PASSED, FAILED or FLAKY
+    pub status: String,
+    /// Seconds of all the runs and attempts of the test
+    pub duration: f64,
+}
+
+/// What Bazel did in a build, read from the file written with --build_event_json_file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildSummary {
+    pub command: Option<String>,
+    pub success: Option<bool>,
+    pub exit_code: Option<String>,
+    /// Seconds
+    pub wall_time: Option<f64>,
+    pub actions_created: u64,
+    pub actions_executed: u64,
+    pub mnemonics: Vec<MnemonicCount>,
+    /// Actions executed by each spawn runner e.g. "remote cache hit"
+    pub runner_counts: BTreeMap<String, u64>,
+    pub targets: Vec<TargetSummary>,
+    pub actions: Vec<ActionTiming>,
+    pub tests: Vec<TestSummary>,
+}
+
+impl BuildSummary {
+    /// Events are one JSON object per line, events of unknown kinds are skipped.
+    pub fn from(content: &str) -> Result<BuildSummary, Error> {
+        let mut summary = BuildSummary::default();
+        let mut started_at = None;
+        let mut finished_at = None;
+        let mut targets: BTreeMap<String, TargetSummary> = BTreeMap::new();
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: Value = serde_json::from_str(line)
+                .map_err(|e| invalid_event(index, e))?;
+            let kind = match event.get("id").and_then(Value::as_object).and_then(|id| id.keys().next()) {
+                Some(kind) => kind.as_str(),
+                None => continue,
+            };
+            match kind {
+                "started" => {
+                    let started: BuildStarted = payload(&event, "started").map_err(|e| invalid_event(index, e))?;
+                    summary.command = started.command;
+                    started_at = started.start_time.or_else(|| from_millis(started.start_time_millis));
+                },
+                "buildFinished" => {
+                    let finished: BuildFinished = payload(&event, "finished").map_err(|e| invalid_event(index, e))?;
+                    summary.success = Some(finished.overall_success);
+                    summary.exit_code = finished.exit_code.map(|code| code.name);
+                    finished_at = finished.finish_time.or_else(|| from_millis(finished.finish_time_millis));
+                },
+                "buildMetrics" => {
+                    let metrics: BuildMetrics = payload(&event, "buildMetrics").map_err(|e| invalid_event(index, e))?;
+                    summary.add_metrics(metrics);
+                },
+                "actionCompleted" => {
+                    let id: ActionCompletedId = id(&event, kind).map_err(|e| invalid_event(index, e))?;
+                    let action: ActionExecuted = payload(&event, "action").map_err(|e| invalid_event(index, e))?;
+                    let label = id.label.unwrap_or_default();
+                    let duration = match (action.start_time, action.end_time) {
+                        (Some(start), Some(end)) => Some((end - start).num_milliseconds() as f64 / 1000.0),
+                        _ => None,
+                    };
+                    if !label.is_empty() {
+                        let target = targets.entry(label.to_string()).or_insert_with(|| TargetSummary { label: label.to_string(), success: action.success, duration: None });
+                        if let Some(duration) = duration {
+                            target.duration = Some(target.duration.unwrap_or(0.0) + duration);
+                        }
+                    }
+                    summary.actions.push(ActionTiming { label, mnemonic: action.mnemonic, primary_output: id.primary_output, success: action.success, duration });
+                },
+                "targetCompleted" => {
+                    let id: LabelId = id(&event, kind).map_err(|e| invalid_event(index, e))?;
+                    // Targets which couldn't be built are aborted instead of completed.
+                    let success = match event.get("completed") {
+                        Some(_) => payload::<TargetComplete>(&event, "completed").map_err(|e| invalid_event(index, e))?.success,
+                        None => false,
+                    };
+                    targets.entry(id.label.to_string())
+                        .or_insert_with(|| TargetSummary { label: id.label.to_string(), success, duration: None })
+                        .success = success;
+                },
+                "testSummary" => {
+                    let id: LabelId = id(&event, kind).map_err(|e| invalid_event(index, e))?;
+                    let test: TestSummaryPayload = payload(&event, "testSummary").map_err(|e| invalid_event(index, e))?;
+                    summary.tests.push(TestSummary {
+                        label: id.label,
+                        status: test.overall_status.unwrap_or_else(|| String::from("NO_STATUS")),
+                        duration: test.total_run_duration_millis as f64 / 1000.0,
+                    });
+                },
+                _ => {},
+            }
+        }
+
+        if summary.wall_time.is_none() {
+            if let (Some(start), Some(end)) = (started_at, finished_at) {
+                summary.wall_time = Some((end - start).num_milliseconds() as f64 / 1000.0);
+            }
+        }
+        summary.targets = targets.into_values().collect();
+        Ok(summary)
+    }
+
+    pub fn read(path: &str) -> Result<BuildSummary, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::new(e.kind(), format!("failed to read {}: {}", path, e)))?;
+        BuildSummary::from(&content)
+            .map_err(|e| Error::new(e.kind(), format!("failed to parse build events of {}: {}", path, e)))
+    }
+
+    fn add_metrics(&mut self, metrics: BuildMetrics) {
+        if let Some(actions) = metrics.action_summary {
+            self.actions_created = actions.actions_created.max(0) as u64;
+            self.actions_executed = actions.actions_executed.max(0) as u64;
+            self.mnemonics = actions.action_data.into_iter()
+                .map(|data| MnemonicCount { mnemonic: data.mnemonic, actions_executed: data.actions_executed.max(0) as u64 })
+                .collect();
+            self.mnemonics.sort_by(|a, b| b.actions_executed.cmp(&a.actions_executed).then(a.mnemonic.cmp(&b.mnemonic)));
+            self.runner_counts = actions.runner_count.into_iter()
+                .map(|runner| (runner.name, runner.count.max(0) as u64))
+                .collect();
+        }
+        if let Some(timing) = metrics.timing_metrics.filter(|timing| timing.wall_time_in_ms > 0) {
+            self.wall_time = Some(timing.wall_time_in_ms as f64 / 1000.0);
+        }
+    }
+
+    /// Share of the executed actions served by the remote cache.
+    pub fn remote_cache_hit_rate(&self) -> Option<f64> {
+        self.hit_rate(REMOTE_CACHE_RUNNER)
+    }
+
+    /// Share of the executed actions served by the disk cache.
+    pub fn disk_cache_hit_rate(&self) -> Option<f64> {
+        self.hit_rate(DISK_CACHE_RUNNER)
+    }
+
+    fn hit_rate(&self, runner: &str) -> Option<f64> {
+        let total = *self.runner_counts.get(TOTAL_RUNNER).filter(|total| **total > 0)?;
+        Some(self.runner_counts.get(runner).copied().unwrap_or(0) as f64 / total as f64)
+    }
+
+    /// Targets with published actions, from the slowest.
+    pub fn slowest_targets(&self) -> Vec<&TargetSummary> {
+        let mut targets: Vec<&TargetSummary> = self.targets.iter().filter(|t| t.duration.is_some()).collect();
+        targets.sort_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap().then(a.label.cmp(&b.label)));
+        targets
+    }
+
+    fn tests_with_status(&self, status: &str) -> usize {
+        self.tests.iter().filter(|t| t.status == status).count()
+    }
+}
+
+impl fmt::Display for BuildSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mnemonics: Vec<String> = self.mnemonics.iter()
+            .map(|m| format!("{}: {}", m.mnemonic, m.actions_executed))
+            .collect();
+        let targets: Vec<String> = self.slowest_targets().iter()
+            .map(|t| format!("{}: {:.1}s{}", t.label, t.duration.unwrap_or_default(), if t.success { "" } else { " (failed)" }))
+            .collect();
+        let failed_targets: Vec<String> = self.targets.iter()
+            .filter(|t| !t.success)
+            .map(|t| t.label.to_string())
+            .collect();
+        let mut actions: Vec<&ActionTiming> = self.actions.iter().filter(|a| a.duration.is_some()).collect();
+        actions.sort_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap());
+        let actions: Vec<String> = actions.iter()
+            .map(|a| format!("{} {} ({}): {:.1}s", a.mnemonic, a.primary_output, a.label, a.duration.unwrap_or_default()))
+            .collect();
+        let tests: Vec<String> = self.tests.iter()
+            .map(|t| format!("{}: {} in {:.1}s", t.label, t.status, t.duration))
+            .collect();
+
+        let display = format!(r#"
+        Bazel build summary:
+
+        Details:
+
+            command: {}
+            success: {}
+            exit code: {}
+            wall time: {}
+            actions created: {}
+            actions executed: {}
+            remote cache hit rate: {}
+            disk cache hit rate: {}
+
+        Executed actions by mnemonic:
+{}
+        Slowest targets:
+{}
+        Failed targets:
+{}
+        Slowest actions:
+{}
+        Tests ({} passed, {} flaky, {} failed):
+{}"#,
+        self.command.as_deref().unwrap_or("unknown"),
+        self.success.map(|s| s.to_string()).unwrap_or_else(|| String::from("unknown")),
+        self.exit_code.as_deref().unwrap_or("unknown"),
+        self.wall_time.map(|t| format!("{:.1}s", t)).unwrap_or_else(|| String::from("unknown")),
+        self.actions_created,
+        self.actions_executed,
+        format_rate(self.remote_cache_hit_rate()),
+        format_rate(self.disk_cache_hit_rate()),
+        format_lines(&mnemonics),
+        format_lines(&targets),
+        format_lines(&failed_targets),
+        format_lines(&actions),
+        self.tests_with_status("PASSED"),
+        self.tests_with_status("FLAKY"),
+        self.tests.len() - self.tests_with_status("PASSED") - self.tests_with_status("FLAKY"),
+        format_lines(&tests));
+        write!(f, "{}", display)
+    }
+}
+
+// Protobuf int64 values are strings in JSON, older Bazel versions wrote numbers.
+fn int64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(0),
+        Value::Number(number) => number.as_i64().ok_or_else(|| de::Error::custom(format!("{} is not an int64", number))),
+        Value::String(text) => text.parse().map_err(|_| de::Error::custom(format!("{} is not an int64", text))),
+        other => Err(de::Error::custom(format!("{} is not an int64", other))),
+    }
+}
+
+fn from_millis(millis: i64) -> Option<DateTime<Utc>> {
+    if millis <= 0 {
+        return None;
+    }
+    DateTime::from_timestamp_millis(millis)
+}
+
+fn id<T: DeserializeOwned>(event: &Value, kind: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_value(event["id"][kind].clone())
+}
+
+// Events without a payload e.g. of an interrupted build still have one, empty.
+fn payload<T: DeserializeOwned>(event: &Value, key: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_value(event.get(key).cloned().unwrap_or_else(|| Value::Object(Default::default())))
+}
+
+fn invalid_event(index: usize, e: serde_json::Error) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("invalid build event on line {}: {}", index + 1, e))
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    rate.map(|r| format!("{:.1}%", r * 100.0)).unwrap_or_else(|| String::from("unknown"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILD: &str = include_str!("../../testdata/bazel/bep/build.json");
+    const FAILED_BUILD: &str = include_str!("../../testdata/bazel/bep/failed_build.json");
+
+    #[test]
+    fn from_reads_metrics_of_build() {
+        let summary = BuildSummary::from(BUILD).unwrap();
+
+        assert_eq!(summary.command.as_deref(), Some("test"));
+        assert_eq!(summary.success, Some(true));
+        assert_eq!(summary.exit_code.as_deref(), Some("SUCCESS"));
+        assert_eq!(summary.wall_time, Some(118.5));
+        assert_eq!(summary.actions_created, 1240);
+        assert_eq!(summary.actions_executed, 412);
+        assert_eq!(summary.mnemonics[0], MnemonicCount { mnemonic: String::from("CppCompile"), actions_executed: 380 });
+        assert_eq!(summary.mnemonics[1].mnemonic, "Genrule");
+        assert_eq!(summary.remote_cache_hit_rate(), Some(0.5));
+        assert_eq!(summary.disk_cache_hit_rate(), Some(0.25));
+    }
+
+    #[test]
+    fn from_sums_action_durations_of_targets() {
+        let summary = BuildSummary::from(BUILD).unwrap();
+
+        let slowest = summary.slowest_targets();
+        assert_eq!(slowest.len(), 2);
+        assert_eq!(slowest[0], &TargetSummary { label: String::from("//:vsomeip3"), success: true, duration: Some(80.5) });
+        assert_eq!(slowest[1].duration, Some(20.0));
+        assert_eq!(summary.targets.len(), 4);
+        assert_eq!(summary.actions[0].primary_output, "bazel-out/k8-fastbuild/bin/_objs/vsomeip3/routing_manager_impl.pic.o");
+        assert_eq!(summary.actions[0].duration, Some(42.5));
+    }
+
+    #[test]
+    fn from_reads_test_summaries() {
+        let summary = BuildSummary::from(BUILD).unwrap();
+
+        assert_eq!(summary.tests, vec![
+            TestSummary { label: String::from("//test:payload_test"), status: String::from("PASSED"), duration: 1.25 },
+            TestSummary { label: String::from("//test:routing_test"), status: String::from("FLAKY"), duration: 7.5 },
+        ]);
+    }
+
+    #[test]
+    fn from_reads_failed_build_of_older_bazel() {
+        let summary = BuildSummary::from(FAILED_BUILD).unwrap();
+
+        assert_eq!(summary.success, Some(false));
+        assert_eq!(summary.exit_code.as_deref(), Some("BUILD_FAILURE"));
+        // No timing metrics, from the start and finish times
+        assert_eq!(summary.wall_time, Some(90.0));
+        assert_eq!(summary.actions_executed, 35);
+        assert_eq!(summary.remote_cache_hit_rate(), Some(0.0));
+        assert_eq!(summary.disk_cache_hit_rate(), Some(0.0));
+        assert_eq!(summary.targets, vec![
+            TargetSummary { label: String::from("//:vsomeip3"), success: true, duration: None },
+            TargetSummary { label: String::from("//:vsomeip3-e2e"), success: false, duration: None },
+        ]);
+        assert!(!summary.actions[0].success);
+    }
+
+    #[test]
+    fn from_fails_on_invalid_event() {
+        let actual_err = BuildSummary::from("{\"id\":{\"started\":{}},\"started\":{}}\n{\"id\":").unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidData);
+        assert!(actual_err.to_string().starts_with("invalid build event on line 2"));
+    }
+
+    #[test]
+    fn hit_rates_are_unknown_without_runner_counts() {
+        let summary = BuildSummary::default();
+
+        assert!(summary.remote_cache_hit_rate().is_none());
+        assert_eq!(format_rate(summary.disk_cache_hit_rate()), "unknown");
+    }
+
+    #[test]
+    fn display_lists_failed_targets_and_tests() {
+        let display = format!("{}", BuildSummary::from(FAILED_BUILD).unwrap());
+
+        assert!(display.contains("        Failed targets:\n            //:vsomeip3-e2e\n"));
+        assert!(display.contains("            wall time: 90.0s\n"));
+        assert!(display.contains("        Tests (0 passed, 0 flaky, 0 failed):\n            none\n"));
+    }
+}
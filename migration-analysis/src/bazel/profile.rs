@@ -0,0 +1,338 @@
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use crate::report::format_lines;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{Error,ErrorKind,Read};
+
+// Number of slowest actions listed per mnemonic.
+const MAX_LISTED_PER_MNEMONIC: usize = 3;
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const PHASE_CATEGORY: &str = "build phase marker";
+const ACTION_CATEGORY: &str = "action processing";
+const CRITICAL_PATH_CATEGORY: &str = "critical path component";
+// Actions of profiles written before Bazel recorded their mnemonic.
+const UNKNOWN_MNEMONIC: &str = "unknown";
+// Phase markers of each reported phase, loading and analysis interleave since Skyframe and share a marker.
+const LOADING_PHASES: [&str; 2] = ["Initialize command", "Evaluate target patterns"];
+const ANALYSIS_PHASES: [&str; 2] = ["Load and analyze dependencies", "Analyze licenses"];
+const EXECUTION_PHASES: [&str; 2] = ["Prepare for build", "Build artifacts"];
+
+/// Profiles are an object with the events or only the array of events, see the Trace Event Format.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum TraceFile {
+    Object {
+        #[serde(rename = "traceEvents")]
+        trace_events: Vec<TraceEvent>,
+    },
+    Events(Vec<TraceEvent>),
+}
+
+/// An event of the profile, times are in microseconds.
+#[derive(Debug, Deserialize)]
+struct TraceEvent {
+    cat: Option<String>,
+    name: Option<String>,
+    ph: Option<String>,
+    ts: Option<f64>,
+    dur: Option<f64>,
+    args: Option<TraceArgs>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TraceArgs {
+    mnemonic: Option<String>,
+    target: Option<String>,
+}
+
+/// Seconds spent in a phase, from its marker to the next one, or in an action of the critical path.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NamedTime {
+    pub name: String,
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionTime {
+    /// Progress message of the action e.g. "Compiling src/main.cpp"
+    pub description: String,
+    pub mnemonic: String,
+    pub target: Option<String>,
+    /// Seconds
+    pub duration: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MnemonicTime {
+    pub mnemonic: String,
+    pub actions: usize,
+    /// Seconds of all the actions of the mnemonic
+    pub total: f64,
+    pub slowest: Vec<ActionTime>,
+}
+
+/// Where the time of a Bazel command went, read from the profile written with --profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProfileSummary {
+    /// Seconds from the first to the last event
+    pub wall_time: f64,
+    pub loading: f64,
+    pub analysis: f64,
+    pub execution: f64,
+    pub phases: Vec<NamedTime>,
+    /// Actions of the critical path, in execution order
+    pub critical_path: Vec<NamedTime>,
+    pub critical_path_duration: f64,
+    /// Mnemonics from the one whose actions took the longest
+    pub mnemonics: Vec<MnemonicTime>,
+    /// Actions of the profile, left out of the JSON output since profiles have thousands of them
+    #[serde(skip)]
+    pub actions: Vec<ActionTime>,
+}
+
+impl ProfileSummary {
+    /// The content is JSON, gzipped as Bazel writes it when the profile file name ends with .gz.
+    pub fn from(content: &[u8]) -> Result<ProfileSummary, Error> {
+        let trace: TraceFile = if content.starts_with(&GZIP_MAGIC) {
+            serde_json::from_reader(GzDecoder::new(content))
+        } else {
+            serde_json::from_slice(content)
+        }
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("invalid trace profile: {}", e)))?;
+        let events = match trace {
+            TraceFile::Object { trace_events } => trace_events,
+            TraceFile::Events(events) => events,
+        };
+        Ok(ProfileSummary::from_events(&events))
+    }
+
+    pub fn read(path: &str) -> Result<ProfileSummary, Error> {
+        let mut content = vec![];
+        fs::File::open(path)
+            .and_then(|mut file| file.read_to_end(&mut content))
+            .map_err(|e| Error::new(e.kind(), format!("failed to read {}: {}", path, e)))?;
+        ProfileSummary::from(&content)
+            .map_err(|e| Error::new(e.kind(), format!("failed to parse profile {}: {}", path, e)))
+    }
+
+    fn from_events(events: &[TraceEvent]) -> ProfileSummary {
+        let start = events.iter().filter_map(|e| e.ts).fold(f64::INFINITY, f64::min);
+        let end = events.iter().filter_map(|e| Some(e.ts? + e.dur.unwrap_or(0.0))).fold(f64::NEG_INFINITY, f64::max);
+        if start > end {
+            return ProfileSummary::default();
+        }
+
+        let phases = phase_times(events, end);
+        let phase_total = |names: &[&str]| -> f64 {
+            phases.iter().filter(|p| names.contains(&p.name.as_str())).map(|p| p.duration).sum()
+        };
+
+        let critical_path: Vec<NamedTime> = completed(events, CRITICAL_PATH_CATEGORY)
+            .map(|(name, duration, _)| NamedTime { name: critical_path_action(name), duration })
+            .collect();
+
+        let actions: Vec<ActionTime> = completed(events, ACTION_CATEGORY)
+            .map(|(name, duration, args)| ActionTime {
+                description: name.to_string(),
+                mnemonic: args.and_then(|a| a.mnemonic.clone()).unwrap_or_else(|| String::from(UNKNOWN_MNEMONIC)),
+                target: args.and_then(|a| a.target.clone()),
+                duration,
+            })
+            .collect();
+
+        ProfileSummary {
+            wall_time: seconds(end - start),
+            loading: phase_total(&LOADING_PHASES),
+            analysis: phase_total(&ANALYSIS_PHASES),
+            execution: phase_total(&EXECUTION_PHASES),
+            critical_path_duration: critical_path.iter().map(|a| a.duration).sum(),
+            critical_path,
+            mnemonics: mnemonic_times(&actions),
+            phases,
+            actions,
+        }
+    }
+}
+
+// Phase markers are instant events, older profiles recorded some of them with a duration.
+fn phase_times(events: &[TraceEvent], end: f64) -> Vec<NamedTime> {
+    let mut markers: Vec<(&str, f64, Option<f64>)> = events.iter()
+        .filter(|e| e.cat.as_deref() == Some(PHASE_CATEGORY))
+        .filter_map(|e| Some((e.name.as_deref()?, e.ts?, e.dur)))
+        .collect();
+    markers.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    markers.iter().enumerate()
+        .map(|(index, (name, ts, dur))| {
+            let next = markers.get(index + 1).map(|m| m.1).unwrap_or(end);
+            NamedTime { name: name.to_string(), duration: seconds(dur.unwrap_or(next - ts)) }
+        })
+        .collect()
+}
+
+// Complete events of a category with their name, seconds and arguments.
+fn completed<'a>(events: &'a [TraceEvent], category: &'a str) -> impl Iterator<Item = (&'a str, f64, Option<&'a TraceArgs>)> {
+    events.iter()
+        .filter(move |e| e.cat.as_deref() == Some(category) && e.ph.as_deref() == Some("X"))
+        .filter_map(|e| Some((e.name.as_deref()?, seconds(e.dur?), e.args.as_ref())))
+}
+
+fn mnemonic_times(actions: &[ActionTime]) -> Vec<MnemonicTime> {
+    let mut by_mnemonic: BTreeMap<&str, Vec<&ActionTime>> = BTreeMap::new();
+    for action in actions {
+        by_mnemonic.entry(&action.mnemonic).or_default().push(action);
+    }
+    let mut mnemonics: Vec<MnemonicTime> = by_mnemonic.into_iter()
+        .map(|(mnemonic, mut actions)| {
+            actions.sort_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap());
+            MnemonicTime {
+                mnemonic: mnemonic.to_string(),
+                actions: actions.len(),
+                total: actions.iter().map(|a| a.duration).sum(),
+                slowest: actions.into_iter().take(MAX_LISTED_PER_MNEMONIC).cloned().collect(),
+            }
+        })
+        .collect();
+    mnemonics.sort_by(|a, b| b.total.partial_cmp(&a.total).unwrap());
+    mnemonics
+}
+
+// Components of the critical path are named "action 'Compiling src/main.cpp'".
+fn critical_path_action(name: &str) -> String {
+    name.strip_prefix("action '")
+        .and_then(|name| name.strip_suffix('\''))
+        .unwrap_or(name)
+        .to_string()
+}
+
+fn seconds(microseconds: f64) -> f64 {
+    microseconds / 1_000_000.0
+}
+
+impl fmt::Display for ProfileSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let phases: Vec<String> = self.phases.iter()
+            .map(|p| format!("{}: {:.1}s", p.name, p.duration))
+            .collect();
+        let critical_path: Vec<String> = self.critical_path.iter()
+            .map(|a| format!("{}: {:.1}s", a.name, a.duration))
+            .collect();
+        let mnemonics: Vec<String> = self.mnemonics.iter()
+            .map(|m| {
+                let slowest: String = m.slowest.iter()
+                    .map(|a| format!("\n                {}{}: {:.1}s", a.description, a.target.as_ref().map(|t| format!(" ({})", t)).unwrap_or_default(), a.duration))
+                    .collect();
+                format!("{}: {} action(s) in {:.1}s{}", m.mnemonic, m.actions, m.total, slowest)
+            })
+            .collect();
+
+        let display = format!(r#"
+        Bazel profile summary:
+
+        Details:
+
+            wall time: {:.1}s
+            loading: {:.1}s
+            analysis: {:.1}s
+            execution: {:.1}s
+            critical path: {:.1}s
+
+        Phases:
+{}
+        Critical path:
+{}
+        Slowest actions by mnemonic:
+{}"#,
+        self.wall_time,
+        self.loading,
+        self.analysis,
+        self.execution,
+        self.critical_path_duration,
+        format_lines(&phases),
+        format_lines(&critical_path),
+        format_lines(&mnemonics));
+        write!(f, "{}", display)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILE: &[u8] = include_bytes!("../../testdata/bazel/profile/command.profile.json");
+    const GZIPPED_PROFILE: &[u8] = include_bytes!("../../testdata/bazel/profile/command.profile.gz");
+
+    #[test]
+    fn from_reports_phases() {
+        let summary = ProfileSummary::from(PROFILE).unwrap();
+
+        assert_eq!(summary.wall_time, 66.0);
+        assert_eq!(summary.loading, 0.7);
+        assert_eq!(summary.analysis, 4.1);
+        assert_eq!(summary.execution, 60.2);
+        assert_eq!(summary.phases[0], NamedTime { name: String::from("Launch Blaze"), duration: 0.5 });
+        assert_eq!(summary.phases.last().unwrap(), &NamedTime { name: String::from("Complete build"), duration: 0.5 });
+    }
+
+    #[test]
+    fn from_reports_critical_path() {
+        let summary = ProfileSummary::from(PROFILE).unwrap();
+
+        let names: Vec<&str> = summary.critical_path.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec![
+            "Executing genrule //:vsomeip_cfg_h",
+            "Compiling implementation/routing/src/routing_manager_impl.cpp",
+            "Linking libvsomeip3.so",
+        ]);
+        assert_eq!(summary.critical_path_duration, 50.55);
+    }
+
+    #[test]
+    fn from_groups_slowest_actions_by_mnemonic() {
+        let summary = ProfileSummary::from(PROFILE).unwrap();
+
+        assert_eq!(summary.actions.len(), 6);
+        let compile = &summary.mnemonics[0];
+        assert_eq!(compile.mnemonic, "CppCompile");
+        assert_eq!(compile.actions, 3);
+        assert_eq!(compile.total, 92.5);
+        assert_eq!(compile.slowest[0].description, "Compiling implementation/routing/src/routing_manager_impl.cpp");
+        assert_eq!(compile.slowest[0].target.as_deref(), Some("//:vsomeip3"));
+        let mnemonics: Vec<&str> = summary.mnemonics.iter().map(|m| m.mnemonic.as_str()).collect();
+        assert_eq!(mnemonics, vec!["CppCompile", "CppLink", "unknown", "Genrule"]);
+    }
+
+    #[test]
+    fn from_reads_gzipped_profile() {
+        assert_eq!(ProfileSummary::from(GZIPPED_PROFILE).unwrap(), ProfileSummary::from(PROFILE).unwrap());
+    }
+
+    #[test]
+    fn from_reads_array_of_events() {
+        let summary = ProfileSummary::from(br#"[
+            {"cat":"build phase marker","name":"Build artifacts","ph":"i","ts":0},
+            {"cat":"action processing","name":"Linking libvsomeip3.so","ph":"X","ts":1000000,"dur":2000000}
+        ]"#).unwrap();
+
+        assert_eq!(summary.wall_time, 3.0);
+        assert_eq!(summary.execution, 3.0);
+        assert_eq!(summary.mnemonics[0].mnemonic, "unknown");
+    }
+
+    #[test]
+    fn from_fails_on_invalid_profile() {
+        let actual_err = ProfileSummary::from(b"{\"traceEvents\":").unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn json_output_leaves_out_actions() {
+        let json = serde_json::to_value(ProfileSummary::from(PROFILE).unwrap()).unwrap();
+
+        assert!(json.get("actions").is_none());
+        assert_eq!(json["mnemonics"][0]["slowest"][0]["mnemonic"], "CppCompile");
+        assert_eq!(json["critical_path"][2]["name"], "Linking libvsomeip3.so");
+    }
+}
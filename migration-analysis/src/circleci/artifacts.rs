@@ -0,0 +1,8 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    pub path: String,
+    pub node_index: u64,
+    pub url: String,
+}
@@ -0,0 +1,239 @@
+use serde_json::{Map, Value};
+use std::io::{Error,ErrorKind};
+use std::time::{Duration, Instant};
+
+use super::client::Client;
+use super::jobs::Jobs;
+use super::pipelines::{Pipeline, TriggeredPipeline};
+use super::runs::{aggregate_jobs, collect_pipeline_runs};
+
+#[derive(Debug)]
+pub struct BenchSettings {
+    pub branch: String,
+    pub parameters: Map<String, Value>,
+    pub pipelines: usize,
+    pub poll_interval: Duration,
+    pub timeout: Duration,
+}
+
+/// Trigger the benchmark pipelines, wait for them and aggregate their base and migration jobs.
+pub async fn run_bench(client: &Client, settings: &BenchSettings, workflow_name: &String, migration_job_name: &String, base_job_name: &String) -> Result<Jobs, Error> {
+    let triggered = trigger_pipelines(client, settings).await?;
+    let pipelines = wait_for_pipelines(client, &triggered, settings).await?;
+    let runs = collect_pipeline_runs(client, &pipelines, workflow_name, migration_job_name, base_job_name).await?;
+    aggregate_jobs(&runs, migration_job_name, base_job_name)
+}
+
+pub async fn trigger_pipelines(client: &Client, settings: &BenchSettings) -> Result<Vec<TriggeredPipeline>, Error> {
+    let mut triggered = vec![];
+    for _ in 0..settings.pipelines {
+        triggered.push(client.trigger_pipeline(&settings.branch, &settings.parameters).await?);
+    }
+    Ok(triggered)
+}
+
+/// Poll the pipelines until all their workflows are finished.
+pub async fn wait_for_pipelines(client: &Client, triggered: &[TriggeredPipeline], settings: &BenchSettings) -> Result<Vec<Pipeline>, Error> {
+    let started = Instant::now();
+    let mut pending: Vec<String> = triggered.iter().map(|p| p.id.to_string()).collect();
+    let mut finished = vec![];
+    loop {
+        let mut still_pending = vec![];
+        for pipeline_id in pending {
+            let pipeline = client.get_pipeline(&pipeline_id).await?;
+            if pipeline.state == "errored" {
+                return Err(Error::other(format!("pipeline {} errored", pipeline.number)));
+            }
+            let workflows = client.get_pipeline_workflows(&pipeline_id).await?;
+            if !workflows.is_empty() && workflows.iter().all(|w| w.is_finished()) {
+                finished.push(pipeline);
+            } else {
+                still_pending.push(pipeline_id);
+            }
+        }
+        pending = still_pending;
+
+        if pending.is_empty() {
+            finished.sort_by_key(|p| p.number);
+            return Ok(finished);
+        }
+        if started.elapsed() >= settings.timeout {
+            return Err(Error::new(ErrorKind::TimedOut, format!("{} pipeline(s) still running after {}s", pending.len(), settings.timeout.as_secs())));
+        }
+        tokio::time::sleep(settings.poll_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+    struct TriggerResponder {
+        triggered: AtomicUsize,
+    }
+
+    impl Respond for TriggerResponder {
+        fn respond(&self, _: &Request) -> ResponseTemplate {
+            let number = self.triggered.fetch_add(1, Ordering::SeqCst) + 1;
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": format!("pipeline-{}", number),
+                "state": "created",
+                "number": number,
+                "created_at": "2023-10-01T10:00:00Z"
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn run_bench_triggers_waits_and_aggregates_pipelines() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/project/gh/org/project/pipeline"))
+            .respond_with(TriggerResponder { triggered: AtomicUsize::new(0) })
+            .expect(2)
+            .mount(&server)
+            .await;
+        for number in 1..=2 {
+            mount_pipeline(&server, number).await;
+        }
+
+        let jobs = run_bench(&stub_client(&server), &settings(2), &String::from("build"), &String::from("bazel_build"), &String::from("cmake_build")).await.unwrap();
+
+        assert_eq!(jobs.items.len(), 2);
+        assert_eq!(jobs.items[0].name, "cmake_build");
+        assert_eq!(jobs.items[0].metrics.total_runs, 2);
+        assert_eq!(jobs.items[0].metrics.duration_metrics.as_ref().unwrap().mean, 300);
+        assert_eq!(jobs.items[1].name, "bazel_build");
+        assert_eq!(jobs.items[1].metrics.duration_metrics.as_ref().unwrap().mean, 240);
+    }
+
+    #[tokio::test]
+    async fn wait_for_pipelines_times_out() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/pipeline/pipeline-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pipeline_json(1)))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/pipeline/pipeline-1/workflow"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(workflows_json(1, "running")))
+            .mount(&server)
+            .await;
+        let mut settings = settings(1);
+        settings.timeout = Duration::from_millis(30);
+        let triggered = vec![TriggeredPipeline {
+            id: String::from("pipeline-1"),
+            state: String::from("created"),
+            number: 1,
+            created_at: chrono::Utc::now(),
+        }];
+
+        let actual_err = wait_for_pipelines(&stub_client(&server), &triggered, &settings).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::TimedOut);
+    }
+
+    async fn mount_pipeline(server: &MockServer, number: u64) {
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/pipeline/pipeline-{}", number)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(pipeline_json(number)))
+            .mount(server)
+            .await;
+        // The first poll finds the workflow still running.
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/pipeline/pipeline-{}/workflow", number)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(workflows_json(number, "running")))
+            .up_to_n_times(1)
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/pipeline/pipeline-{}/workflow", number)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(workflows_json(number, "success")))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/workflow/workflow-{}/job", number)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    workflow_job_json("cmake_build", "2023-10-01T10:00:00Z", "2023-10-01T10:05:00Z"),
+                    workflow_job_json("bazel_build", "2023-10-01T10:00:00Z", "2023-10-01T10:04:00Z")
+                ],
+                "next_page_token": null
+            })))
+            .mount(server)
+            .await;
+    }
+
+    fn pipeline_json(number: u64) -> Value {
+        serde_json::json!({
+            "id": format!("pipeline-{}", number),
+            "project_slug": "gh/org/project",
+            "number": number,
+            "state": "created",
+            "created_at": "2023-10-01T10:00:00Z",
+            "trigger": {"type": "api", "received_at": "2023-10-01T10:00:00Z", "actor": {"login": "bench"}},
+            "vcs": {
+                "provider_name": "GitHub",
+                "origin_repository_url": "https://github.com/org/project",
+                "target_repository_url": "https://github.com/org/project",
+                "revision": "abc",
+                "branch": "main"
+            }
+        })
+    }
+
+    fn workflows_json(number: u64, status: &str) -> Value {
+        serde_json::json!({
+            "items": [{
+                "id": format!("workflow-{}", number),
+                "name": "build",
+                "pipeline_id": format!("pipeline-{}", number),
+                "pipeline_number": number,
+                "project_slug": "gh/org/project",
+                "status": status,
+                "started_by": "bench",
+                "created_at": "2023-10-01T10:00:00Z",
+                "stopped_at": null
+            }],
+            "next_page_token": null
+        })
+    }
+
+    fn workflow_job_json(name: &str, started_at: &str, stopped_at: &str) -> Value {
+        serde_json::json!({
+            "id": format!("{}-id", name),
+            "name": name,
+            "job_number": 1,
+            "project_slug": "gh/org/project",
+            "status": "success",
+            "type": "build",
+            "dependencies": [],
+            "started_at": started_at,
+            "stopped_at": stopped_at
+        })
+    }
+
+    fn settings(pipelines: usize) -> BenchSettings {
+        BenchSettings {
+            branch: String::from("main"),
+            parameters: Map::new(),
+            pipelines,
+            poll_interval: Duration::from_millis(10),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    fn stub_client(server: &MockServer) -> Client {
+        Client::from(
+            &String::from("gh/org"),
+            &String::from("project"),
+            &String::from("build"),
+            &String::from("last-90-days"),
+            &String::from("token"),
+        ).unwrap().with_api_url(&server.uri())
+    }
+}
@@ -1,8 +1,24 @@
-use reqwest::header::HeaderMap;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::RequestBuilder;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
 use std::io::{Error,ErrorKind};
 
+use crate::config::secret::Secret;
+use crate::http::query_value;
+use super::artifacts::Artifact;
+use super::flaky_tests::FlakyTests;
+use super::job_details::JobDetails;
+use super::job_tests::JobTest;
 use super::jobs::Jobs;
+use super::org::OrgSummary;
+use super::pipelines::{Page, Pipeline, TriggerPipelineRequest, TriggeredPipeline, Workflow, WorkflowJob};
+use super::steps::JobSteps;
+use super::test_metrics::TestMetrics;
+use super::workflows::WorkflowSummary;
 
+const DEFAULT_API_URL: &str = "https://circleci.com/api";
 
 #[derive(Debug)]
 pub struct Client {
@@ -10,11 +26,13 @@ pub struct Client {
     pub project: String,
     pub workflow: String,
     pub reporting_window: String,
-    pub circleci_token: String,
+    pub circleci_token: Secret,
+    pub api_url: String,
+    http: reqwest::Client,
 }
 
 impl Client {
-    pub fn from(git_slug: &String, project: &String, workflow: &String, reporting_window: &String, circleci_token: &String) -> Result<Client, Error> {
+    pub fn from(git_slug: &String, project: &String, workflow: &String, reporting_window: &String, circleci_token: &str) -> Result<Client, Error> {
         if git_slug.is_empty() {
             return Err(Error::new(ErrorKind::InvalidInput, "git_slug is empty"))
         }
@@ -40,45 +58,332 @@ impl Client {
             project: project.to_string(),
             workflow: workflow.to_string(),
             reporting_window: reporting_window.to_string(),
-            circleci_token: circleci_token.to_string(),
+            circleci_token: Secret::from(circleci_token),
+            api_url: DEFAULT_API_URL.to_string(),
+            http: reqwest::Client::new(),
         })
     }
 
+    /// Send the requests to another CircleCI server, e.g. a self-hosted one.
+    pub fn with_api_url(mut self, api_url: &str) -> Client {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// A client for another workflow, possibly of another project of the organization.
+    pub fn for_workflow(&self, project: &String, workflow: &String) -> Client {
+        Client {
+            git_slug: self.git_slug.to_string(),
+            project: project.to_string(),
+            workflow: workflow.to_string(),
+            reporting_window: self.reporting_window.to_string(),
+            circleci_token: self.circleci_token.clone(),
+            api_url: self.api_url.to_string(),
+            http: self.http.clone(),
+        }
+    }
+
+    /// Send the requests with a client configured for the network, e.g. its proxy.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Client {
+        self.http = http;
+        self
+    }
+
     fn jobs_url(&self) -> String {
         let url = format!(
-            "https://circleci.com/api/v2/insights/{}/{}/workflows/{}/jobs?branch=main&reporting-window={}",
+            "{}/v2/insights/{}/{}/workflows/{}/jobs?branch=main&reporting-window={}",
+            self.api_url,
             self.git_slug,
             self.project,
             self.workflow,
             self.reporting_window
         );
-        return url.to_string();
+        url.to_string()
+    }
+
+    fn workflows_url(&self) -> String {
+        format!(
+            "{}/v2/insights/{}/{}/workflows?reporting-window={}",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            self.reporting_window
+        )
+    }
+
+    fn org_summary_url(&self) -> String {
+        format!(
+            "{}/v2/insights/{}/summary?reporting-window={}",
+            self.api_url,
+            self.git_slug,
+            self.reporting_window
+        )
+    }
+
+    fn test_metrics_url(&self) -> String {
+        format!(
+            "{}/v2/insights/{}/{}/workflows/{}/test-metrics?branch=main",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            self.workflow
+        )
+    }
+
+    fn flaky_tests_url(&self) -> String {
+        format!(
+            "{}/v2/insights/{}/{}/flaky-tests",
+            self.api_url,
+            self.git_slug,
+            self.project
+        )
+    }
+
+    fn pipelines_url(&self, branch: &str) -> String {
+        format!(
+            "{}/v2/project/{}/{}/pipeline?branch={}",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            query_value(branch)
+        )
+    }
+
+    fn trigger_pipeline_url(&self) -> String {
+        format!(
+            "{}/v2/project/{}/{}/pipeline",
+            self.api_url,
+            self.git_slug,
+            self.project
+        )
+    }
+
+    fn pipeline_url(&self, pipeline_id: &String) -> String {
+        format!("{}/v2/pipeline/{}", self.api_url, pipeline_id)
+    }
+
+    fn pipeline_workflows_url(&self, pipeline_id: &String) -> String {
+        format!("{}/v2/pipeline/{}/workflow", self.api_url, pipeline_id)
+    }
+
+    fn workflow_jobs_url(&self, workflow_id: &String) -> String {
+        format!("{}/v2/workflow/{}/job", self.api_url, workflow_id)
+    }
+
+    fn job_details_url(&self, job_number: u64) -> String {
+        format!(
+            "{}/v2/project/{}/{}/job/{}",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            job_number
+        )
+    }
+
+    fn job_steps_url(&self, job_number: u64) -> String {
+        format!(
+            "{}/v1.1/project/{}/{}/{}",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            job_number
+        )
+    }
+
+    fn artifacts_url(&self, job_number: u64) -> String {
+        format!(
+            "{}/v2/project/{}/{}/{}/artifacts",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            job_number
+        )
+    }
+
+    fn job_tests_url(&self, job_number: u64) -> String {
+        format!(
+            "{}/v2/project/{}/{}/{}/tests",
+            self.api_url,
+            self.git_slug,
+            self.project,
+            job_number
+        )
     }
 
     fn headers(&self) -> HeaderMap {
-        let auth_header = format!("circle-token {}", &self.circleci_token);
+        let auth_header = format!("circle-token {}", self.circleci_token.expose());
         let mut headers = HeaderMap::new();
-        headers.insert("authorization", auth_header.parse().unwrap());
-        return headers;
+        headers.insert("authorization", sensitive(&auth_header));
+        headers
     }
 
-    pub async fn get_jobs(&self) -> Jobs {
-        let client = reqwest::Client::new();
-        
-        return client.get(self.jobs_url())
-            .headers(self.headers()) 
+    // The v1.1 API only accepts the token through the circle-token header.
+    fn v1_headers(&self) -> HeaderMap {
+        let mut headers = self.headers();
+        headers.insert("circle-token", sensitive(self.circleci_token.expose()));
+        headers
+    }
+
+    /// Retrieve the jobs of the workflow from all pages, items which can't be decoded are reported in the jobs.
+    pub async fn get_jobs(&self) -> Result<Jobs, Error> {
+        let values: Vec<Value> = self.get_pages(self.jobs_url(), None).await?;
+        Ok(Jobs::from_values(values))
+    }
+
+    pub async fn get_workflows(&self) -> Result<Vec<WorkflowSummary>, Error> {
+        self.get_pages(self.workflows_url(), None).await
+    }
+
+    /// Retrieve the insights summary of the organization owning the project.
+    pub async fn get_org_summary(&self) -> Result<OrgSummary, Error> {
+        self.get(self.org_summary_url()).await
+    }
+
+    pub async fn get_test_metrics(&self) -> Result<TestMetrics, Error> {
+        self.get(self.test_metrics_url()).await
+    }
+
+    pub async fn get_flaky_tests(&self) -> Result<FlakyTests, Error> {
+        self.get(self.flaky_tests_url()).await
+    }
+
+    /// Retrieve the most recent pipelines of a branch, newest first.
+    pub async fn get_pipelines(&self, branch: &str, max_pipelines: usize) -> Result<Vec<Pipeline>, Error> {
+        self.get_pages(self.pipelines_url(branch), Some(max_pipelines)).await
+    }
+
+    /// Retrieve the pipelines of a branch created since a date, newest first.
+    pub async fn get_pipelines_since(&self, branch: &str, since: DateTime<Utc>) -> Result<Vec<Pipeline>, Error> {
+        let mut pipelines: Vec<Pipeline> = self.get_pages_until(self.pipelines_url(branch), |pipelines: &[Pipeline]| {
+            pipelines.last().is_some_and(|p| p.created_at < since)
+        }).await?;
+        pipelines.retain(|p| p.created_at >= since);
+        Ok(pipelines)
+    }
+
+    pub async fn get_pipeline(&self, pipeline_id: &String) -> Result<Pipeline, Error> {
+        self.get(self.pipeline_url(pipeline_id)).await
+    }
+
+    pub async fn trigger_pipeline(&self, branch: &String, parameters: &Map<String, Value>) -> Result<TriggeredPipeline, Error> {
+        let url = self.trigger_pipeline_url();
+        let body = TriggerPipelineRequest {
+            branch: branch.to_string(),
+            parameters: parameters.clone(),
+        };
+        self.send(self.http.post(&url).json(&body), url).await
+    }
+
+    pub async fn get_pipeline_workflows(&self, pipeline_id: &String) -> Result<Vec<Workflow>, Error> {
+        self.get_pages(self.pipeline_workflows_url(pipeline_id), None).await
+    }
+
+    pub async fn get_workflow_jobs(&self, workflow_id: &String) -> Result<Vec<WorkflowJob>, Error> {
+        self.get_pages(self.workflow_jobs_url(workflow_id), None).await
+    }
+
+    pub async fn get_job_details(&self, job_number: u64) -> Result<JobDetails, Error> {
+        self.get(self.job_details_url(job_number)).await
+    }
+
+    pub async fn get_job_steps(&self, job_number: u64) -> Result<JobSteps, Error> {
+        let url = self.job_steps_url(job_number);
+        self.send(self.http.get(&url).headers(self.v1_headers()), url).await
+    }
+
+    pub async fn get_job_tests(&self, job_number: u64) -> Result<Vec<JobTest>, Error> {
+        self.get_pages(self.job_tests_url(job_number), None).await
+    }
+
+    pub async fn get_artifacts(&self, job_number: u64) -> Result<Vec<Artifact>, Error> {
+        self.get_pages(self.artifacts_url(job_number), None).await
+    }
+
+    pub async fn download_artifact(&self, artifact: &Artifact) -> Result<Vec<u8>, Error> {
+        let response = self.http.get(&artifact.url)
+            .headers(self.headers())
             .send()
             .await
-            .expect("failed to retrieve response")
-            .json::<Jobs>()
+            .map_err(|e| Error::other(format!("failed to download artifact {}: {}", &artifact.path, e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::other(format!("download of artifact {} failed with status {}", &artifact.path, response.status())));
+        }
+
+        let content = response.bytes()
+            .await
+            .map_err(|e| Error::other(format!("failed to download artifact {}: {}", &artifact.path, e)))?;
+        Ok(content.to_vec())
+    }
+
+    async fn get_pages<T: DeserializeOwned>(&self, url: String, max_items: Option<usize>) -> Result<Vec<T>, Error> {
+        let mut items = self.get_pages_until(url, |items: &[T]| max_items.is_some_and(|max| items.len() >= max)).await?;
+        if let Some(max) = max_items {
+            items.truncate(max);
+        }
+        Ok(items)
+    }
+
+    /// Follow the pages until there are no more or the items retrieved so far are enough.
+    async fn get_pages_until<T: DeserializeOwned>(&self, url: String, enough: impl Fn(&[T]) -> bool) -> Result<Vec<T>, Error> {
+        let mut items = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let page_url = match &page_token {
+                Some(token) => with_page_token(&url, token),
+                None => url.to_string(),
+            };
+            let page: Page<T> = self.get(page_url).await?;
+            items.extend(page.items);
+
+            if enough(&items) {
+                return Ok(items);
+            }
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        self.send(self.http.get(&url), url).await
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder, url: String) -> Result<T, Error> {
+        let response = request
+            .headers(self.headers())
+            .send()
             .await
-            .expect("failed to deserialize response"); 
+            .map_err(|e| Error::other(format!("failed to retrieve response from {}: {}", &url, e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::other(format!("request to {} failed with status {}", &url, response.status())));
+        }
+
+        response.json::<T>()
+            .await
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("failed to deserialize response from {}: {}", &url, e)))
     }
 }
 
+// Sensitive values are redacted when the headers are printed.
+fn sensitive(value: &str) -> HeaderValue {
+    let mut header: HeaderValue = value.parse().unwrap();
+    header.set_sensitive(true);
+    header
+}
+
+fn with_page_token(url: &String, page_token: &str) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}page-token={}", url, separator, query_value(page_token))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
     fn initialisation_fails_if_git_slug_is_omitted() {
@@ -211,7 +516,22 @@ mod tests {
         assert_eq!(&circleci.project, &project);
         assert_eq!(&circleci.workflow, &workflow);
         assert_eq!(&circleci.reporting_window, &reporting_window);
-        assert_eq!(&circleci.circleci_token, &circleci_token);
+        assert_eq!(circleci.circleci_token.expose(), &circleci_token);
+    }
+
+    #[test]
+    fn debug_client_redacts_circleci_token() {
+        let circleci = Client::from(
+            &String::from("git_slug"),
+            &String::from("project"),
+            &String::from("workflow"),
+            &String::from("reporting_window"),
+            &String::from("very-secret-token"),
+        ).unwrap();
+
+        let debug = format!("{:?} {:?}", circleci, circleci.headers());
+
+        assert!(!debug.contains("very-secret-token"));
     }
 
     #[test]
@@ -234,6 +554,408 @@ mod tests {
         assert_eq!(url, format!("https://circleci.com/api/v2/insights/{}/{}/workflows/{}/jobs?branch=main&reporting-window={}",&git_slug,&project,&workflow,&reporting_window)); 
     }
 
+    #[test]
+    fn workflows_url_returns_concatenated_workflows_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.workflows_url();
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/insights/{}/{}/workflows?reporting-window={}",&git_slug,&project,&reporting_window)); 
+    }
+
+    #[test]
+    fn org_summary_url_returns_concatenated_org_summary_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.org_summary_url();
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/insights/{}/summary?reporting-window={}",&git_slug,&reporting_window)); 
+    }
+
+    #[test]
+    fn for_workflow_keeps_organization_and_token() {
+        let circleci = Client::from(
+            &String::from("git_slug"),
+            &String::from("project"),
+            &String::from("workflow"),
+            &String::from("reporting_window"),
+            &String::from("cicleci_token"),
+        ).unwrap().with_api_url("http://localhost:8080/api");
+
+        let other = circleci.for_workflow(&String::from("other"), &String::from("nightly"));
+
+        assert_eq!(other.jobs_url(), "http://localhost:8080/api/v2/insights/git_slug/other/workflows/nightly/jobs?branch=main&reporting-window=reporting_window");
+        assert_eq!(other.circleci_token.expose(), "cicleci_token");
+    }
+
+    #[test]
+    fn test_metrics_url_returns_concatenated_test_metrics_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.test_metrics_url();
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/insights/{}/{}/workflows/{}/test-metrics?branch=main",&git_slug,&project,&workflow)); 
+    }
+
+    #[test]
+    fn flaky_tests_url_returns_concatenated_flaky_tests_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.flaky_tests_url();
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/insights/{}/{}/flaky-tests",&git_slug,&project)); 
+    }
+
+    #[test]
+    fn pipelines_url_returns_concatenated_pipelines_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.pipelines_url(&String::from("main"));
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/project/{}/{}/pipeline?branch=main",&git_slug,&project)); 
+    }
+
+    #[test]
+    fn job_details_url_returns_concatenated_job_details_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.job_details_url(42);
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/project/{}/{}/job/42",&git_slug,&project)); 
+    }
+
+    #[test]
+    fn job_steps_url_returns_concatenated_v1_job_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.job_steps_url(42);
+
+        assert_eq!(url, format!("https://circleci.com/api/v1.1/project/{}/{}/42",&git_slug,&project)); 
+    }
+
+    #[test]
+    fn artifacts_url_returns_concatenated_artifacts_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.artifacts_url(42);
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/project/{}/{}/42/artifacts",&git_slug,&project)); 
+    }
+
+    #[test]
+    fn job_tests_url_returns_concatenated_job_tests_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let url = circleci.job_tests_url(42);
+
+        assert_eq!(url, format!("https://circleci.com/api/v2/project/{}/{}/42/tests",&git_slug,&project)); 
+    }
+
+    #[test]
+    fn with_api_url_replaces_circleci_api_url() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap().with_api_url(&String::from("http://localhost:8080/api/"));
+
+        let url = circleci.trigger_pipeline_url();
+
+        assert_eq!(url, format!("http://localhost:8080/api/v2/project/{}/{}/pipeline",&git_slug,&project)); 
+    }
+
+    #[tokio::test]
+    async fn trigger_pipeline_posts_branch_and_parameters() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/project/gh/org/project/pipeline"))
+            .and(header("authorization", "circle-token token"))
+            .and(body_json(serde_json::json!({"branch": "main", "parameters": {"bench": true}})))
+            .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!({
+                "id": "pipeline-id",
+                "state": "pending",
+                "number": 12,
+                "created_at": "2023-10-01T10:00:00Z"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let parameters = serde_json::json!({"bench": true}).as_object().unwrap().clone();
+
+        let pipeline = stub_client(&server).trigger_pipeline(&String::from("main"), &parameters).await.unwrap();
+
+        assert_eq!(pipeline.id, "pipeline-id");
+        assert_eq!(pipeline.number, 12);
+    }
+
+    #[tokio::test]
+    async fn get_pages_follows_next_page_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/project/gh/org/project/1/tests"))
+            .and(query_param("page-token", "next"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"name": "second", "classname": "Suite", "result": "success", "message": null, "run_time": 0.1, "source": "gtest", "file": null}],
+                "next_page_token": null
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/project/gh/org/project/1/tests"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{"name": "first", "classname": "Suite", "result": "success", "message": null, "run_time": 0.1, "source": "gtest", "file": null}],
+                "next_page_token": "next"
+            })))
+            .mount(&server)
+            .await;
+
+        let tests = stub_client(&server).get_job_tests(1).await.unwrap();
+
+        let names: Vec<String> = tests.into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec![String::from("first"), String::from("second")]);
+    }
+
+    #[tokio::test]
+    async fn get_workflows_returns_workflow_summaries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/insights/gh/org/project/workflows"))
+            .and(query_param("reporting-window", "last-90-days"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [{
+                    "name": "build",
+                    "metrics": {
+                        "total_runs": 10,
+                        "successful_runs": 9,
+                        "mttr": 100,
+                        "total_credits_used": 500,
+                        "failed_runs": 1,
+                        "median_credits_used": 50,
+                        "success_rate": 0.9,
+                        "duration_metrics": {"min": 10, "mean": 20, "median": 20, "p95": 30, "max": 40, "standard_deviation": 5.0},
+                        "total_recoveries": 1,
+                        "throughput": 0.5
+                    },
+                    "window_start": "2023-07-01T10:00:00Z",
+                    "window_end": "2023-10-01T10:00:00Z",
+                    "project_id": "id"
+                }, {
+                    "name": "nightly",
+                    "metrics": {
+                        "total_runs": 2,
+                        "successful_runs": 0,
+                        "failed_runs": 2,
+                        "success_rate": null,
+                        "total_credits_used": null,
+                        "duration_metrics": {"min": null, "mean": null, "median": null, "p95": null, "max": null}
+                    },
+                    "window_start": "2023-07-01T10:00:00Z",
+                    "window_end": "2023-10-01T10:00:00Z"
+                }],
+                "next_page_token": null
+            })))
+            .mount(&server)
+            .await;
+
+        let workflows = stub_client(&server).get_workflows().await.unwrap();
+
+        assert_eq!(workflows.len(), 2);
+        assert_eq!(workflows[0].name, "build");
+        assert_eq!(workflows[0].metrics.duration_metrics.as_ref().unwrap().median, 20);
+        assert_eq!(workflows[1].metrics.success_rate, 0.0);
+        assert!(workflows[1].metrics.duration_metrics.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_pipelines_since_stops_at_older_pipelines() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/project/gh/org/project/pipeline"))
+            .and(query_param("page-token", "next"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [pipeline_json(2, "2023-09-20T10:00:00Z"), pipeline_json(1, "2023-09-10T10:00:00Z")],
+                "next_page_token": "never-requested"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/project/gh/org/project/pipeline"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [pipeline_json(3, "2023-10-01T10:00:00Z")],
+                "next_page_token": "next"
+            })))
+            .mount(&server)
+            .await;
+        let since = DateTime::parse_from_rfc3339("2023-09-15T00:00:00Z").unwrap().with_timezone(&Utc);
+
+        let pipelines = stub_client(&server).get_pipelines_since(&String::from("main"), since).await.unwrap();
+
+        let numbers: Vec<u64> = pipelines.iter().map(|p| p.number).collect();
+        assert_eq!(numbers, vec![3, 2]);
+    }
+
+    fn pipeline_json(number: u64, created_at: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": format!("pipeline-{}", number),
+            "project_slug": "gh/org/project",
+            "number": number,
+            "state": "created",
+            "created_at": created_at,
+            "trigger": {"type": "webhook", "received_at": created_at, "actor": {"login": "dev"}},
+            "vcs": null
+        })
+    }
+
+    #[tokio::test]
+    async fn get_fails_on_unsuccessful_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let actual_err = stub_client(&server).get_pipeline(&String::from("unknown")).await.unwrap_err();
+
+        assert!(actual_err.to_string().contains("failed with status 404"));
+    }
+
+    fn stub_client(server: &MockServer) -> Client {
+        Client::from(
+            &String::from("gh/org"),
+            &String::from("project"),
+            &String::from("workflow"),
+            &String::from("last-90-days"),
+            &String::from("token"),
+        ).unwrap().with_api_url(&server.uri())
+    }
+
+    #[test]
+    fn pipelines_url_and_page_token_encode_query_values() {
+        let client = Client::from(&String::from("gh/org"), &String::from("project"), &String::from("workflow"), &String::from("last-90-days"), "token").unwrap();
+
+        let url = client.pipelines_url(&String::from("feature/a&b"));
+
+        assert_eq!(url, "https://circleci.com/api/v2/project/gh/org/project/pipeline?branch=feature%2Fa%26b");
+        assert_eq!(with_page_token(&url, &String::from("a+b/c==")), format!("{}&page-token=a%2Bb%2Fc%3D%3D", url));
+    }
+
+    #[test]
+    fn with_page_token_appends_page_token_query_parameter() {
+        let url_with_query = String::from("https://circleci.com/api/v2/project/gh/org/project/pipeline?branch=main");
+        let url_without_query = String::from("https://circleci.com/api/v2/workflow/id/job");
+        let page_token = String::from("token");
+
+        assert_eq!(with_page_token(&url_with_query, &page_token), format!("{}&page-token=token", &url_with_query));
+        assert_eq!(with_page_token(&url_without_query, &page_token), format!("{}?page-token=token", &url_without_query));
+    }
+
     #[test]
     fn headers_return_headers_containing_authorization() {
         let git_slug = String::from("git_slug");
@@ -256,4 +978,47 @@ mod tests {
         assert_eq!(format!("circle-token {}", circleci_token), header_string);
     }
 
+    #[test]
+    fn headers_return_headers_containing_circle_token() {
+        let git_slug = String::from("git_slug");
+        let project = String::from("project"); 
+        let workflow = String::from("workflow");
+        let reporting_window = String::from("reporting_window"); 
+        let circleci_token = String::from("cicleci_token");
+        let circleci = Client::from(
+            &git_slug,
+            &project,
+            &workflow,
+            &reporting_window,
+            &circleci_token,
+        ).unwrap();
+
+        let token_header = circleci.v1_headers().get("circle-token").cloned().unwrap();
+
+        assert_eq!(circleci_token, token_header.to_str().unwrap());
+        assert!(circleci.headers().get("circle-token").is_none());
+    }
+
+    #[tokio::test]
+    async fn get_job_steps_sends_circle_token_only_to_v1_api() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1.1/project/gh/org/project/42"))
+            .and(header("circle-token", "token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"build_num": 42, "steps": []})))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/project/gh/org/project/42/tests"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({"items": [], "next_page_token": null})))
+            .mount(&server)
+            .await;
+        let client = stub_client(&server);
+
+        assert_eq!(client.get_job_steps(42).await.unwrap().build_num, 42);
+        assert!(client.get_job_tests(42).await.unwrap().is_empty());
+        let requests = server.received_requests().await.unwrap();
+        let v2_request = requests.iter().find(|r| r.url.path().starts_with("/v2/")).unwrap();
+        assert!(v2_request.headers.keys().all(|name| name.as_str() != "circle-token"));
+    }
 }
@@ -0,0 +1,129 @@
+use serde::de::DeserializeOwned;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::fmt;
+
+/// An item of a response which couldn't be decoded, the other items are kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeFailure {
+    pub index: usize,
+    pub name: Option<String>,
+    pub error: String,
+}
+
+impl fmt::Display for DecodeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "skipped item {} ({}) of the response: {}", self.index, name, self.error),
+            None => write!(f, "skipped item {} of the response: {}", self.index, self.error),
+        }
+    }
+}
+
+/// Decode the items one by one so that a single unexpected item doesn't fail the whole response.
+pub fn decode_items<T: DeserializeOwned>(values: Vec<Value>) -> (Vec<T>, Vec<DecodeFailure>) {
+    let mut items = vec![];
+    let mut failures = vec![];
+    for (index, value) in values.into_iter().enumerate() {
+        let name = value.get("name").and_then(|n| n.as_str()).map(|n| n.to_string());
+        match serde_json::from_value(value) {
+            Ok(item) => items.push(item),
+            Err(e) => failures.push(DecodeFailure { index, name, error: e.to_string() }),
+        }
+    }
+    (items, failures)
+}
+
+/// Missing and null values are decoded as the default value.
+pub fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Missing and null values, and objects whose fields are all null, are decoded as None.
+/// Other values which can't be decoded fail with the name of the type they were decoded to.
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = Value::deserialize(deserializer)?;
+    let is_null = match &value {
+        Value::Null => true,
+        Value::Object(fields) => fields.values().all(Value::is_null),
+        _ => false,
+    };
+    if is_null {
+        return Ok(None);
+    }
+    let type_name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+    serde_json::from_value(value)
+        .map(Some)
+        .map_err(|e| D::Error::custom(format!("invalid {}: {}", type_name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Named {
+        name: String,
+        #[serde(default, deserialize_with = "null_as_default")]
+        count: u64,
+        #[serde(default, deserialize_with = "nullable")]
+        nested: Option<Nested>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Nested {
+        value: u64,
+    }
+
+    #[test]
+    fn decode_items_keeps_decodable_items() {
+        let values = vec![
+            serde_json::json!({"name": "first", "count": 1, "nested": {"value": 2}}),
+            serde_json::json!({"name": 3}),
+            serde_json::json!({"name": "third", "count": null, "nested": {"value": null}}),
+        ];
+
+        let (items, failures): (Vec<Named>, Vec<DecodeFailure>) = decode_items(values);
+
+        assert_eq!(items, vec![
+            Named { name: String::from("first"), count: 1, nested: Some(Nested { value: 2 }) },
+            Named { name: String::from("third"), count: 0, nested: None },
+        ]);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].index, 1);
+        assert_eq!(failures[0].name, None);
+    }
+
+    #[test]
+    fn decode_items_report_malformed_nullable_values() {
+        let values = vec![
+            serde_json::json!({"name": "first", "nested": {"value": "2"}}),
+            serde_json::json!({"name": "second", "nested": null}),
+            serde_json::json!({"name": "third"}),
+        ];
+
+        let (items, failures): (Vec<Named>, Vec<DecodeFailure>) = decode_items(values);
+
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.nested.is_none()));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].name, Some(String::from("first")));
+        assert!(failures[0].error.starts_with("invalid Nested: invalid type: string"), "{}", failures[0].error);
+    }
+
+    #[test]
+    fn display_decode_failure_names_item() {
+        let failure = DecodeFailure { index: 2, name: Some(String::from("bazel_build")), error: String::from("invalid type") };
+
+        assert_eq!(format!("{}", failure), "skipped item 2 (bazel_build) of the response: invalid type");
+    }
+}
@@ -0,0 +1,172 @@
+use super::flaky_tests::{FlakyTest, FlakyTests};
+use super::parity::test_identifier;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlakySummary {
+    pub test: String,
+    pub times_flaked: u64,
+    pub time_wasted: u64,
+}
+
+#[derive(Debug)]
+pub struct FlakyReport {
+    pub workflow: String,
+    pub base_job: String,
+    pub migration_job: String,
+    pub base: Vec<FlakySummary>,
+    pub migration: Vec<FlakySummary>,
+    pub other_jobs: BTreeMap<String, Vec<FlakySummary>>,
+}
+
+impl FlakyReport {
+    pub fn from(workflow: &String, migration_job_name: &String, base_job_name: &String, flaky_tests: &FlakyTests) -> FlakyReport {
+        let mut by_job = group_by_job(flaky_tests.flaky_tests.iter().filter(|t| &t.workflow_name == workflow));
+
+        FlakyReport {
+            workflow: String::from(workflow),
+            base_job: String::from(base_job_name),
+            migration_job: String::from(migration_job_name),
+            base: by_job.remove(base_job_name).unwrap_or_default(),
+            migration: by_job.remove(migration_job_name).unwrap_or_default(),
+            other_jobs: by_job,
+        }
+    }
+
+    /// Flaky tests of the migration job that are not flaky in the base job.
+    pub fn introduced_by_migration(&self) -> Vec<&FlakySummary> {
+        self.migration.iter()
+            .filter(|m| !self.base.iter().any(|b| b.test == m.test))
+            .collect()
+    }
+}
+
+impl fmt::Display for FlakyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let introduced: Vec<FlakySummary> = self.introduced_by_migration().into_iter().cloned().collect();
+        let other_jobs: String = self.other_jobs.iter()
+            .map(|(job, tests)| format!("\n        {}:\n{}", job, format_flaky_tests(tests)))
+            .collect();
+
+        let display = format!(r#"
+        Flaky tests analysis:
+
+        Details:
+
+            workflow: {}
+            base job: {} ({} flaky tests)
+            migration job: {} ({} flaky tests)
+
+        Flaky in {}:
+{}
+        Flaky in {}:
+{}
+        Only flaky in {}:
+{}{}"#,
+        self.workflow,
+        self.base_job,
+        self.base.len(),
+        self.migration_job,
+        self.migration.len(),
+        self.base_job,
+        format_flaky_tests(&self.base),
+        self.migration_job,
+        format_flaky_tests(&self.migration),
+        self.migration_job,
+        format_flaky_tests(&introduced),
+        other_jobs);
+        write!(f, "{}", display)
+    }
+}
+
+fn group_by_job<'a>(flaky_tests: impl Iterator<Item = &'a FlakyTest>) -> BTreeMap<String, Vec<FlakySummary>> {
+    let mut by_job: BTreeMap<String, BTreeMap<String, FlakySummary>> = BTreeMap::new();
+    for flaky_test in flaky_tests {
+        let test = test_identifier(&flaky_test.classname, &flaky_test.test_name);
+        let summary = by_job.entry(flaky_test.job_name.to_string())
+            .or_default()
+            .entry(test.to_string())
+            .or_insert(FlakySummary { test, times_flaked: 0, time_wasted: 0 });
+        summary.times_flaked += flaky_test.times_flaked;
+        summary.time_wasted += flaky_test.time_wasted.unwrap_or(0);
+    }
+    by_job.into_iter()
+        .map(|(job, tests)| (job, tests.into_values().collect()))
+        .collect()
+}
+
+fn format_flaky_tests(tests: &[FlakySummary]) -> String {
+    if tests.is_empty() {
+        return String::from("\n            none\n");
+    }
+    let lines: String = tests.iter()
+        .map(|t| format!("\n            {}: flaked {} time(s), {}s wasted", t.test, t.times_flaked, t.time_wasted))
+        .collect();
+    format!("{}\n", lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flaky_report_groups_tests_by_job() {
+        let flaky_tests = flaky_tests(vec![
+            flaky_test("build", "cmake_build", "Suite", "shared", 1),
+            flaky_test("build", "bazel_build", "Suite", "shared", 2),
+            flaky_test("build", "bazel_build", "Suite", "shared", 1),
+            flaky_test("build", "bazel_build", "Suite", "bazel_only", 3),
+            flaky_test("build", "lint", "Suite", "lint", 1),
+            flaky_test("nightly", "cmake_build", "Suite", "nightly", 1),
+        ]);
+
+        let report = FlakyReport::from(&String::from("build"), &String::from("bazel_build"), &String::from("cmake_build"), &flaky_tests);
+
+        assert_eq!(report.base, vec![FlakySummary { test: String::from("Suite.shared"), times_flaked: 1, time_wasted: 10 }]);
+        assert_eq!(report.migration, vec![
+            FlakySummary { test: String::from("Suite.bazel_only"), times_flaked: 3, time_wasted: 10 },
+            FlakySummary { test: String::from("Suite.shared"), times_flaked: 3, time_wasted: 20 },
+        ]);
+        assert_eq!(report.other_jobs.keys().collect::<Vec<&String>>(), vec!["lint"]);
+    }
+
+    #[test]
+    fn introduced_by_migration_returns_tests_only_flaky_in_migration_job() {
+        let flaky_tests = flaky_tests(vec![
+            flaky_test("build", "cmake_build", "Suite", "shared", 1),
+            flaky_test("build", "bazel_build", "Suite", "shared", 1),
+            flaky_test("build", "bazel_build", "Suite", "bazel_only", 1),
+        ]);
+
+        let report = FlakyReport::from(&String::from("build"), &String::from("bazel_build"), &String::from("cmake_build"), &flaky_tests);
+        let introduced = report.introduced_by_migration();
+
+        assert_eq!(introduced.len(), 1);
+        assert_eq!(introduced[0].test, "Suite.bazel_only");
+    }
+
+    fn flaky_tests(tests: Vec<FlakyTest>) -> FlakyTests {
+        FlakyTests {
+            total_flaky_tests: tests.len() as u64,
+            flaky_tests: tests,
+        }
+    }
+
+    fn flaky_test(workflow_name: &str, job_name: &str, classname: &str, test_name: &str, times_flaked: u64) -> FlakyTest {
+        FlakyTest {
+            time_wasted: Some(10),
+            workflow_created_at: String::from("2023-10-01T00:00:00Z"),
+            workflow_id: String::from("workflow-id"),
+            classname: classname.to_string(),
+            pipeline_number: 1,
+            workflow_name: workflow_name.to_string(),
+            test_name: test_name.to_string(),
+            job_name: job_name.to_string(),
+            job_number: 1,
+            times_flaked,
+            source: String::from("gtest"),
+            file: None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlakyTests {
+    pub flaky_tests: Vec<FlakyTest>,
+    pub total_flaky_tests: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlakyTest {
+    pub time_wasted: Option<u64>,
+    pub workflow_created_at: String,
+    pub workflow_id: String,
+    pub classname: String,
+    pub pipeline_number: u64,
+    pub workflow_name: String,
+    pub test_name: String,
+    pub job_name: String,
+    pub job_number: u64,
+    pub times_flaked: u64,
+    pub source: String,
+    pub file: Option<String>,
+}
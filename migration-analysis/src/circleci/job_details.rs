@@ -0,0 +1,39 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobDetails {
+    pub number: u64,
+    pub name: String,
+    pub status: String,
+    pub web_url: String,
+    pub parallelism: u64,
+    pub executor: Executor,
+    pub created_at: DateTime<Utc>,
+    pub queued_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub duration: Option<u64>,
+}
+
+impl JobDetails {
+    /// Time spent waiting for an executor.
+    pub fn queue_time(&self) -> Option<Duration> {
+        self.started_at.map(|started_at| started_at - self.queued_at)
+    }
+
+    /// Time spent running on the executor, including its spin-up.
+    pub fn execution_time(&self) -> Option<Duration> {
+        match (self.started_at, self.stopped_at) {
+            (Some(started_at), Some(stopped_at)) => Some(stopped_at - started_at),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Executor {
+    #[serde(rename = "type")]
+    pub executor_type: String,
+    pub resource_class: String,
+}
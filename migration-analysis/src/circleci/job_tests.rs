@@ -0,0 +1,13 @@
+use serde::{Serialize, Deserialize};
+
+/// A test result stored by `store_test_results` in a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobTest {
+    pub name: String,
+    pub classname: String,
+    pub result: String,
+    pub message: Option<String>,
+    pub run_time: f64,
+    pub source: String,
+    pub file: Option<String>,
+}
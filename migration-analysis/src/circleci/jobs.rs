@@ -1,29 +1,60 @@
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+
+use crate::stats::{mean, median, percentile, standard_deviation};
+use super::decode::{decode_items, null_as_default, nullable, DecodeFailure};
+use super::window::Window;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Jobs {
     pub next_page_token: Option<serde_json::Value>,
     pub items: Vec<Item>,
+    /// Items of the response which couldn't be decoded.
+    #[serde(skip)]
+    pub decode_failures: Vec<DecodeFailure>,
+}
+
+impl Jobs {
+    pub fn from_values(values: Vec<Value>) -> Jobs {
+        let (items, decode_failures) = decode_items(values);
+        Jobs { next_page_token: None, items, decode_failures }
+    }
 }
 
+// Metrics may be null, e.g. durations of jobs without successful runs, and fields
+// added to the API later are kept in `extra` rather than failing the decoding.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
     pub name: String,
     pub metrics: Metrics,
-    pub window_start: String,
-    pub window_end: String,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metrics {
+    #[serde(default, deserialize_with = "null_as_default")]
     pub total_runs: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub failed_runs: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub successful_runs: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub median_credits_used: u64,
-    pub duration_metrics: DurationMetrics,
+    /// None when the job has no successful runs in the window.
+    #[serde(default, deserialize_with = "nullable")]
+    pub duration_metrics: Option<DurationMetrics>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub success_rate: f64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub total_credits_used: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub throughput: f64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,8 +62,197 @@ pub struct DurationMetrics {
     pub min: u64,
     pub mean: u64,
     pub median: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub p95: u64,
     pub max: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub standard_deviation: f64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub total_duration: u64,
-}
\ No newline at end of file
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A finished execution of a job, used to aggregate metrics from per-run data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobRun {
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: DateTime<Utc>,
+    pub success: bool,
+}
+
+impl JobRun {
+    pub fn duration(&self) -> f64 {
+        (self.stopped_at - self.started_at).num_milliseconds() as f64 / 1000.0
+    }
+}
+
+impl Item {
+    pub fn window(&self) -> Window {
+        Window::from(self.window_start, self.window_end)
+    }
+
+    /// Aggregate runs like the insights endpoint does, only successful runs count towards the durations.
+    /// Credits are not known from per-run data and are left at zero.
+    pub fn from_runs(name: &String, runs: &[JobRun]) -> Option<Item> {
+        let durations: Vec<f64> = runs.iter().filter(|r| r.success).map(|r| r.duration()).collect();
+        let duration_metrics = DurationMetrics::from(&durations)?;
+        let window_start = runs.iter().map(|r| r.started_at).min()?;
+        let window_end = runs.iter().map(|r| r.stopped_at).max()?;
+        let window_days = ((window_end - window_start).num_seconds() as f64 / 86400.0).max(1.0);
+        let successful_runs = durations.len() as u64;
+        let total_runs = runs.len() as u64;
+
+        Some(Item {
+            name: String::from(name),
+            metrics: Metrics {
+                total_runs,
+                failed_runs: total_runs - successful_runs,
+                successful_runs,
+                median_credits_used: 0,
+                duration_metrics: Some(duration_metrics),
+                success_rate: successful_runs as f64 / total_runs as f64,
+                total_credits_used: 0,
+                throughput: total_runs as f64 / window_days,
+                extra: Map::new(),
+            },
+            window_start,
+            window_end,
+            extra: Map::new(),
+        })
+    }
+}
+
+impl DurationMetrics {
+    /// Durations are in seconds, like the ones of the insights endpoint.
+    pub fn from(durations: &[f64]) -> Option<DurationMetrics> {
+        Some(DurationMetrics {
+            min: durations.iter().cloned().reduce(f64::min)?.round() as u64,
+            mean: mean(durations)?.round() as u64,
+            median: median(durations)?.round() as u64,
+            p95: percentile(durations, 95.0)?.round() as u64,
+            max: durations.iter().cloned().reduce(f64::max)?.round() as u64,
+            standard_deviation: standard_deviation(durations)?,
+            total_duration: durations.iter().sum::<f64>().round() as u64,
+            extra: Map::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::pipelines::Page;
+    use chrono::Duration;
+
+    #[test]
+    fn item_from_runs_aggregates_successful_durations() {
+        let start = Utc::now();
+        let runs = vec![
+            job_run(start, 100, true),
+            job_run(start + Duration::hours(1), 200, true),
+            job_run(start + Duration::hours(2), 10, false),
+        ];
+
+        let item = Item::from_runs(&String::from("bazel_build"), &runs).unwrap();
+
+        assert_eq!(item.metrics.total_runs, 3);
+        assert_eq!(item.metrics.failed_runs, 1);
+        let duration_metrics = item.metrics.duration_metrics.unwrap();
+        assert_eq!(duration_metrics.min, 100);
+        assert_eq!(duration_metrics.mean, 150);
+        assert_eq!(duration_metrics.max, 200);
+        assert_eq!(duration_metrics.total_duration, 300);
+        assert_eq!(item.metrics.throughput, 3.0);
+        assert_eq!(item.window_start, start);
+    }
+
+    #[test]
+    fn item_from_runs_without_successful_runs_is_none() {
+        let runs = vec![job_run(Utc::now(), 10, false)];
+
+        assert!(Item::from_runs(&String::from("bazel_build"), &runs).is_none());
+    }
+
+    #[test]
+    fn jobs_from_values_decode_schema_drift_corpus() {
+        // (file, decoded jobs, skipped items)
+        let corpus = [
+            ("current.json", vec!["cmake_build", "bazel_build"], 0),
+            ("unknown_fields.json", vec!["bazel_build"], 0),
+            ("null_durations.json", vec!["bazel_build"], 0),
+            ("removed_fields.json", vec!["cmake_build"], 0),
+            ("malformed_item.json", vec!["cmake_build", "deploy"], 1),
+            ("malformed_durations.json", vec!["bazel_build"], 1),
+        ];
+
+        for (file, expected_names, expected_failures) in corpus {
+            let jobs = corpus_jobs(file);
+
+            let names: Vec<&str> = jobs.items.iter().map(|i| i.name.as_str()).collect();
+            assert_eq!(names, expected_names, "{}", file);
+            assert_eq!(jobs.decode_failures.len(), expected_failures, "{}", file);
+        }
+    }
+
+    #[test]
+    fn jobs_from_values_keep_unknown_fields() {
+        let jobs = corpus_jobs("unknown_fields.json");
+
+        let item = &jobs.items[0];
+        assert!(item.extra.contains_key("project_id"));
+        assert_eq!(item.metrics.extra.get("mttr"), Some(&serde_json::json!(3600)));
+        assert_eq!(item.metrics.duration_metrics.as_ref().unwrap().extra.get("p99"), Some(&serde_json::json!(780)));
+        assert!(serde_json::to_string(item).unwrap().contains("\"total_recoveries\":1"));
+    }
+
+    #[test]
+    fn jobs_from_values_accept_null_metrics() {
+        let jobs = corpus_jobs("null_durations.json");
+
+        let metrics = &jobs.items[0].metrics;
+        assert!(metrics.duration_metrics.is_none());
+        assert_eq!(metrics.median_credits_used, 0);
+        assert_eq!(metrics.failed_runs, 3);
+    }
+
+    #[test]
+    fn jobs_from_values_default_removed_fields() {
+        let jobs = corpus_jobs("removed_fields.json");
+
+        let metrics = &jobs.items[0].metrics;
+        assert_eq!(metrics.duration_metrics.as_ref().unwrap().median, 700);
+        assert_eq!(metrics.duration_metrics.as_ref().unwrap().p95, 0);
+        assert_eq!(metrics.throughput, 0.0);
+    }
+
+    #[test]
+    fn jobs_from_values_report_malformed_items() {
+        let jobs = corpus_jobs("malformed_item.json");
+
+        assert_eq!(jobs.decode_failures[0].index, 1);
+        assert_eq!(jobs.decode_failures[0].name, Some(String::from("bazel_build")));
+    }
+
+    #[test]
+    fn jobs_from_values_report_malformed_duration_metrics() {
+        let jobs = corpus_jobs("malformed_durations.json");
+
+        assert_eq!(jobs.decode_failures[0].name, Some(String::from("cmake_build")));
+        assert!(jobs.decode_failures[0].error.contains("invalid DurationMetrics"), "{}", jobs.decode_failures[0].error);
+    }
+
+    fn corpus_jobs(file: &str) -> Jobs {
+        let content = std::fs::read_to_string(format!("testdata/insights/jobs/{}", file)).unwrap();
+        let page: Page<Value> = serde_json::from_str(&content).unwrap();
+        Jobs::from_values(page.items)
+    }
+
+    fn job_run(started_at: DateTime<Utc>, duration_seconds: i64, success: bool) -> JobRun {
+        JobRun {
+            started_at,
+            stopped_at: started_at + Duration::seconds(duration_seconds),
+            success,
+        }
+    }
+}
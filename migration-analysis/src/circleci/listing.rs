@@ -0,0 +1,106 @@
+use super::jobs::Item;
+use super::workflows::WorkflowSummary;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct ListingRow {
+    pub name: String,
+    pub total_runs: u64,
+    pub success_rate: f64,
+    pub median_duration: Option<u64>,
+}
+
+/// Workflows or jobs known by the insights API, to pick names from.
+#[derive(Debug)]
+pub struct Listing {
+    pub title: String,
+    pub rows: Vec<ListingRow>,
+}
+
+impl Listing {
+    pub fn from_workflows(workflows: &[WorkflowSummary]) -> Listing {
+        Listing {
+            title: String::from("Workflows"),
+            rows: workflows.iter().map(|w| ListingRow {
+                name: w.name.to_string(),
+                total_runs: w.metrics.total_runs,
+                success_rate: w.metrics.success_rate,
+                median_duration: w.metrics.duration_metrics.as_ref().map(|d| d.median),
+            }).collect(),
+        }
+    }
+
+    pub fn from_jobs(workflow: &String, jobs: &[Item]) -> Listing {
+        Listing {
+            title: format!("Jobs of workflow {}", workflow),
+            rows: jobs.iter().map(|j| ListingRow {
+                name: j.name.to_string(),
+                total_runs: j.metrics.total_runs,
+                success_rate: j.metrics.success_rate,
+                median_duration: j.metrics.duration_metrics.as_ref().map(|d| d.median),
+            }).collect(),
+        }
+    }
+}
+
+impl fmt::Display for Listing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: String = if self.rows.is_empty() {
+            String::from("\n            none")
+        } else {
+            self.rows.iter()
+                .map(|r| format!("\n            {} ({} runs, success rate: {:.2}, median duration: {})",
+                    r.name,
+                    r.total_runs,
+                    r.success_rate,
+                    r.median_duration.map(|d| format!("{}s", d)).unwrap_or_else(|| String::from("n/a"))))
+                .collect()
+        };
+
+        let display = format!(r#"
+        {}:
+{}
+"#,
+        self.title,
+        rows);
+        write!(f, "{}", display)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::workflows::{WorkflowDurationMetrics, WorkflowMetrics};
+
+    #[test]
+    fn display_listing_lists_workflows() {
+        let listing = Listing::from_workflows(&[WorkflowSummary {
+            name: String::from("build"),
+            metrics: WorkflowMetrics {
+                total_runs: 10,
+                successful_runs: 9,
+                failed_runs: 1,
+                success_rate: 0.9,
+                total_credits_used: 100,
+                duration_metrics: Some(WorkflowDurationMetrics { min: 10, mean: 20, median: 21, p95: 30, max: 40 }),
+            },
+            window_start: chrono::Utc::now(),
+            window_end: chrono::Utc::now(),
+        }]);
+
+        let display = format!("{}", listing);
+
+        assert!(display.contains("Workflows:"));
+        assert!(display.contains("build (10 runs, success rate: 0.90, median duration: 21s)"));
+    }
+
+    #[test]
+    fn display_listing_without_rows_shows_none() {
+        let listing = Listing::from_jobs(&String::from("build"), &[]);
+
+        let display = format!("{}", listing);
+
+        assert!(display.contains("Jobs of workflow build:"));
+        assert!(display.contains("none"));
+    }
+}
@@ -1,7 +1,13 @@
-use super::jobs::{Jobs,Item};
+use super::jobs::{DurationMetrics,Jobs,Item};
+use super::suggestions::suggestion;
+use super::window::Window;
+use crate::bazel::bep::BuildSummary;
 use std::fmt;
 use std::io::{Error,ErrorKind};
 
+// Below this share of shared time the windows of the compared jobs differ substantially.
+const MIN_WINDOW_OVERLAP: f64 = 0.8;
+
 #[derive(Debug)]
 pub struct Insight {
     pub base_job: String,
@@ -10,12 +16,26 @@ pub struct Insight {
     pub mean_differential: f64,
     pub median_differential: f64,
     pub max_differential: f64,
-    pub window_start: String,
-    pub window_end: String,
+    pub base_window: Window,
+    pub migration_window: Window,
+    pub warnings: Vec<String>,
+    /// What Bazel did in a build of the migration job, read from its build events
+    pub bazel_build: Option<BuildSummary>,
+}
+
+impl Insight {
+    pub fn with_bazel_build(mut self, bazel_build: BuildSummary) -> Insight {
+        self.bazel_build = Some(bazel_build);
+        self
+    }
 }
 
 impl fmt::Display for Insight {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let warnings: String = self.warnings.iter()
+            .map(|w| format!("\n        !!! WARNING: {} !!!\n", w))
+            .collect();
+
         let display = format!(r#"
         Migration analysis:
 
@@ -23,8 +43,8 @@ impl fmt::Display for Insight {
 
             base job: {}
             migration job: {}
-            window start: {}
-            window end: {}
+            base window: {}
+            migration window: {}
 
         Data:
 
@@ -32,16 +52,21 @@ impl fmt::Display for Insight {
             maximum duration - differential: {}
             mean duration - differential: {}
             median duration - differential: {}
-        "#, 
+{}        "#, 
         self.base_job,
         self.migration_job,
-        self.window_start,
-        self.window_end,
+        self.base_window,
+        self.migration_window,
         self.min_differential,
         self.max_differential,
         self.mean_differential,
-        self.median_differential);
-        write!(f, "{}", display)
+        self.median_differential,
+        warnings);
+        write!(f, "{}", display)?;
+        match &self.bazel_build {
+            Some(bazel_build) => write!(f, "{}", bazel_build),
+            None => Ok(()),
+        }
     }
 }
 
@@ -53,29 +78,56 @@ impl Analysis {
     pub fn get_insights(&self, migration_job_name: &String, base_job_name: &String, jobs: &Jobs) -> Result<Insight, Error> {
         let base_workflow_option = get_item_with_name(&jobs.items, base_job_name);
         if base_workflow_option.is_none() {
-            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find workflow {} in retrieved jobs", &base_job_name))); 
+            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find workflow {} in retrieved jobs{}", &base_job_name, job_name_suggestion(jobs, base_job_name)))); 
         }
         let base_workflow = base_workflow_option.unwrap();
 
         let migration_workflow_option = get_item_with_name(&jobs.items, migration_job_name);
         if migration_workflow_option.is_none() {
-            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find workflow {} in retrieved jobs", &migration_job_name))); 
+            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find workflow {} in retrieved jobs{}", &migration_job_name, job_name_suggestion(jobs, migration_job_name)))); 
         }
         let migration_workflow = migration_workflow_option.unwrap();
 
+        let base_window = base_workflow.window();
+        let migration_window = migration_workflow.window();
+        if !base_window.overlaps(&migration_window) {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("windows of {} ({}) and {} ({}) don't overlap, their durations can't be compared",
+                &base_job_name, base_window, &migration_job_name, migration_window)));
+        }
+        let mut warnings = vec![];
+        let overlap = base_window.overlap_ratio(&migration_window);
+        if overlap < MIN_WINDOW_OVERLAP {
+            warnings.push(format!("windows of {} and {} only share {:.0}% of their time, durations may not be comparable", &base_job_name, &migration_job_name, overlap * 100.0));
+        }
+
+        let base_durations = duration_metrics_of(&base_workflow)?;
+        let migration_durations = duration_metrics_of(&migration_workflow)?;
+
         Ok(Insight {
             base_job: String::from(base_job_name),
             migration_job: String::from(migration_job_name), 
-            min_differential: (migration_workflow.metrics.duration_metrics.min as f64 - base_workflow.metrics.duration_metrics.min as f64),
-            mean_differential: (migration_workflow.metrics.duration_metrics.mean as f64 - base_workflow.metrics.duration_metrics.mean as f64),
-            median_differential: (migration_workflow.metrics.duration_metrics.median as f64 - base_workflow.metrics.duration_metrics.median as f64),
-            max_differential: (migration_workflow.metrics.duration_metrics.max as f64 - base_workflow.metrics.duration_metrics.max as f64),
-            window_start: migration_workflow.window_start,
-            window_end: migration_workflow.window_end,
+            min_differential: (migration_durations.min as f64 - base_durations.min as f64),
+            mean_differential: (migration_durations.mean as f64 - base_durations.mean as f64),
+            median_differential: (migration_durations.median as f64 - base_durations.median as f64),
+            max_differential: (migration_durations.max as f64 - base_durations.max as f64),
+            base_window,
+            migration_window,
+            warnings,
+            bazel_build: None,
          })
     }
 }
 
+fn duration_metrics_of(item: &Item) -> Result<&DurationMetrics, Error> {
+    item.metrics.duration_metrics.as_ref()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("job {} has no duration metrics, it didn't succeed in the reporting window", &item.name)))
+}
+
+fn job_name_suggestion(jobs: &Jobs, name: &str) -> String {
+    suggestion(name, jobs.items.iter().map(|i| &i.name))
+}
+
+#[allow(clippy::ptr_arg, clippy::needless_return)]
 fn get_item_with_name(items: &Vec<Item>, name: &String) -> Option<Item> {
     let item_option = items.iter().find(|i| &i.name == name);
     match item_option {
@@ -87,7 +139,9 @@ fn get_item_with_name(items: &Vec<Item>, name: &String) -> Option<Item> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use super::super::jobs::{Metrics,DurationMetrics};
+    use super::super::jobs::Metrics;
+    use serde_json::Map;
+    use chrono::{DateTime, Utc};
     
     #[test]
     fn get_insights_fails_if_base_workflow_not_found() {
@@ -98,6 +152,7 @@ mod tests {
 
         let jobs = Jobs{
             next_page_token: None,
+            decode_failures: vec![],
             items: vec![]
         };
 
@@ -116,6 +171,7 @@ mod tests {
 
         let jobs = Jobs{
             next_page_token: None,
+            decode_failures: vec![],
             items: vec![base_workflow_item]
         };
 
@@ -126,24 +182,40 @@ mod tests {
         assert_eq!(actual_err.into_inner().unwrap().to_string(), format!("failed to find workflow {} in retrieved jobs", &migration_workflow_name))   
     }
 
+    #[test]
+    fn get_insights_suggests_close_job_name() {
+        let base_workflow_name = String::from("cmake_build");
+        let jobs = Jobs{
+            next_page_token: None,
+            decode_failures: vec![],
+            items: vec![base_workflow_item(&base_workflow_name), migration_workflow_item(&String::from("bazel_build"))]
+        };
+
+        let analysis = Analysis{};
+        let actual_err = analysis.get_insights(&String::from("bazel_buid"), &base_workflow_name, &jobs).unwrap_err();
+
+        assert_eq!(actual_err.into_inner().unwrap().to_string(), "failed to find workflow bazel_buid in retrieved jobs, did you mean bazel_build?")
+    }
+
     #[test]
     fn get_insights() {
         let base_job_name: String = String::from("base workflow");
         let base_workflow_item = base_workflow_item(&base_job_name);
-        let base_workflow_min = base_workflow_item.metrics.duration_metrics.min;
-        let base_workflow_median = base_workflow_item.metrics.duration_metrics.median;
-        let base_workflow_mean = base_workflow_item.metrics.duration_metrics.mean;
-        let base_workflow_max = base_workflow_item.metrics.duration_metrics.max;
+        let base_workflow_min = base_workflow_item.metrics.duration_metrics.as_ref().unwrap().min;
+        let base_workflow_median = base_workflow_item.metrics.duration_metrics.as_ref().unwrap().median;
+        let base_workflow_mean = base_workflow_item.metrics.duration_metrics.as_ref().unwrap().mean;
+        let base_workflow_max = base_workflow_item.metrics.duration_metrics.as_ref().unwrap().max;
         
         let migration_workflow_name = String::from("migration workflow");
         let migration_workflow_item = migration_workflow_item(&migration_workflow_name);
-        let migration_workflow_min = migration_workflow_item.metrics.duration_metrics.min;
-        let migration_workflow_median = migration_workflow_item.metrics.duration_metrics.median;
-        let migration_workflow_mean = migration_workflow_item.metrics.duration_metrics.mean;
-        let migration_workflow_max = migration_workflow_item.metrics.duration_metrics.max;
+        let migration_workflow_min = migration_workflow_item.metrics.duration_metrics.as_ref().unwrap().min;
+        let migration_workflow_median = migration_workflow_item.metrics.duration_metrics.as_ref().unwrap().median;
+        let migration_workflow_mean = migration_workflow_item.metrics.duration_metrics.as_ref().unwrap().mean;
+        let migration_workflow_max = migration_workflow_item.metrics.duration_metrics.as_ref().unwrap().max;
 
         let jobs = Jobs{
             next_page_token: None,
+            decode_failures: vec![],
             items: vec![base_workflow_item,migration_workflow_item]
         };
 
@@ -161,6 +233,67 @@ mod tests {
         assert_eq!(median_differential,insights.median_differential);
         assert_eq!(mean_differential,insights.mean_differential);
         assert_eq!(max_differential,insights.max_differential);
+        assert!(insights.warnings.is_empty());
+    }
+
+    #[test]
+    fn get_insights_fails_if_windows_do_not_overlap() {
+        let base_job_name = String::from("base workflow");
+        let migration_job_name = String::from("migration workflow");
+        let mut migration_item = migration_workflow_item(&migration_job_name);
+        migration_item.window_start = timestamp("2023-10-02T00:00:00Z");
+        migration_item.window_end = timestamp("2023-12-01T00:00:00Z");
+        let jobs = Jobs{
+            next_page_token: None,
+            decode_failures: vec![],
+            items: vec![base_workflow_item(&base_job_name), migration_item]
+        };
+
+        let actual_err = Analysis{}.get_insights(&migration_job_name, &base_job_name, &jobs).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+        assert!(actual_err.to_string().contains("don't overlap"));
+    }
+
+    #[test]
+    fn get_insights_compares_jobs_with_a_single_run() {
+        let base_job_name = String::from("base workflow");
+        let migration_job_name = String::from("migration workflow");
+        let mut base_item = base_workflow_item(&base_job_name);
+        base_item.window_start = timestamp("2023-09-01T10:00:00Z");
+        base_item.window_end = timestamp("2023-09-01T10:00:00Z");
+        let mut migration_item = migration_workflow_item(&migration_job_name);
+        migration_item.window_start = timestamp("2023-09-01T10:00:00Z");
+        migration_item.window_end = timestamp("2023-09-01T10:00:00Z");
+        let jobs = Jobs{
+            next_page_token: None,
+            decode_failures: vec![],
+            items: vec![base_item, migration_item]
+        };
+
+        let insights = Analysis{}.get_insights(&migration_job_name, &base_job_name, &jobs).unwrap();
+
+        assert!(insights.warnings.is_empty());
+        assert_eq!(insights.base_window, window("2023-09-01T10:00:00Z", "2023-09-01T10:00:00Z"));
+    }
+
+    #[test]
+    fn get_insights_warns_if_windows_differ_substantially() {
+        let base_job_name = String::from("base workflow");
+        let migration_job_name = String::from("migration workflow");
+        let mut migration_item = migration_workflow_item(&migration_job_name);
+        migration_item.window_start = timestamp("2023-09-01T00:00:00Z");
+        let jobs = Jobs{
+            next_page_token: None,
+            decode_failures: vec![],
+            items: vec![base_workflow_item(&base_job_name), migration_item]
+        };
+
+        let insights = Analysis{}.get_insights(&migration_job_name, &base_job_name, &jobs).unwrap();
+
+        assert_eq!(insights.warnings, vec![String::from("windows of base workflow and migration workflow only share 33% of their time, durations may not be comparable")]);
+        assert_eq!(insights.base_window.start, timestamp("2023-07-01T00:00:00Z"));
+        assert_eq!(insights.migration_window.start, timestamp("2023-09-01T00:00:00Z"));
     }
 
     #[test]
@@ -171,8 +304,8 @@ mod tests {
         let mean_differential: f64 = 1.0;
         let median_differential: f64 = 1.0;
         let max_differential: f64 = 1.0;
-        let window_start: String = String::from("start");
-        let window_end: String = String::from("end");
+        let base_window = window("2023-07-01T00:00:00Z", "2023-10-01T00:00:00Z");
+        let migration_window = window("2023-08-01T00:00:00Z", "2023-10-01T00:00:00Z");
 
         let expected_display = format!(r#"
         Migration analysis:
@@ -181,8 +314,8 @@ mod tests {
 
             base job: {}
             migration job: {}
-            window start: {}
-            window end: {}
+            base window: 2023-07-01T00:00:00+00:00 - 2023-10-01T00:00:00+00:00
+            migration window: 2023-08-01T00:00:00+00:00 - 2023-10-01T00:00:00+00:00
 
         Data:
 
@@ -190,11 +323,11 @@ mod tests {
             maximum duration - differential: {}
             mean duration - differential: {}
             median duration - differential: {}
+
+        !!! WARNING: windows differ !!!
         "#,
         &base_job,
         &migration_job,
-        &window_start,
-        &window_end,
         &min_differential,
         &max_differential,
         &mean_differential,
@@ -207,8 +340,10 @@ mod tests {
             mean_differential,
             median_differential,
             max_differential,
-            window_start,
-            window_end
+            base_window,
+            migration_window,
+            warnings: vec![String::from("windows differ")],
+            bazel_build: None,
         };
 
         let insight_display = format!("{}", insights);
@@ -216,6 +351,29 @@ mod tests {
         assert_eq!(insight_display,expected_display);
     }
 
+    #[test]
+    fn display_insights_appends_bazel_build_summary() {
+        let insights = Insight {
+            base_job: String::from("base job"),
+            migration_job: String::from("migration job"),
+            min_differential: 1.0,
+            mean_differential: 1.0,
+            median_differential: 1.0,
+            max_differential: 1.0,
+            base_window: window("2023-07-01T00:00:00Z", "2023-10-01T00:00:00Z"),
+            migration_window: window("2023-07-01T00:00:00Z", "2023-10-01T00:00:00Z"),
+            warnings: vec![],
+            bazel_build: None,
+        };
+        let summary = BuildSummary { command: Some(String::from("build")), ..BuildSummary::default() };
+
+        let insight_display = format!("{}", insights.with_bazel_build(summary.clone()));
+
+        assert!(insight_display.ends_with(&format!("{}", summary)));
+        assert!(insight_display.contains("        Bazel build summary:"));
+    }
+
+    #[allow(clippy::redundant_field_names, clippy::needless_return)]
     fn base_workflow_item(workflow_name: &String) -> Item { 
         let duration_metrics = DurationMetrics {
             min: 1,
@@ -225,6 +383,7 @@ mod tests {
             max: 12,
             standard_deviation: 1.1,
             total_duration: 23,
+            extra: Map::new(),
         };
 
         let metrics = Metrics {
@@ -232,20 +391,23 @@ mod tests {
             failed_runs: 2,
             successful_runs: 5,
             median_credits_used: 4,
-            duration_metrics: duration_metrics,
+            duration_metrics: Some(duration_metrics),
             success_rate: 0.9,
             total_credits_used: 4,
             throughput: 5.0,
+            extra: Map::new(),
         };
 
         return Item {
             name: workflow_name.to_string(),
             metrics: metrics,
-            window_start: "start".to_string(),
-            window_end: "end".to_string(),
+            window_start: timestamp("2023-07-01T00:00:00Z"),
+            window_end: timestamp("2023-10-01T00:00:00Z"),
+            extra: Map::new(),
         }; 
     }
 
+    #[allow(clippy::redundant_field_names, clippy::needless_return)]
     fn migration_workflow_item(workflow_name: &String) -> Item {
         let duration_metrics = DurationMetrics {
             min: 1,
@@ -255,6 +417,7 @@ mod tests {
             max: 11,
             standard_deviation: 1.0,
             total_duration: 22,
+            extra: Map::new(),
         };
 
         let metrics = Metrics {
@@ -262,17 +425,27 @@ mod tests {
             failed_runs: 2,
             successful_runs: 5,
             median_credits_used: 4,
-            duration_metrics: duration_metrics,
+            duration_metrics: Some(duration_metrics),
             success_rate: 0.9,
             total_credits_used: 4,
             throughput: 5.0,
+            extra: Map::new(),
         };
 
         return Item {
             name: workflow_name.to_string(),
             metrics: metrics,
-            window_start: "start".to_string(),
-            window_end: "end".to_string(),
-        };  
+            window_start: timestamp("2023-07-01T00:00:00Z"),
+            window_end: timestamp("2023-10-01T00:00:00Z"),
+            extra: Map::new(),
+        }; 
+    }
+
+    fn window(start: &str, end: &str) -> Window {
+        Window::from(timestamp(start), timestamp(end))
+    }
+
+    fn timestamp(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc)
     }
 }
\ No newline at end of file
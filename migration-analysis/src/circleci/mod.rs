@@ -1,3 +1,23 @@
+pub mod artifacts;
+pub mod bench;
 pub mod client;
+pub mod decode;
+pub mod flakiness;
+pub mod flaky_tests;
+pub mod job_details;
+pub mod job_tests;
 pub mod jobs;
-pub mod migration;
\ No newline at end of file
+pub mod listing;
+pub mod migration;
+pub mod org;
+pub mod parity;
+pub mod phases;
+pub mod pipelines;
+pub mod runs;
+pub mod steps;
+pub mod suggestions;
+pub mod test_diff;
+pub mod test_metrics;
+pub mod timing;
+pub mod window;
+pub mod workflows;
\ No newline at end of file
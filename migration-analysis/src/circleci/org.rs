@@ -0,0 +1,335 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Error;
+
+use super::client::Client;
+
+// Jobs whose name contains it are considered to be built with Bazel.
+const BAZEL_MARKER: &str = "bazel";
+
+/// Response of the org-level insights summary endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrgSummary {
+    pub org_data: OrgData,
+    pub org_project_data: Vec<ProjectData>,
+    pub all_projects: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrgData {
+    pub metrics: SummaryMetrics,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectData {
+    pub project_name: String,
+    pub metrics: SummaryMetrics,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryMetrics {
+    pub total_runs: u64,
+    pub total_duration_secs: u64,
+    pub total_credits_used: u64,
+    pub success_rate: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BuildSystems {
+    Unknown,
+    LegacyOnly,
+    BazelAlongsideLegacy,
+    BazelOnly,
+}
+
+impl BuildSystems {
+    pub fn from(job_names: &[String]) -> BuildSystems {
+        let bazel_jobs = job_names.iter().filter(|name| name.to_lowercase().contains(BAZEL_MARKER)).count();
+        match (bazel_jobs, job_names.len() - bazel_jobs) {
+            (0, 0) => BuildSystems::Unknown,
+            (0, _) => BuildSystems::LegacyOnly,
+            (_, 0) => BuildSystems::BazelOnly,
+            _ => BuildSystems::BazelAlongsideLegacy,
+        }
+    }
+}
+
+impl fmt::Display for BuildSystems {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            BuildSystems::Unknown => "unknown jobs",
+            BuildSystems::LegacyOnly => "legacy jobs only",
+            BuildSystems::BazelAlongsideLegacy => "bazel alongside legacy jobs",
+            BuildSystems::BazelOnly => "bazel jobs only",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub struct ProjectRanking {
+    pub project: String,
+    pub metrics: SummaryMetrics,
+    pub build_systems: BuildSystems,
+}
+
+/// Names of the jobs each project ran, and why the ones of some projects couldn't be listed.
+#[derive(Debug, Default)]
+pub struct ProjectJobs {
+    pub job_names: BTreeMap<String, Vec<String>>,
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct OrgReport {
+    pub org: String,
+    pub metrics: SummaryMetrics,
+    pub projects: Vec<ProjectRanking>,
+    /// Projects whose jobs couldn't be listed, their build systems are unknown
+    pub failures: Vec<String>,
+}
+
+impl OrgReport {
+    /// Projects are ranked by CI duration then credits, the most expensive first.
+    pub fn from(org: &String, summary: &OrgSummary, project_jobs: &ProjectJobs) -> OrgReport {
+        let job_names = &project_jobs.job_names;
+        let mut projects: Vec<ProjectRanking> = summary.org_project_data.iter()
+            .map(|p| ProjectRanking {
+                project: p.project_name.to_string(),
+                metrics: p.metrics.clone(),
+                build_systems: BuildSystems::from(job_names.get(&p.project_name).map(|n| n.as_slice()).unwrap_or_default()),
+            })
+            .collect();
+        projects.sort_by(|a, b| b.metrics.total_duration_secs.cmp(&a.metrics.total_duration_secs)
+            .then(b.metrics.total_credits_used.cmp(&a.metrics.total_credits_used)));
+
+        OrgReport {
+            org: org.to_string(),
+            metrics: summary.org_data.metrics.clone(),
+            projects,
+            failures: project_jobs.failures.clone(),
+        }
+    }
+
+    /// Projects with Bazel jobs running next to legacy ones, i.e. migrations in progress.
+    pub fn migrations_in_progress(&self) -> Vec<&ProjectRanking> {
+        self.projects.iter().filter(|p| p.build_systems == BuildSystems::BazelAlongsideLegacy).collect()
+    }
+}
+
+impl fmt::Display for OrgReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let projects: String = self.projects.iter().enumerate()
+            .map(|(rank, p)| format!("\n            {}. {}: {:.1}h, {} credits, {} runs, success rate: {:.2}, {}{}",
+                rank + 1,
+                p.project,
+                p.metrics.total_duration_secs as f64 / 3600.0,
+                p.metrics.total_credits_used,
+                p.metrics.total_runs,
+                p.metrics.success_rate,
+                p.build_systems,
+                if p.build_systems == BuildSystems::BazelAlongsideLegacy { " <-- migration in progress" } else { "" }))
+            .collect();
+        let warnings: String = self.failures.iter()
+            .map(|failure| format!("\n        !!! WARNING: {} !!!\n", failure))
+            .collect();
+
+        let display = format!(r#"
+        Organization insights:
+
+        Details:
+
+            organization: {}
+            total duration: {:.1}h
+            total credits: {}
+            total runs: {}
+            migrations in progress: {}
+
+        Projects ranked by CI duration:
+{}
+{}"#,
+        self.org,
+        self.metrics.total_duration_secs as f64 / 3600.0,
+        self.metrics.total_credits_used,
+        self.metrics.total_runs,
+        self.migrations_in_progress().len(),
+        projects,
+        warnings);
+        write!(f, "{}", display)
+    }
+}
+
+/// Retrieve the names of the jobs each project ran, through the insights workflows and jobs.
+/// Projects whose insights can't be retrieved, e.g. without access to them, are reported as failures.
+pub async fn collect_project_jobs(client: &Client, projects: &[String]) -> ProjectJobs {
+    let mut project_jobs = ProjectJobs::default();
+    for project in projects {
+        match collect_job_names(client, project).await {
+            Ok(names) => {
+                project_jobs.job_names.insert(project.to_string(), names);
+            },
+            Err(e) => project_jobs.failures.push(format!("failed to list the jobs of project {}: {}", project, e)),
+        }
+    }
+    project_jobs
+}
+
+async fn collect_job_names(client: &Client, project: &String) -> Result<Vec<String>, Error> {
+    let project_client = client.for_workflow(project, &client.workflow);
+    let mut names: Vec<String> = vec![];
+    for workflow in project_client.get_workflows().await? {
+        let jobs = client.for_workflow(project, &workflow.name).get_jobs().await?;
+        names.extend(jobs.items.into_iter().map(|j| j.name));
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn build_systems_from_job_names() {
+        assert_eq!(BuildSystems::from(&[]), BuildSystems::Unknown);
+        assert_eq!(BuildSystems::from(&names(&["cmake_build", "deploy"])), BuildSystems::LegacyOnly);
+        assert_eq!(BuildSystems::from(&names(&["cmake_build", "Bazel_Build"])), BuildSystems::BazelAlongsideLegacy);
+        assert_eq!(BuildSystems::from(&names(&["bazel_build", "bazel_test"])), BuildSystems::BazelOnly);
+    }
+
+    #[test]
+    fn org_report_ranks_projects_by_duration_then_credits() {
+        let summary = OrgSummary {
+            org_data: OrgData { metrics: metrics(1000, 30) },
+            org_project_data: vec![
+                ProjectData { project_name: String::from("small"), metrics: metrics(100, 5) },
+                ProjectData { project_name: String::from("cheap"), metrics: metrics(450, 5) },
+                ProjectData { project_name: String::from("expensive"), metrics: metrics(450, 20) },
+            ],
+            all_projects: names(&["small", "cheap", "expensive"]),
+        };
+        let mut project_jobs = ProjectJobs::default();
+        project_jobs.job_names.insert(String::from("expensive"), names(&["cmake_build", "bazel_build"]));
+        project_jobs.job_names.insert(String::from("cheap"), names(&["cmake_build"]));
+
+        let report = OrgReport::from(&String::from("gh/org"), &summary, &project_jobs);
+
+        let ranked: Vec<&str> = report.projects.iter().map(|p| p.project.as_str()).collect();
+        assert_eq!(ranked, vec!["expensive", "cheap", "small"]);
+        assert_eq!(report.projects[2].build_systems, BuildSystems::Unknown);
+        assert_eq!(report.migrations_in_progress().len(), 1);
+        assert!(format!("{}", report).contains("1. expensive: 0.1h, 20 credits, 10 runs, success rate: 0.90, bazel alongside legacy jobs <-- migration in progress"));
+    }
+
+    #[tokio::test]
+    async fn collect_project_jobs_lists_jobs_of_all_workflows() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/insights/gh/org/other/workflows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [workflow_json("build"), workflow_json("nightly")],
+                "next_page_token": null
+            })))
+            .mount(&server)
+            .await;
+        for (workflow, job) in [("build", "cmake_build"), ("nightly", "bazel_build")] {
+            Mock::given(method("GET"))
+                .and(path(format!("/v2/insights/gh/org/other/workflows/{}/jobs", workflow)))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "items": [job_json(job)],
+                    "next_page_token": null
+                })))
+                .mount(&server)
+                .await;
+        }
+        let client = Client::from(
+            &String::from("gh/org"),
+            &String::from("project"),
+            &String::from("any"),
+            &String::from("last-90-days"),
+            &String::from("token"),
+        ).unwrap().with_api_url(&server.uri());
+
+        let project_jobs = collect_project_jobs(&client, &names(&["other"])).await;
+
+        assert_eq!(project_jobs.job_names.get("other").unwrap(), &names(&["bazel_build", "cmake_build"]));
+        assert!(project_jobs.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn collect_project_jobs_reports_projects_it_cannot_access() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/insights/gh/org/private/workflows"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/insights/gh/org/other/workflows"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [workflow_json("build")],
+                "next_page_token": null
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/insights/gh/org/other/workflows/build/jobs"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [job_json("bazel_build")],
+                "next_page_token": null
+            })))
+            .mount(&server)
+            .await;
+        let client = Client::from(
+            &String::from("gh/org"),
+            &String::from("project"),
+            &String::from("any"),
+            &String::from("last-90-days"),
+            &String::from("token"),
+        ).unwrap().with_api_url(&server.uri());
+
+        let project_jobs = collect_project_jobs(&client, &names(&["private", "other"])).await;
+
+        assert_eq!(project_jobs.job_names.get("other").unwrap(), &names(&["bazel_build"]));
+        assert!(!project_jobs.job_names.contains_key("private"));
+        assert_eq!(project_jobs.failures.len(), 1);
+        assert!(project_jobs.failures[0].starts_with("failed to list the jobs of project private: "));
+    }
+
+    fn workflow_json(name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": name,
+            "metrics": {
+                "total_runs": 1, "successful_runs": 1, "failed_runs": 0, "success_rate": 1.0, "total_credits_used": 1,
+                "duration_metrics": {"min": 1, "mean": 1, "median": 1, "p95": 1, "max": 1}
+            },
+            "window_start": "2023-07-01T10:00:00Z",
+            "window_end": "2023-10-01T10:00:00Z"
+        })
+    }
+
+    fn job_json(name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": name,
+            "metrics": {
+                "total_runs": 1, "failed_runs": 0, "successful_runs": 1, "median_credits_used": 1,
+                "duration_metrics": {"min": 1, "mean": 1, "median": 1, "p95": 1, "max": 1, "standard_deviation": 0.0, "total_duration": 1},
+                "success_rate": 1.0, "total_credits_used": 1, "throughput": 1.0
+            },
+            "window_start": "2023-07-01T10:00:00Z",
+            "window_end": "2023-10-01T10:00:00Z"
+        })
+    }
+
+    fn metrics(total_duration_secs: u64, total_credits_used: u64) -> SummaryMetrics {
+        SummaryMetrics { total_runs: 10, total_duration_secs, total_credits_used, success_rate: 0.9 }
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+}
@@ -0,0 +1,299 @@
+use super::test_metrics::{TestCase, TestMetrics};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Error,ErrorKind};
+
+#[derive(Debug)]
+pub struct TestComparison {
+    pub test: String,
+    pub base_failure_rate: f64,
+    pub migration_failure_rate: f64,
+    pub base_p95_duration: f64,
+    pub migration_p95_duration: f64,
+}
+
+impl TestComparison {
+    pub fn failure_rate_differential(&self) -> f64 {
+        self.migration_failure_rate - self.base_failure_rate
+    }
+
+    pub fn p95_duration_differential(&self) -> f64 {
+        self.migration_p95_duration - self.base_p95_duration
+    }
+}
+
+#[derive(Debug)]
+pub struct TestParity {
+    pub base_job: String,
+    pub migration_job: String,
+    pub base_only: Vec<String>,
+    pub migration_only: Vec<String>,
+    pub both: Vec<TestComparison>,
+    /// Test cases left out of the capped most failed and slowest lists of the endpoint
+    pub unlisted_failed_tests: u64,
+    pub unlisted_slow_tests: u64,
+}
+
+impl TestParity {
+    pub fn from(migration_job_name: &String, base_job_name: &String, metrics: &TestMetrics) -> Result<TestParity, Error> {
+        let base_tests = get_tests_of_job(metrics, base_job_name);
+        if base_tests.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find tests of job {} in retrieved test metrics", &base_job_name)));
+        }
+        let mut migration_tests = get_tests_of_job(metrics, migration_job_name);
+
+        let mut base_only = vec![];
+        let mut both = vec![];
+        for (test, base_case) in base_tests {
+            match migration_tests.remove(&test) {
+                Some(migration_case) => both.push(TestComparison {
+                    test,
+                    base_failure_rate: failure_rate(base_case),
+                    migration_failure_rate: failure_rate(migration_case),
+                    base_p95_duration: base_case.p95_duration,
+                    migration_p95_duration: migration_case.p95_duration,
+                }),
+                None => base_only.push(test),
+            }
+        }
+
+        Ok(TestParity {
+            base_job: String::from(base_job_name),
+            migration_job: String::from(migration_job_name),
+            base_only,
+            migration_only: migration_tests.into_keys().collect(),
+            both,
+            unlisted_failed_tests: metrics.most_failed_tests_extra,
+            unlisted_slow_tests: metrics.slowest_tests_extra,
+        })
+    }
+
+    pub fn base_test_count(&self) -> usize {
+        self.base_only.len() + self.both.len()
+    }
+
+    pub fn migration_test_count(&self) -> usize {
+        self.migration_only.len() + self.both.len()
+    }
+
+    /// Both lists hold every test case, otherwise a test case may be missing from the lists of one job only
+    /// e.g. when it's among the slowest tests of the base job but runs faster in the migration job.
+    pub fn lists_complete(&self) -> bool {
+        self.unlisted_failed_tests == 0 && self.unlisted_slow_tests == 0
+    }
+
+    /// True when the migration job is missing test cases that the base job executes.
+    pub fn migration_runs_fewer_tests(&self) -> bool {
+        !self.base_only.is_empty() && self.lists_complete()
+    }
+}
+
+impl fmt::Display for TestParity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut warning = String::new();
+        if self.migration_runs_fewer_tests() {
+            warning = format!(r#"
+        !!! WARNING: {} does not run {} test case(s) executed by {} ({} vs {} test cases) !!!
+"#,
+            self.migration_job,
+            self.base_only.len(),
+            self.base_job,
+            self.migration_test_count(),
+            self.base_test_count());
+        } else if !self.base_only.is_empty() {
+            warning = format!(r#"
+        !!! WARNING: {} test case(s) listed for {} aren't listed for {}, the lists of the test metrics endpoint are truncated so they may still run, compare the job tests with mig parity job-tests !!!
+"#,
+            self.base_only.len(),
+            self.base_job,
+            self.migration_job);
+        }
+
+        let mut note = String::new();
+        if !self.lists_complete() {
+            note = format!(r#"
+        Note: {} most failed and {} slowest test case(s) were not listed by the test metrics endpoint and are not compared.
+"#, self.unlisted_failed_tests, self.unlisted_slow_tests);
+        }
+
+        let display = format!(r#"
+        Test parity analysis:
+
+        Details:
+
+            base job: {} ({} test cases)
+            migration job: {} ({} test cases)
+{}{}
+        Only executed by {}:
+{}
+        Only executed by {}:
+{}
+        Executed by both (base -> migration):
+{}"#,
+        self.base_job,
+        self.base_test_count(),
+        self.migration_job,
+        self.migration_test_count(),
+        warning,
+        note,
+        self.base_job,
+        format_tests(&self.base_only),
+        self.migration_job,
+        format_tests(&self.migration_only),
+        format_comparisons(&self.both));
+        write!(f, "{}", display)
+    }
+}
+
+fn get_tests_of_job<'a>(metrics: &'a TestMetrics, job_name: &String) -> BTreeMap<String, &'a TestCase> {
+    metrics.most_failed_tests.iter()
+        .chain(metrics.slowest_tests.iter())
+        .filter(|t| &t.job_name == job_name)
+        .map(|t| (test_identifier(&t.classname, &t.test_name), t))
+        .collect()
+}
+
+pub fn test_identifier(classname: &str, test_name: &str) -> String {
+    if classname.is_empty() {
+        return test_name.to_string();
+    }
+    format!("{}.{}", classname, test_name)
+}
+
+fn failure_rate(test: &TestCase) -> f64 {
+    if test.total_runs == 0 {
+        return 0.0;
+    }
+    test.failed_runs as f64 / test.total_runs as f64
+}
+
+fn format_tests(tests: &[String]) -> String {
+    if tests.is_empty() {
+        return String::from("\n            none\n");
+    }
+    let lines: String = tests.iter().map(|t| format!("\n            {}", t)).collect();
+    format!("{}\n", lines)
+}
+
+fn format_comparisons(comparisons: &[TestComparison]) -> String {
+    if comparisons.is_empty() {
+        return String::from("\n            none\n        ");
+    }
+    let lines: String = comparisons.iter().map(|c| format!(
+        "\n            {}: failure rate {:.2} -> {:.2} ({:+.2}), p95 duration {:.2}s -> {:.2}s ({:+.2}s)",
+        c.test,
+        c.base_failure_rate,
+        c.migration_failure_rate,
+        c.failure_rate_differential(),
+        c.base_p95_duration,
+        c.migration_p95_duration,
+        c.p95_duration_differential())).collect();
+    format!("{}\n        ", lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parity_fails_if_base_job_has_no_tests() {
+        let metrics = test_metrics(vec![test_case("bazel_build", "Suite", "test", 0, 1.0)]);
+
+        let actual_err = TestParity::from(&String::from("bazel_build"), &String::from("cmake_build"), &metrics).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(actual_err.into_inner().unwrap().to_string(), "failed to find tests of job cmake_build in retrieved test metrics")
+    }
+
+    #[test]
+    fn test_parity_splits_tests_by_job() {
+        let metrics = test_metrics(vec![
+            test_case("cmake_build", "Suite", "shared", 1, 2.0),
+            test_case("cmake_build", "Suite", "base_only", 0, 1.0),
+            test_case("bazel_build", "Suite", "shared", 2, 1.5),
+            test_case("bazel_build", "", "migration_only", 0, 1.0),
+            test_case("other_job", "Suite", "other", 0, 1.0),
+        ]);
+
+        let parity = TestParity::from(&String::from("bazel_build"), &String::from("cmake_build"), &metrics).unwrap();
+
+        assert_eq!(parity.base_only, vec![String::from("Suite.base_only")]);
+        assert_eq!(parity.migration_only, vec![String::from("migration_only")]);
+        assert_eq!(parity.both.len(), 1);
+        assert_eq!(parity.both[0].test, "Suite.shared");
+        assert_eq!(parity.both[0].base_failure_rate, 0.1);
+        assert_eq!(parity.both[0].migration_failure_rate, 0.2);
+        assert_eq!(parity.both[0].p95_duration_differential(), -0.5);
+        assert_eq!(parity.base_test_count(), 2);
+        assert_eq!(parity.migration_test_count(), 2);
+        assert!(parity.migration_runs_fewer_tests());
+    }
+
+    #[test]
+    fn test_parity_deduplicates_tests_listed_as_failed_and_slow() {
+        let mut metrics = test_metrics(vec![test_case("cmake_build", "Suite", "test", 1, 2.0)]);
+        metrics.slowest_tests = metrics.most_failed_tests.clone();
+        metrics.most_failed_tests.push(test_case("bazel_build", "Suite", "test", 1, 2.0));
+
+        let parity = TestParity::from(&String::from("bazel_build"), &String::from("cmake_build"), &metrics).unwrap();
+
+        assert_eq!(parity.base_test_count(), 1);
+        assert!(!parity.migration_runs_fewer_tests());
+    }
+
+    #[test]
+    fn display_test_parity_warns_when_migration_job_runs_fewer_tests() {
+        let metrics = test_metrics(vec![test_case("cmake_build", "Suite", "test", 0, 1.0)]);
+
+        let parity = TestParity::from(&String::from("bazel_build"), &String::from("cmake_build"), &metrics).unwrap();
+        let display = format!("{}", parity);
+
+        assert!(display.contains("!!! WARNING: bazel_build does not run 1 test case(s) executed by cmake_build (0 vs 1 test cases) !!!"));
+    }
+
+    #[test]
+    fn test_parity_only_warns_about_tests_missing_from_truncated_lists() {
+        let mut metrics = test_metrics(vec![test_case("bazel_build", "Suite", "shared", 1, 1.0)]);
+        metrics.slowest_tests = vec![
+            test_case("cmake_build", "Suite", "shared", 1, 3.0),
+            test_case("cmake_build", "Suite", "slow_with_cmake", 0, 2.0),
+        ];
+        metrics.slowest_tests_extra = 40;
+
+        let parity = TestParity::from(&String::from("bazel_build"), &String::from("cmake_build"), &metrics).unwrap();
+        let display = format!("{}", parity);
+
+        assert_eq!(parity.base_only, vec![String::from("Suite.slow_with_cmake")]);
+        assert!(!parity.lists_complete());
+        assert!(!parity.migration_runs_fewer_tests());
+        assert!(!display.contains("does not run"));
+        assert!(display.contains("!!! WARNING: 1 test case(s) listed for cmake_build aren't listed for bazel_build, the lists of the test metrics endpoint are truncated"));
+        assert!(display.contains("Note: 0 most failed and 40 slowest test case(s) were not listed"));
+    }
+
+    fn test_metrics(tests: Vec<TestCase>) -> TestMetrics {
+        TestMetrics {
+            average_test_count: 0,
+            most_failed_tests: tests,
+            most_failed_tests_extra: 0,
+            slowest_tests: vec![],
+            slowest_tests_extra: 0,
+            total_test_runs: 0,
+            test_runs: vec![],
+        }
+    }
+
+    fn test_case(job_name: &str, classname: &str, test_name: &str, failed_runs: u64, p95_duration: f64) -> TestCase {
+        TestCase {
+            failed_runs,
+            job_name: job_name.to_string(),
+            p95_duration,
+            test_name: test_name.to_string(),
+            classname: classname.to_string(),
+            file: None,
+            flaky: false,
+            source: "gtest".to_string(),
+            total_runs: 10,
+        }
+    }
+}
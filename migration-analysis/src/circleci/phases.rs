@@ -0,0 +1,301 @@
+use super::runs::{successful_job_number, PipelineRun, RunSource};
+use super::steps::JobSteps;
+use crate::stats::mean;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Error,ErrorKind};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Environment,
+    Checkout,
+    Dependencies,
+    Build,
+    Test,
+    Other,
+}
+
+impl FromStr for Phase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Phase, Error> {
+        match s.to_lowercase().as_str() {
+            "environment" => Ok(Phase::Environment),
+            "checkout" => Ok(Phase::Checkout),
+            "dependencies" => Ok(Phase::Dependencies),
+            "build" => Ok(Phase::Build),
+            "test" => Ok(Phase::Test),
+            "other" => Ok(Phase::Other),
+            _ => Err(Error::new(ErrorKind::InvalidInput, format!("unknown phase {}, expected one of environment, checkout, dependencies, build, test, other", s))),
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Phase::Environment => "environment",
+            Phase::Checkout => "checkout",
+            Phase::Dependencies => "dependencies",
+            Phase::Build => "build",
+            Phase::Test => "test",
+            Phase::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Assigns job steps to phases, by name unless the step is listed in the overrides.
+#[derive(Debug, Default)]
+pub struct StepMapping {
+    overrides: BTreeMap<String, Phase>,
+}
+
+impl StepMapping {
+    pub fn from(overrides: &BTreeMap<String, String>) -> Result<StepMapping, Error> {
+        let mut mapping = StepMapping::default();
+        for (step, phase) in overrides {
+            mapping.overrides.insert(step.to_string(), Phase::from_str(phase)?);
+        }
+        Ok(mapping)
+    }
+
+    pub fn phase_of(&self, step_name: &str) -> Phase {
+        if let Some(phase) = self.overrides.get(step_name) {
+            return *phase;
+        }
+        let name = step_name.to_lowercase();
+        if name.contains("environment") {
+            return Phase::Environment;
+        }
+        if name.contains("checkout") {
+            return Phase::Checkout;
+        }
+        if ["install", "setup", "dependenc", "restor", "apt"].iter().any(|k| name.contains(k)) {
+            return Phase::Dependencies;
+        }
+        if name.contains("test") {
+            return Phase::Test;
+        }
+        if ["build", "compil", "make", "bazel"].iter().any(|k| name.contains(k)) {
+            return Phase::Build;
+        }
+        Phase::Other
+    }
+}
+
+#[derive(Debug)]
+pub struct PhaseComparison {
+    pub phase: Phase,
+    pub base_duration: f64,
+    pub migration_duration: f64,
+    pub base_steps: Vec<String>,
+    pub migration_steps: Vec<String>,
+}
+
+impl PhaseComparison {
+    pub fn differential(&self) -> f64 {
+        self.migration_duration - self.base_duration
+    }
+}
+
+#[derive(Debug)]
+pub struct StepsInsight {
+    pub base_job: String,
+    pub migration_job: String,
+    pub base_samples: usize,
+    pub migration_samples: usize,
+    pub phases: Vec<PhaseComparison>,
+}
+
+impl StepsInsight {
+    /// Compare the mean time the base and migration jobs spend in each phase.
+    pub fn from(migration_job_name: &String, base_job_name: &String, migration_jobs: &[JobSteps], base_jobs: &[JobSteps], mapping: &StepMapping) -> Result<StepsInsight, Error> {
+        if base_jobs.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find steps of job {}", &base_job_name)));
+        }
+        if migration_jobs.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("failed to find steps of job {}", &migration_job_name)));
+        }
+        let base = phase_durations(base_jobs, mapping);
+        let mut migration = phase_durations(migration_jobs, mapping);
+
+        let mut phases = vec![];
+        for (phase, (base_duration, base_steps)) in base {
+            let (migration_duration, migration_steps) = migration.remove(&phase).unwrap_or_default();
+            phases.push(PhaseComparison { phase, base_duration, migration_duration, base_steps, migration_steps });
+        }
+        for (phase, (migration_duration, migration_steps)) in migration {
+            phases.push(PhaseComparison { phase, base_duration: 0.0, migration_duration, base_steps: vec![], migration_steps });
+        }
+        phases.sort_by_key(|p| p.phase);
+
+        Ok(StepsInsight {
+            base_job: String::from(base_job_name),
+            migration_job: String::from(migration_job_name),
+            base_samples: base_jobs.len(),
+            migration_samples: migration_jobs.len(),
+            phases,
+        })
+    }
+}
+
+impl fmt::Display for StepsInsight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let phases: String = self.phases.iter()
+            .map(|p| format!(
+                "\n            {} duration - differential: {:.1} ({:.1}s -> {:.1}s)\n                {} steps: {}\n                {} steps: {}",
+                p.phase,
+                p.differential(),
+                p.base_duration,
+                p.migration_duration,
+                self.base_job,
+                format_steps(&p.base_steps),
+                self.migration_job,
+                format_steps(&p.migration_steps)))
+            .collect();
+
+        let display = format!(r#"
+        Step analysis:
+
+        Details:
+
+            base job: {} ({} runs)
+            migration job: {} ({} runs)
+
+        Mean duration per phase:
+{}
+        "#,
+        self.base_job,
+        self.base_samples,
+        self.migration_job,
+        self.migration_samples,
+        phases);
+        write!(f, "{}", display)
+    }
+}
+
+/// Retrieve the steps of the successful base and migration jobs of the runs.
+pub async fn collect_job_steps(source: &(dyn RunSource + Sync), runs: &[PipelineRun]) -> Result<(Vec<JobSteps>, Vec<JobSteps>), Error> {
+    let mut migration_jobs = vec![];
+    let mut base_jobs = vec![];
+    for run in runs {
+        if let Some(job_number) = successful_job_number(&run.migration_job) {
+            migration_jobs.push(source.get_job_steps(job_number).await?);
+        }
+        if let Some(job_number) = successful_job_number(&run.base_job) {
+            base_jobs.push(source.get_job_steps(job_number).await?);
+        }
+    }
+    Ok((migration_jobs, base_jobs))
+}
+
+fn phase_durations(jobs: &[JobSteps], mapping: &StepMapping) -> BTreeMap<Phase, (f64, Vec<String>)> {
+    let mut durations: BTreeMap<Phase, (Vec<f64>, Vec<String>)> = BTreeMap::new();
+    for (index, job) in jobs.iter().enumerate() {
+        for step in &job.steps {
+            let (job_durations, steps) = durations.entry(mapping.phase_of(&step.name)).or_default();
+            // Phases missing from earlier jobs took no time in them.
+            job_durations.resize(index + 1, 0.0);
+            job_durations[index] += step.duration();
+            if !steps.contains(&step.name) {
+                steps.push(step.name.to_string());
+            }
+        }
+    }
+    durations.into_iter()
+        .map(|(phase, (mut job_durations, steps))| {
+            job_durations.resize(jobs.len(), 0.0);
+            (phase, (mean(&job_durations).unwrap_or(0.0), steps))
+        })
+        .collect()
+}
+
+fn format_steps(steps: &[String]) -> String {
+    if steps.is_empty() {
+        return String::from("none");
+    }
+    steps.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::steps::{Action, Step};
+
+    #[test]
+    fn step_mapping_classifies_steps_by_name() {
+        let mapping = StepMapping::default();
+
+        assert_eq!(mapping.phase_of("Spin up environment"), Phase::Environment);
+        assert_eq!(mapping.phase_of("Checkout code"), Phase::Checkout);
+        assert_eq!(mapping.phase_of("Install project dependencies"), Phase::Dependencies);
+        assert_eq!(mapping.phase_of("Build vSOMEIP"), Phase::Build);
+        assert_eq!(mapping.phase_of("Test migration analysis"), Phase::Test);
+        assert_eq!(mapping.phase_of("Upload artifacts"), Phase::Other);
+    }
+
+    #[test]
+    fn step_mapping_overrides_take_precedence() {
+        let overrides = BTreeMap::from([(String::from("Build nothing"), String::from("other"))]);
+
+        let mapping = StepMapping::from(&overrides).unwrap();
+
+        assert_eq!(mapping.phase_of("Build nothing"), Phase::Other);
+    }
+
+    #[test]
+    fn step_mapping_fails_on_unknown_phase() {
+        let overrides = BTreeMap::from([(String::from("Build nothing"), String::from("compile"))]);
+
+        let actual_err = StepMapping::from(&overrides).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn steps_insight_compares_mean_phase_durations() {
+        let base_jobs = vec![
+            job_steps(vec![step("Install project dependencies", 200_000), step("Build vSOMEIP", 300_000)]),
+            job_steps(vec![step("Install project dependencies", 100_000), step("Build vSOMEIP", 100_000)]),
+        ];
+        let migration_jobs = vec![job_steps(vec![step("bazel build //...", 120_000), step("bazel test //...", 30_000)])];
+
+        let insight = StepsInsight::from(&String::from("bazel_build"), &String::from("cmake_build"), &migration_jobs, &base_jobs, &StepMapping::default()).unwrap();
+
+        let phases: Vec<(Phase, f64)> = insight.phases.iter().map(|p| (p.phase, p.differential())).collect();
+        assert_eq!(phases, vec![(Phase::Dependencies, -150.0), (Phase::Build, -80.0), (Phase::Test, 30.0)]);
+        assert_eq!(insight.phases[1].base_steps, vec![String::from("Build vSOMEIP")]);
+    }
+
+    #[test]
+    fn phase_durations_count_missing_phases_as_zero() {
+        let jobs = vec![
+            job_steps(vec![step("Build", 10_000)]),
+            job_steps(vec![step("Build", 10_000), step("Run tests", 10_000)]),
+        ];
+
+        let durations = phase_durations(&jobs, &StepMapping::default());
+
+        assert_eq!(durations.get(&Phase::Test).unwrap().0, 5.0);
+    }
+
+    fn job_steps(steps: Vec<Step>) -> JobSteps {
+        JobSteps { build_num: 1, steps }
+    }
+
+    fn step(name: &str, run_time_millis: u64) -> Step {
+        Step {
+            name: name.to_string(),
+            actions: vec![Action {
+                name: name.to_string(),
+                index: 0,
+                status: Some(String::from("success")),
+                run_time_millis: Some(run_time_millis),
+                start_time: None,
+                end_time: None,
+            }],
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+
+use super::jobs::JobRun;
+
+// Statuses of workflows which will not change anymore.
+const WORKFLOW_FINAL_STATUSES: [&str; 6] = ["success", "failed", "error", "canceled", "unauthorized", "not_run"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub next_page_token: Option<String>,
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub id: String,
+    pub project_slug: String,
+    pub number: u64,
+    pub state: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub trigger: Trigger,
+    pub vcs: Option<Vcs>,
+}
+
+impl Pipeline {
+    pub fn revision(&self) -> Option<&String> {
+        self.vcs.as_ref().map(|vcs| &vcs.revision)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerPipelineRequest {
+    pub branch: String,
+    pub parameters: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggeredPipeline {
+    pub id: String,
+    pub state: String,
+    pub number: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trigger {
+    #[serde(rename = "type")]
+    pub trigger_type: String,
+    pub received_at: DateTime<Utc>,
+    pub actor: Actor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Actor {
+    pub login: String,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vcs {
+    pub provider_name: String,
+    pub origin_repository_url: String,
+    pub target_repository_url: String,
+    pub revision: String,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub commit: Option<Commit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Commit {
+    pub subject: Option<String>,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workflow {
+    pub id: String,
+    pub name: String,
+    pub pipeline_id: String,
+    pub pipeline_number: u64,
+    pub project_slug: String,
+    pub status: String,
+    pub started_by: String,
+    pub created_at: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+}
+
+impl Workflow {
+    pub fn is_finished(&self) -> bool {
+        WORKFLOW_FINAL_STATUSES.contains(&self.status.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowJob {
+    pub id: String,
+    pub name: String,
+    pub job_number: Option<u64>,
+    pub project_slug: String,
+    pub status: String,
+    #[serde(rename = "type")]
+    pub job_type: String,
+    pub dependencies: Vec<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub stopped_at: Option<DateTime<Utc>>,
+}
+
+impl WorkflowJob {
+    pub fn duration(&self) -> Option<Duration> {
+        match (self.started_at, self.stopped_at) {
+            (Some(started_at), Some(stopped_at)) => Some(stopped_at - started_at),
+            _ => None,
+        }
+    }
+
+    /// The run of a job which ran to completion, canceled or skipped jobs have none.
+    pub fn job_run(&self) -> Option<JobRun> {
+        let success = match self.status.as_str() {
+            "success" => true,
+            "failed" | "infrastructure_fail" | "timedout" => false,
+            _ => return None,
+        };
+        Some(JobRun {
+            started_at: self.started_at?,
+            stopped_at: self.stopped_at?,
+            success,
+        })
+    }
+}
@@ -0,0 +1,466 @@
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::io::{Error,ErrorKind};
+
+use super::client::Client;
+use super::job_details::JobDetails;
+use super::jobs::{Item, JobRun, Jobs};
+use super::pipelines::{Pipeline, Workflow, WorkflowJob};
+use super::steps::JobSteps;
+use crate::stats::mean;
+
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait RunSource {
+    async fn get_pipelines(&self, branch: &str, max_pipelines: usize) -> Result<Vec<Pipeline>, Error>;
+    async fn get_pipelines_since(&self, branch: &str, since: DateTime<Utc>) -> Result<Vec<Pipeline>, Error>;
+    async fn get_pipeline_workflows(&self, pipeline_id: &str) -> Result<Vec<Workflow>, Error>;
+    async fn get_workflow_jobs(&self, workflow_id: &str) -> Result<Vec<WorkflowJob>, Error>;
+    async fn get_job_details(&self, job_number: u64) -> Result<JobDetails, Error>;
+    async fn get_job_steps(&self, job_number: u64) -> Result<JobSteps, Error>;
+}
+
+#[async_trait]
+impl RunSource for Client {
+    async fn get_pipelines(&self, branch: &str, max_pipelines: usize) -> Result<Vec<Pipeline>, Error> {
+        self.get_pipelines(branch, max_pipelines).await
+    }
+
+    async fn get_pipelines_since(&self, branch: &str, since: DateTime<Utc>) -> Result<Vec<Pipeline>, Error> {
+        self.get_pipelines_since(branch, since).await
+    }
+
+    async fn get_pipeline_workflows(&self, pipeline_id: &str) -> Result<Vec<Workflow>, Error> {
+        self.get_pipeline_workflows(&pipeline_id.to_string()).await
+    }
+
+    async fn get_workflow_jobs(&self, workflow_id: &str) -> Result<Vec<WorkflowJob>, Error> {
+        self.get_workflow_jobs(&workflow_id.to_string()).await
+    }
+
+    async fn get_job_details(&self, job_number: u64) -> Result<JobDetails, Error> {
+        self.get_job_details(job_number).await
+    }
+
+    async fn get_job_steps(&self, job_number: u64) -> Result<JobSteps, Error> {
+        self.get_job_steps(job_number).await
+    }
+}
+
+/// A single execution of the analysed workflow together with the base and migration jobs it ran.
+#[derive(Debug, Clone)]
+pub struct PipelineRun {
+    pub pipeline_number: u64,
+    pub revision: Option<String>,
+    pub trigger_type: String,
+    pub created_at: DateTime<Utc>,
+    pub base_job: Option<WorkflowJob>,
+    pub migration_job: Option<WorkflowJob>,
+}
+
+pub async fn collect_runs(
+    source: &(dyn RunSource + Sync),
+    branch: &str,
+    workflow_name: &String,
+    migration_job_name: &String,
+    base_job_name: &String,
+    max_pipelines: usize,
+) -> Result<Vec<PipelineRun>, Error> {
+    let pipelines = source.get_pipelines(branch, max_pipelines).await?;
+    collect_pipeline_runs(source, &pipelines, workflow_name, migration_job_name, base_job_name).await
+}
+
+/// Runs of the pipelines created in a date range, to analyse any range and not only the reporting windows.
+pub async fn collect_runs_between(
+    source: &(dyn RunSource + Sync),
+    branch: &str,
+    workflow_name: &String,
+    migration_job_name: &String,
+    base_job_name: &String,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Vec<PipelineRun>, Error> {
+    let pipelines = pipelines_between(source, branch, since, until).await?;
+    collect_pipeline_runs(source, &pipelines, workflow_name, migration_job_name, base_job_name).await
+}
+
+/// Pipelines of the branch created in a date range.
+pub async fn pipelines_between(source: &(dyn RunSource + Sync), branch: &str, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<Pipeline>, Error> {
+    if since >= until {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("start of the range {} is not before its end {}", since.to_rfc3339(), until.to_rfc3339())));
+    }
+    let mut pipelines = source.get_pipelines_since(branch, since).await?;
+    pipelines.retain(|p| p.created_at < until);
+    Ok(pipelines)
+}
+
+pub async fn collect_pipeline_runs(
+    source: &(dyn RunSource + Sync),
+    pipelines: &[Pipeline],
+    workflow_name: &String,
+    migration_job_name: &String,
+    base_job_name: &String,
+) -> Result<Vec<PipelineRun>, Error> {
+    Ok(collect_workflow_jobs(source, pipelines, workflow_name).await?
+        .into_iter()
+        .map(|(pipeline, jobs)| PipelineRun {
+            pipeline_number: pipeline.number,
+            revision: pipeline.revision().cloned(),
+            trigger_type: pipeline.trigger.trigger_type.to_string(),
+            created_at: pipeline.created_at,
+            base_job: jobs.iter().find(|j| &j.name == base_job_name).cloned(),
+            migration_job: jobs.iter().find(|j| &j.name == migration_job_name).cloned(),
+        })
+        .collect())
+}
+
+/// Jobs of every run of the workflow in the pipelines, with the pipeline of the run.
+pub async fn collect_workflow_jobs<'a>(
+    source: &(dyn RunSource + Sync),
+    pipelines: &'a [Pipeline],
+    workflow_name: &String,
+) -> Result<Vec<(&'a Pipeline, Vec<WorkflowJob>)>, Error> {
+    let mut runs = vec![];
+    for pipeline in pipelines {
+        let workflows = source.get_pipeline_workflows(&pipeline.id).await?;
+        for workflow in workflows.iter().filter(|w| &w.name == workflow_name) {
+            runs.push((pipeline, source.get_workflow_jobs(&workflow.id).await?));
+        }
+    }
+    Ok(runs)
+}
+
+/// Aggregate the finished base and migration jobs of the runs into the shape of the insights endpoint.
+pub fn aggregate_jobs(runs: &[PipelineRun], migration_job_name: &String, base_job_name: &String) -> Result<Jobs, Error> {
+    let mut items = vec![];
+    for (name, jobs) in [
+        (base_job_name, runs.iter().filter_map(|r| r.base_job.as_ref()).collect::<Vec<&WorkflowJob>>()),
+        (migration_job_name, runs.iter().filter_map(|r| r.migration_job.as_ref()).collect::<Vec<&WorkflowJob>>()),
+    ] {
+        let job_runs: Vec<JobRun> = jobs.iter().filter_map(|j| j.job_run()).collect();
+        let item = Item::from_runs(name, &job_runs)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("failed to find successful runs of job {}", name)))?;
+        items.push(item);
+    }
+    Ok(Jobs { next_page_token: None, items, decode_failures: vec![] })
+}
+
+#[derive(Debug)]
+pub struct CommitComparison {
+    pub revision: String,
+    pub first_seen_at: DateTime<Utc>,
+    pub trigger_types: Vec<String>,
+    pub pipeline_numbers: Vec<u64>,
+    pub base_durations: Vec<f64>,
+    pub migration_durations: Vec<f64>,
+}
+
+impl CommitComparison {
+    pub fn base_mean_duration(&self) -> Option<f64> {
+        mean(&self.base_durations)
+    }
+
+    pub fn migration_mean_duration(&self) -> Option<f64> {
+        mean(&self.migration_durations)
+    }
+
+    pub fn differential(&self) -> Option<f64> {
+        match (self.migration_mean_duration(), self.base_mean_duration()) {
+            (Some(migration), Some(base)) => Some(migration - base),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RunsReport {
+    pub workflow: String,
+    pub base_job: String,
+    pub migration_job: String,
+    pub commits: Vec<CommitComparison>,
+}
+
+impl RunsReport {
+    /// Group runs by commit, only successful jobs contribute a duration.
+    pub fn from(workflow: &String, migration_job_name: &String, base_job_name: &String, runs: &[PipelineRun]) -> RunsReport {
+        let mut commits: Vec<CommitComparison> = vec![];
+        for run in runs {
+            let revision = run.revision.clone().unwrap_or_else(|| String::from("unknown"));
+            let index = match commits.iter().position(|c| c.revision == revision) {
+                Some(index) => index,
+                None => {
+                    commits.push(CommitComparison {
+                        revision,
+                        first_seen_at: run.created_at,
+                        trigger_types: vec![],
+                        pipeline_numbers: vec![],
+                        base_durations: vec![],
+                        migration_durations: vec![],
+                    });
+                    commits.len() - 1
+                }
+            };
+            let commit = &mut commits[index];
+            commit.first_seen_at = commit.first_seen_at.min(run.created_at);
+            if !commit.trigger_types.contains(&run.trigger_type) {
+                commit.trigger_types.push(run.trigger_type.to_string());
+            }
+            if !commit.pipeline_numbers.contains(&run.pipeline_number) {
+                commit.pipeline_numbers.push(run.pipeline_number);
+            }
+            if let Some(duration) = successful_duration(&run.base_job) {
+                commit.base_durations.push(duration);
+            }
+            if let Some(duration) = successful_duration(&run.migration_job) {
+                commit.migration_durations.push(duration);
+            }
+        }
+
+        RunsReport {
+            workflow: String::from(workflow),
+            base_job: String::from(base_job_name),
+            migration_job: String::from(migration_job_name),
+            commits,
+        }
+    }
+}
+
+impl fmt::Display for RunsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let commits: String = self.commits.iter()
+            .map(|c| format!(
+                "\n            {} {} via {} (pipelines {}): base {}, migration {}, differential {}",
+                short_revision(&c.revision),
+                c.first_seen_at.format("%Y-%m-%d %H:%M"),
+                c.trigger_types.join("/"),
+                c.pipeline_numbers.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(", "),
+                format_duration(c.base_mean_duration()),
+                format_duration(c.migration_mean_duration()),
+                format_duration(c.differential())))
+            .collect();
+
+        let display = format!(r#"
+        Per commit analysis:
+
+        Details:
+
+            workflow: {}
+            base job: {}
+            migration job: {}
+
+        Mean duration per commit:
+{}
+        "#,
+        self.workflow,
+        self.base_job,
+        self.migration_job,
+        commits);
+        write!(f, "{}", display)
+    }
+}
+
+pub fn successful_job_number(job: &Option<WorkflowJob>) -> Option<u64> {
+    job.as_ref()
+        .filter(|j| j.status == "success")
+        .and_then(|j| j.job_number)
+}
+
+fn successful_duration(job: &Option<WorkflowJob>) -> Option<f64> {
+    job.as_ref()
+        .filter(|j| j.status == "success")
+        .and_then(|j| j.duration())
+        .map(|d| d.num_milliseconds() as f64 / 1000.0)
+}
+
+fn short_revision(revision: &str) -> &str {
+    &revision[..revision.len().min(12)]
+}
+
+fn format_duration(duration: Option<f64>) -> String {
+    match duration {
+        Some(seconds) => format!("{:.1}s", seconds),
+        None => String::from("n/a"),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use super::super::pipelines::{Actor, Trigger, Vcs};
+    use mockall::predicate::{eq, function};
+
+    #[tokio::test]
+    async fn collect_runs_matches_jobs_of_workflow_runs() {
+        let mut source = MockRunSource::new();
+        source.expect_get_pipelines()
+            .with(function(|branch: &str| branch == "main"), eq(10))
+            .returning(|_, _| Ok(vec![pipeline("pipeline-1", 1, "abc")]));
+        source.expect_get_pipeline_workflows()
+            .with(function(|id: &str| id == "pipeline-1"))
+            .returning(|_| Ok(vec![workflow("workflow-1", "build"), workflow("workflow-2", "nightly")]));
+        source.expect_get_workflow_jobs()
+            .with(function(|id: &str| id == "workflow-1"))
+            .times(1)
+            .returning(|_| Ok(vec![job("cmake_build", "success", 100), job("bazel_build", "success", 80)]));
+
+        let runs = collect_runs(&source, "main", &String::from("build"), &String::from("bazel_build"), &String::from("cmake_build"), 10).await.unwrap();
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].revision, Some(String::from("abc")));
+        assert_eq!(runs[0].base_job.as_ref().unwrap().name, "cmake_build");
+        assert_eq!(runs[0].migration_job.as_ref().unwrap().name, "bazel_build");
+    }
+
+    #[tokio::test]
+    async fn collect_runs_between_keeps_pipelines_of_range() {
+        let since = Utc::now() - chrono::Duration::days(7);
+        let until = Utc::now() - chrono::Duration::days(1);
+        let mut source = MockRunSource::new();
+        source.expect_get_pipelines_since()
+            .with(function(|branch: &str| branch == "main"), eq(since))
+            .returning(|_, _| {
+                let mut recent = pipeline("pipeline-2", 2, "def");
+                recent.created_at = Utc::now();
+                let mut older = pipeline("pipeline-1", 1, "abc");
+                older.created_at = Utc::now() - chrono::Duration::days(3);
+                Ok(vec![recent, older])
+            });
+        source.expect_get_pipeline_workflows()
+            .with(function(|id: &str| id == "pipeline-1"))
+            .times(1)
+            .returning(|_| Ok(vec![workflow("workflow-1", "build")]));
+        source.expect_get_workflow_jobs()
+            .returning(|_| Ok(vec![job("cmake_build", "success", 100), job("bazel_build", "success", 80)]));
+
+        let runs = collect_runs_between(&source, "main", &String::from("build"), &String::from("bazel_build"), &String::from("cmake_build"), since, until).await.unwrap();
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].pipeline_number, 1);
+    }
+
+    #[tokio::test]
+    async fn collect_runs_between_fails_on_empty_range() {
+        let source = MockRunSource::new();
+        let now = Utc::now();
+
+        let actual_err = collect_runs_between(&source, "main", &String::from("build"), &String::from("bazel_build"), &String::from("cmake_build"), now, now).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn aggregate_jobs_returns_insights_of_base_and_migration_jobs() {
+        let runs = vec![
+            run(1, "abc", job("cmake_build", "success", 100), job("bazel_build", "success", 80)),
+            run(2, "def", job("cmake_build", "success", 120), job("bazel_build", "failed", 10)),
+        ];
+
+        let jobs = aggregate_jobs(&runs, &String::from("bazel_build"), &String::from("cmake_build")).unwrap();
+
+        assert_eq!(jobs.items[0].name, "cmake_build");
+        assert_eq!(jobs.items[0].metrics.duration_metrics.as_ref().unwrap().mean, 110);
+        assert_eq!(jobs.items[1].name, "bazel_build");
+        assert_eq!(jobs.items[1].metrics.total_runs, 2);
+        assert_eq!(jobs.items[1].metrics.duration_metrics.as_ref().unwrap().mean, 80);
+    }
+
+    #[test]
+    fn aggregate_jobs_fails_without_successful_runs() {
+        let runs = vec![
+            run(1, "abc", job("cmake_build", "success", 100), job("bazel_build", "failed", 80)),
+            run(2, "abc", job("cmake_build", "success", 100), job("bazel_build", "canceled", 10)),
+        ];
+
+        let actual_err = aggregate_jobs(&runs, &String::from("bazel_build"), &String::from("cmake_build")).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(actual_err.into_inner().unwrap().to_string(), "failed to find successful runs of job bazel_build");
+    }
+
+    #[test]
+    fn runs_report_groups_successful_durations_by_commit() {
+        let runs = vec![
+            run(1, "abc", job("cmake_build", "success", 100), job("bazel_build", "success", 80)),
+            run(2, "abc", job("cmake_build", "success", 120), job("bazel_build", "failed", 10)),
+            run(3, "def", job("cmake_build", "success", 100), job("bazel_build", "success", 110)),
+        ];
+
+        let report = RunsReport::from(&String::from("build"), &String::from("bazel_build"), &String::from("cmake_build"), &runs);
+
+        assert_eq!(report.commits.len(), 2);
+        assert_eq!(report.commits[0].revision, "abc");
+        assert_eq!(report.commits[0].pipeline_numbers, vec![1, 2]);
+        assert_eq!(report.commits[0].base_mean_duration(), Some(110.0));
+        assert_eq!(report.commits[0].migration_mean_duration(), Some(80.0));
+        assert_eq!(report.commits[0].differential(), Some(-30.0));
+        assert_eq!(report.commits[1].differential(), Some(10.0));
+    }
+
+    pub(crate) fn pipeline(id: &str, number: u64, revision: &str) -> Pipeline {
+        Pipeline {
+            id: id.to_string(),
+            project_slug: String::from("gh/org/project"),
+            number,
+            state: String::from("created"),
+            created_at: Utc::now(),
+            updated_at: None,
+            trigger: Trigger {
+                trigger_type: String::from("webhook"),
+                received_at: Utc::now(),
+                actor: Actor { login: String::from("user"), avatar_url: None },
+            },
+            vcs: Some(Vcs {
+                provider_name: String::from("GitHub"),
+                origin_repository_url: String::from("https://github.com/org/project"),
+                target_repository_url: String::from("https://github.com/org/project"),
+                revision: revision.to_string(),
+                branch: Some(String::from("main")),
+                tag: None,
+                commit: None,
+            }),
+        }
+    }
+
+    pub(crate) fn workflow(id: &str, name: &str) -> Workflow {
+        Workflow {
+            id: id.to_string(),
+            name: name.to_string(),
+            pipeline_id: String::from("pipeline-1"),
+            pipeline_number: 1,
+            project_slug: String::from("gh/org/project"),
+            status: String::from("success"),
+            started_by: String::from("user"),
+            created_at: Utc::now(),
+            stopped_at: None,
+        }
+    }
+
+    pub(crate) fn job(name: &str, status: &str, duration_seconds: i64) -> WorkflowJob {
+        let started_at = Utc::now();
+        WorkflowJob {
+            id: format!("{}-id", name),
+            name: name.to_string(),
+            job_number: Some(1),
+            project_slug: String::from("gh/org/project"),
+            status: status.to_string(),
+            job_type: String::from("build"),
+            dependencies: vec![],
+            started_at: Some(started_at),
+            stopped_at: Some(started_at + chrono::Duration::seconds(duration_seconds)),
+        }
+    }
+
+    fn run(pipeline_number: u64, revision: &str, base_job: WorkflowJob, migration_job: WorkflowJob) -> PipelineRun {
+        PipelineRun {
+            pipeline_number,
+            revision: Some(revision.to_string()),
+            trigger_type: String::from("webhook"),
+            created_at: Utc::now(),
+            base_job: Some(base_job),
+            migration_job: Some(migration_job),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+/// Step CircleCI adds to every job for the executor to start, e.g. pull the image or boot the machine.
+pub const SPIN_UP_STEP: &str = "Spin up environment";
+
+/// Job details of the v1.1 API, the only one exposing the steps of a job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSteps {
+    pub build_num: u64,
+    pub steps: Vec<Step>,
+}
+
+impl JobSteps {
+    /// Seconds the executor took to spin up, none when the job has no spin-up step.
+    pub fn spin_up_time(&self) -> Option<f64> {
+        self.steps.iter().find(|s| s.name == SPIN_UP_STEP).map(|s| s.duration())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub name: String,
+    pub actions: Vec<Action>,
+}
+
+impl Step {
+    /// Duration in seconds, parallel actions of a step run at the same time.
+    pub fn duration(&self) -> f64 {
+        self.actions.iter()
+            .filter_map(|a| a.run_time_millis)
+            .max()
+            .unwrap_or(0) as f64 / 1000.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Action {
+    pub name: String,
+    pub index: u64,
+    pub status: Option<String>,
+    pub run_time_millis: Option<u64>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+}
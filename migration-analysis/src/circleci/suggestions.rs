@@ -0,0 +1,70 @@
+/// Find the candidate closest to a mistyped name, if any is close enough to be a typo.
+pub fn did_you_mean<'a>(name: &str, candidates: impl IntoIterator<Item = &'a String>) -> Option<&'a String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+    candidates.into_iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Error message suffix suggesting the closest candidate.
+pub fn suggestion<'a>(name: &str, candidates: impl IntoIterator<Item = &'a String>) -> String {
+    match did_you_mean(name, candidates) {
+        Some(candidate) => format!(", did you mean {}?", candidate),
+        None => String::new(),
+    }
+}
+
+// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn did_you_mean_returns_closest_candidate() {
+        let candidates = names(&["cmake_build", "bazel_build", "bazel_test"]);
+
+        assert_eq!(did_you_mean("bazle_build", &candidates), Some(&candidates[1]));
+        assert_eq!(did_you_mean("CMake_Build", &candidates), Some(&candidates[0]));
+    }
+
+    #[test]
+    fn did_you_mean_ignores_distant_candidates() {
+        let candidates = names(&["cmake_build", "bazel_build"]);
+
+        assert_eq!(did_you_mean("deploy", &candidates), None);
+    }
+
+    #[test]
+    fn suggestion_is_empty_without_candidates() {
+        assert_eq!(suggestion("bazel_build", &names(&[])), "");
+        assert_eq!(suggestion("bazel_buid", &names(&["bazel_build"])), ", did you mean bazel_build?");
+    }
+
+    #[test]
+    fn edit_distance_counts_edits() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", "abc"), 0);
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+}
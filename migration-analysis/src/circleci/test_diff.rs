@@ -0,0 +1,172 @@
+use super::job_tests::JobTest;
+use super::parity::test_identifier;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct StatusChange {
+    pub test: String,
+    pub base_result: String,
+    pub migration_result: String,
+}
+
+#[derive(Debug)]
+pub struct TestRunDiff {
+    pub base_job_number: u64,
+    pub migration_job_number: u64,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub status_changed: Vec<StatusChange>,
+    pub unchanged: usize,
+}
+
+impl TestRunDiff {
+    pub fn from(base_job_number: u64, migration_job_number: u64, base_tests: &[JobTest], migration_tests: &[JobTest]) -> TestRunDiff {
+        let base_results = results_by_test(base_tests);
+        let mut migration_results = results_by_test(migration_tests);
+
+        let mut removed = vec![];
+        let mut status_changed = vec![];
+        let mut unchanged = 0;
+        for (test, base_result) in base_results {
+            match migration_results.remove(&test) {
+                Some(migration_result) if migration_result == base_result => unchanged += 1,
+                Some(migration_result) => status_changed.push(StatusChange { test, base_result, migration_result }),
+                None => removed.push(test),
+            }
+        }
+
+        TestRunDiff {
+            base_job_number,
+            migration_job_number,
+            added: migration_results.into_keys().collect(),
+            removed,
+            status_changed,
+            unchanged,
+        }
+    }
+}
+
+impl fmt::Display for TestRunDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status_changed: Vec<String> = self.status_changed.iter()
+            .map(|c| format!("{}: {} -> {}", c.test, c.base_result, c.migration_result))
+            .collect();
+
+        let display = format!(r#"
+        Job test results comparison:
+
+        Details:
+
+            base job number: {}
+            migration job number: {}
+            unchanged test cases: {}
+
+        Added by the migration job:
+{}
+        Removed by the migration job:
+{}
+        Status changed (base -> migration):
+{}"#,
+        self.base_job_number,
+        self.migration_job_number,
+        self.unchanged,
+        format_lines(&self.added),
+        format_lines(&self.removed),
+        format_lines(&status_changed));
+        write!(f, "{}", display)
+    }
+}
+
+// Test cases reported more than once, e.g. retried ones, keep all their results in the order of the report.
+fn results_by_test(tests: &[JobTest]) -> BTreeMap<String, String> {
+    let mut results: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for test in tests {
+        results.entry(test_identifier(&test.classname, &test.name)).or_default().push(&test.result);
+    }
+    results.into_iter()
+        .map(|(test, results)| (test, results.join(", ")))
+        .collect()
+}
+
+fn format_lines(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::from("\n            none\n");
+    }
+    let formatted: String = lines.iter().map(|l| format!("\n            {}", l)).collect();
+    format!("{}\n", formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_diff_lists_added_removed_and_changed_tests() {
+        let base_tests = vec![
+            job_test("Suite", "unchanged", "success"),
+            job_test("Suite", "removed", "success"),
+            job_test("Suite", "fixed", "failure"),
+            job_test("Suite", "broken", "success"),
+        ];
+        let migration_tests = vec![
+            job_test("Suite", "unchanged", "success"),
+            job_test("Suite", "fixed", "success"),
+            job_test("Suite", "broken", "skipped"),
+            job_test("", "added", "success"),
+        ];
+
+        let diff = TestRunDiff::from(1, 2, &base_tests, &migration_tests);
+
+        assert_eq!(diff.added, vec![String::from("added")]);
+        assert_eq!(diff.removed, vec![String::from("Suite.removed")]);
+        assert_eq!(diff.status_changed, vec![
+            StatusChange { test: String::from("Suite.broken"), base_result: String::from("success"), migration_result: String::from("skipped") },
+            StatusChange { test: String::from("Suite.fixed"), base_result: String::from("failure"), migration_result: String::from("success") },
+        ]);
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn display_test_run_diff_lists_status_changes() {
+        let diff = TestRunDiff::from(1, 2, &[job_test("Suite", "test", "success")], &[job_test("Suite", "test", "failure")]);
+
+        let display = format!("{}", diff);
+
+        assert!(display.contains("Suite.test: success -> failure"));
+    }
+
+    #[test]
+    fn test_run_diff_keeps_all_results_of_repeated_tests() {
+        let base_tests = vec![
+            job_test("Suite", "flaky", "failure"),
+            job_test("Suite", "flaky", "success"),
+            job_test("Suite", "stable", "success"),
+            job_test("Suite", "stable", "success"),
+        ];
+        let migration_tests = vec![
+            job_test("Suite", "flaky", "success"),
+            job_test("Suite", "stable", "success"),
+            job_test("Suite", "stable", "success"),
+        ];
+
+        let diff = TestRunDiff::from(1, 2, &base_tests, &migration_tests);
+
+        assert_eq!(diff.status_changed, vec![
+            StatusChange { test: String::from("Suite.flaky"), base_result: String::from("failure, success"), migration_result: String::from("success") },
+        ]);
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    fn job_test(classname: &str, name: &str, result: &str) -> JobTest {
+        JobTest {
+            name: name.to_string(),
+            classname: classname.to_string(),
+            result: result.to_string(),
+            message: None,
+            run_time: 0.1,
+            source: String::from("gtest"),
+            file: None,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestMetrics {
+    pub average_test_count: u64,
+    pub most_failed_tests: Vec<TestCase>,
+    pub most_failed_tests_extra: u64,
+    pub slowest_tests: Vec<TestCase>,
+    pub slowest_tests_extra: u64,
+    pub total_test_runs: u64,
+    pub test_runs: Vec<TestRun>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCase {
+    pub failed_runs: u64,
+    pub job_name: String,
+    pub p95_duration: f64,
+    pub test_name: String,
+    pub classname: String,
+    pub file: Option<String>,
+    pub flaky: bool,
+    pub source: String,
+    pub total_runs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRun {
+    pub pipeline_number: u64,
+    pub workflow_id: String,
+    pub success_rate: f64,
+    pub test_counts: TestCounts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCounts {
+    pub error: u64,
+    pub failure: u64,
+    pub skipped: u64,
+    pub success: u64,
+    pub total: u64,
+}
@@ -0,0 +1,270 @@
+use super::job_details::JobDetails;
+use super::runs::{successful_job_number, PipelineRun, RunSource};
+use super::steps::JobSteps;
+use crate::stats::{mean, median};
+use std::fmt;
+use std::io::{Error,ErrorKind};
+
+/// Details and steps of a job run, the steps tell the executor spin-up apart from the build.
+#[derive(Debug, Clone)]
+pub struct TimedJob {
+    pub details: JobDetails,
+    pub steps: JobSteps,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TimingMetrics {
+    pub samples: usize,
+    pub executor: String,
+    pub mean_queue_time: f64,
+    pub median_queue_time: f64,
+    pub mean_spin_up_time: f64,
+    pub median_spin_up_time: f64,
+    /// Time running on the executor once it's spun up
+    pub mean_execution_time: f64,
+    pub median_execution_time: f64,
+}
+
+impl TimingMetrics {
+    pub fn from(jobs: &[TimedJob]) -> Option<TimingMetrics> {
+        let timed: Vec<(f64, f64, f64)> = jobs.iter()
+            .filter_map(|j| match (j.details.queue_time(), j.details.execution_time()) {
+                (Some(queue), Some(execution)) => {
+                    let spin_up = j.steps.spin_up_time().unwrap_or(0.0);
+                    Some((seconds(queue), spin_up, seconds(execution) - spin_up))
+                },
+                _ => None,
+            })
+            .collect();
+        let queue_times: Vec<f64> = timed.iter().map(|t| t.0).collect();
+        let spin_up_times: Vec<f64> = timed.iter().map(|t| t.1).collect();
+        let execution_times: Vec<f64> = timed.iter().map(|t| t.2).collect();
+
+        Some(TimingMetrics {
+            samples: timed.len(),
+            executor: jobs.first()
+                .map(|j| format!("{} ({})", j.details.executor.executor_type, j.details.executor.resource_class))
+                .unwrap_or_default(),
+            mean_queue_time: mean(&queue_times)?,
+            median_queue_time: median(&queue_times)?,
+            mean_spin_up_time: mean(&spin_up_times)?,
+            median_spin_up_time: median(&spin_up_times)?,
+            mean_execution_time: mean(&execution_times)?,
+            median_execution_time: median(&execution_times)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct TimingInsight {
+    pub base_job: String,
+    pub migration_job: String,
+    pub base: TimingMetrics,
+    pub migration: TimingMetrics,
+}
+
+impl TimingInsight {
+    pub fn from(migration_job_name: &String, base_job_name: &String, migration_jobs: &[TimedJob], base_jobs: &[TimedJob]) -> Result<TimingInsight, Error> {
+        let base = TimingMetrics::from(base_jobs)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("failed to find finished runs of job {}", &base_job_name)))?;
+        let migration = TimingMetrics::from(migration_jobs)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("failed to find finished runs of job {}", &migration_job_name)))?;
+
+        Ok(TimingInsight {
+            base_job: String::from(base_job_name),
+            migration_job: String::from(migration_job_name),
+            base,
+            migration,
+        })
+    }
+
+    pub fn median_queue_time_differential(&self) -> f64 {
+        self.migration.median_queue_time - self.base.median_queue_time
+    }
+
+    pub fn median_spin_up_time_differential(&self) -> f64 {
+        self.migration.median_spin_up_time - self.base.median_spin_up_time
+    }
+
+    pub fn median_execution_time_differential(&self) -> f64 {
+        self.migration.median_execution_time - self.base.median_execution_time
+    }
+
+    pub fn mean_queue_time_differential(&self) -> f64 {
+        self.migration.mean_queue_time - self.base.mean_queue_time
+    }
+
+    pub fn mean_spin_up_time_differential(&self) -> f64 {
+        self.migration.mean_spin_up_time - self.base.mean_spin_up_time
+    }
+
+    pub fn mean_execution_time_differential(&self) -> f64 {
+        self.migration.mean_execution_time - self.base.mean_execution_time
+    }
+
+    /// Names the part of the job which dominates the median duration differential,
+    /// queue and spin-up time both count towards the executor.
+    pub fn dominant_factor(&self) -> &'static str {
+        let queue = self.median_queue_time_differential();
+        let spin_up = self.median_spin_up_time_differential();
+        if self.median_execution_time_differential().abs() >= (queue + spin_up).abs() {
+            return "execution (the build itself)";
+        }
+        if spin_up.abs() > queue.abs() {
+            return "spin-up (the executor)";
+        }
+        "queue (the executor)"
+    }
+}
+
+impl fmt::Display for TimingInsight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let display = format!(r#"
+        Queue and execution time analysis:
+
+        Details:
+
+            base job: {} on {} ({} runs)
+            migration job: {} on {} ({} runs)
+
+        Data:
+
+            mean queue time - differential: {:.1}
+            median queue time - differential: {:.1}
+            mean spin-up time - differential: {:.1}
+            median spin-up time - differential: {:.1}
+            mean execution time - differential: {:.1}
+            median execution time - differential: {:.1}
+
+            differential mostly comes from: {}
+        "#,
+        self.base_job,
+        self.base.executor,
+        self.base.samples,
+        self.migration_job,
+        self.migration.executor,
+        self.migration.samples,
+        self.mean_queue_time_differential(),
+        self.median_queue_time_differential(),
+        self.mean_spin_up_time_differential(),
+        self.median_spin_up_time_differential(),
+        self.mean_execution_time_differential(),
+        self.median_execution_time_differential(),
+        self.dominant_factor());
+        write!(f, "{}", display)
+    }
+}
+
+/// Retrieve the job details and steps of the successful base and migration jobs of the runs.
+pub async fn collect_timed_jobs(source: &(dyn RunSource + Sync), runs: &[PipelineRun]) -> Result<(Vec<TimedJob>, Vec<TimedJob>), Error> {
+    let mut migration_jobs = vec![];
+    let mut base_jobs = vec![];
+    for run in runs {
+        if let Some(job_number) = successful_job_number(&run.migration_job) {
+            migration_jobs.push(timed_job(source, job_number).await?);
+        }
+        if let Some(job_number) = successful_job_number(&run.base_job) {
+            base_jobs.push(timed_job(source, job_number).await?);
+        }
+    }
+    Ok((migration_jobs, base_jobs))
+}
+
+async fn timed_job(source: &(dyn RunSource + Sync), job_number: u64) -> Result<TimedJob, Error> {
+    Ok(TimedJob {
+        details: source.get_job_details(job_number).await?,
+        steps: source.get_job_steps(job_number).await?,
+    })
+}
+
+fn seconds(duration: chrono::Duration) -> f64 {
+    duration.num_milliseconds() as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::job_details::Executor;
+    use super::super::steps::{Action, Step, SPIN_UP_STEP};
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn timing_insight_fails_if_base_job_has_no_finished_runs() {
+        let migration_jobs = vec![timed_job("docker", 10, 0, 100)];
+
+        let actual_err = TimingInsight::from(&String::from("bazel_build"), &String::from("cmake_build"), &migration_jobs, &[]).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(actual_err.into_inner().unwrap().to_string(), "failed to find finished runs of job cmake_build");
+    }
+
+    #[test]
+    fn timing_insight_separates_queue_and_execution_time() {
+        let base_jobs = vec![timed_job("machine", 60, 0, 300), timed_job("machine", 40, 0, 320)];
+        let migration_jobs = vec![timed_job("docker", 5, 0, 290), timed_job("docker", 15, 0, 310)];
+
+        let insight = TimingInsight::from(&String::from("bazel_build"), &String::from("cmake_build"), &migration_jobs, &base_jobs).unwrap();
+
+        assert_eq!(insight.base.samples, 2);
+        assert_eq!(insight.base.executor, "machine (medium)");
+        assert_eq!(insight.median_queue_time_differential(), -40.0);
+        assert_eq!(insight.median_execution_time_differential(), -10.0);
+        assert_eq!(insight.dominant_factor(), "queue (the executor)");
+    }
+
+    #[test]
+    fn timing_insight_counts_spin_up_towards_the_executor() {
+        let base_jobs = vec![timed_job("machine", 10, 90, 300)];
+        let migration_jobs = vec![timed_job("docker", 10, 5, 300)];
+
+        let insight = TimingInsight::from(&String::from("bazel_build"), &String::from("cmake_build"), &migration_jobs, &base_jobs).unwrap();
+
+        assert_eq!(insight.base.median_spin_up_time, 90.0);
+        assert_eq!(insight.median_spin_up_time_differential(), -85.0);
+        assert_eq!(insight.median_queue_time_differential(), 0.0);
+        assert_eq!(insight.median_execution_time_differential(), 0.0);
+        assert_eq!(insight.dominant_factor(), "spin-up (the executor)");
+    }
+
+    #[test]
+    fn timing_metrics_ignore_jobs_which_did_not_run() {
+        let mut not_started = timed_job("docker", 5, 0, 10);
+        not_started.details.started_at = None;
+
+        let metrics = TimingMetrics::from(&[not_started, timed_job("docker", 5, 0, 10)]).unwrap();
+
+        assert_eq!(metrics.samples, 1);
+        assert_eq!(metrics.mean_execution_time, 10.0);
+    }
+
+    // Jobs run the spin-up step first, then the build.
+    fn timed_job(executor_type: &str, queue_seconds: i64, spin_up_seconds: i64, execution_seconds: i64) -> TimedJob {
+        let queued_at = Utc::now();
+        let started_at = queued_at + Duration::seconds(queue_seconds);
+        let details = JobDetails {
+            number: 1,
+            name: String::from("job"),
+            status: String::from("success"),
+            web_url: String::from("https://circleci.com"),
+            parallelism: 1,
+            executor: Executor { executor_type: executor_type.to_string(), resource_class: String::from("medium") },
+            created_at: queued_at,
+            queued_at,
+            started_at: Some(started_at),
+            stopped_at: Some(started_at + Duration::seconds(spin_up_seconds + execution_seconds)),
+            duration: None,
+        };
+        let spin_up = Step {
+            name: String::from(SPIN_UP_STEP),
+            actions: vec![Action {
+                name: String::from(SPIN_UP_STEP),
+                index: 0,
+                status: Some(String::from("success")),
+                run_time_millis: Some(spin_up_seconds as u64 * 1000),
+                start_time: None,
+                end_time: None,
+            }],
+        };
+        TimedJob { details, steps: JobSteps { build_num: 1, steps: vec![spin_up] } }
+    }
+}
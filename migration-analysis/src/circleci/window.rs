@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use std::fmt;
+
+/// Period covered by the metrics of a job.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Window {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Window {
+    pub fn from(start: DateTime<Utc>, end: DateTime<Utc>) -> Window {
+        Window { start, end }
+    }
+
+    /// Windows overlap when they share time, a window of a single instant e.g. of a job with one run
+    /// overlaps the windows containing it.
+    pub fn overlaps(&self, other: &Window) -> bool {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        start < end || (start == end && (self.start == self.end || other.start == other.end))
+    }
+
+    /// Time covered by both windows, none when they don't overlap.
+    pub fn intersection(&self, other: &Window) -> Option<Window> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        Some(Window::from(self.start.max(other.start), self.end.min(other.end)))
+    }
+
+    /// Share of the time covered by either window which both windows cover, 1.0 for identical windows.
+    pub fn overlap_ratio(&self, other: &Window) -> f64 {
+        let overlap = (self.end.min(other.end) - self.start.max(other.start)).num_seconds().max(0);
+        let union = (self.end.max(other.end) - self.start.min(other.start)).num_seconds();
+        if union <= 0 {
+            // Both windows are the same instant.
+            return if self.start == other.start { 1.0 } else { 0.0 };
+        }
+        overlap as f64 / union as f64
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} - {}", self.start.to_rfc3339(), self.end.to_rfc3339())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn overlap_ratio_of_identical_windows_is_one() {
+        let window = days(0, 90);
+
+        assert_eq!(window.overlap_ratio(&window), 1.0);
+        assert!(window.overlaps(&window));
+    }
+
+    #[test]
+    fn overlap_ratio_is_shared_share_of_union() {
+        assert_eq!(days(0, 90).overlap_ratio(&days(60, 90)), 1.0 / 3.0);
+        assert_eq!(days(0, 60).overlap_ratio(&days(30, 90)), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn disjoint_windows_do_not_overlap() {
+        assert!(!days(0, 30).overlaps(&days(30, 60)));
+        assert_eq!(days(0, 30).overlap_ratio(&days(40, 60)), 0.0);
+    }
+
+    #[test]
+    fn windows_of_a_single_instant_overlap_windows_containing_it() {
+        assert!(days(30, 30).overlaps(&days(30, 30)));
+        assert!(days(30, 30).overlaps(&days(0, 60)));
+        assert!(days(0, 30).overlaps(&days(30, 30)));
+        assert!(!days(30, 30).overlaps(&days(40, 60)));
+        assert!(!days(30, 30).overlaps(&days(31, 31)));
+        assert_eq!(days(30, 30).intersection(&days(0, 60)), Some(days(30, 30)));
+    }
+
+    #[test]
+    fn intersection_is_shared_time() {
+        assert_eq!(days(0, 60).intersection(&days(30, 90)), Some(days(30, 60)));
+        assert_eq!(days(0, 30).intersection(&days(30, 60)), None);
+    }
+
+    #[test]
+    fn display_window_shows_rfc3339_bounds() {
+        let start = DateTime::parse_from_rfc3339("2023-07-01T10:00:00Z").unwrap().with_timezone(&Utc);
+
+        let window = Window::from(start, start + Duration::days(1));
+
+        assert_eq!(format!("{}", window), "2023-07-01T10:00:00+00:00 - 2023-07-02T10:00:00+00:00");
+    }
+
+    fn days(start: i64, end: i64) -> Window {
+        let origin = DateTime::parse_from_rfc3339("2023-07-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        Window::from(origin + Duration::days(start), origin + Duration::days(end))
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+use super::decode::{null_as_default, nullable};
+
+/// Summary of a workflow returned by the insights workflows endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSummary {
+    pub name: String,
+    pub metrics: WorkflowMetrics,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowMetrics {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub total_runs: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub successful_runs: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub failed_runs: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub success_rate: f64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub total_credits_used: u64,
+    /// None when the workflow has no successful runs in the window.
+    #[serde(default, deserialize_with = "nullable")]
+    pub duration_metrics: Option<WorkflowDurationMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowDurationMetrics {
+    pub min: u64,
+    pub mean: u64,
+    pub median: u64,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub p95: u64,
+    pub max: u64,
+}
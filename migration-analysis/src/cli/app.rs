@@ -1,4 +1,6 @@
-use clap::{Args,Parser,Subcommand};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{ArgGroup,Args,Parser,Subcommand,ValueEnum};
+use serde_json::Value;
 
 /// A CLI to get circleci jobs insights
 #[derive(Debug, Parser)]
@@ -7,6 +9,36 @@ pub struct App {
     /// Manage the configuration
     #[clap(subcommand)]
     pub command: Actions, 
+
+    /// Print where settings such as the token come from
+    #[clap(short,long,global = true)]
+    pub verbose: bool,
+
+    #[clap(flatten)]
+    pub http: Http,
+}
+
+#[derive(Debug, Args)]
+pub struct Http {
+  /// Set the proxy of HTTP(S) requests e.g. (http://proxy.example.com:3128)
+  #[clap(long,global = true)]
+  pub proxy: Option<String>,
+
+  /// Set the comma separated hosts reached without the proxy
+  #[clap(long,global = true)]
+  pub no_proxy: Option<String>,
+
+  /// Add a PEM file of CA certificates to trust
+  #[clap(long,global = true)]
+  pub ca_cert: Vec<String>,
+
+  /// Set the PEM file of the client certificate
+  #[clap(long,global = true,requires = "client_key")]
+  pub client_cert: Option<String>,
+
+  /// Set the PEM file of the private key of the client certificate
+  #[clap(long,global = true,requires = "client_cert")]
+  pub client_key: Option<String>,
 }
 
 #[derive(Debug,Subcommand)]
@@ -16,19 +48,524 @@ pub enum Actions {
 
   /// Manage the configuration
   Config(Config),
+
+  /// Compare what the base and migration jobs execute
+  Parity(Parity),
+
+  /// Report flaky tests of the base and migration jobs
+  Flaky(Flaky),
+
+  /// Compare the base and migration jobs commit by commit
+  Runs(Runs),
+
+  /// Separate queue time from execution time of the base and migration jobs
+  #[clap(after_help = "The durations compared by analysis come from the insights API, which doesn't split them.\nQueue and execution times need the details of every job run, one request each,\nso they are only compared over the last pipelines of the branch.")]
+  Timing(Timing),
+
+  /// Compare the time the base and migration jobs spend in each phase
+  Steps(Steps),
+
+  /// Benchmark the base and migration jobs on demand
+  Bench(Bench),
+
+  /// List the workflows and jobs known by the insights API
+  List(List),
+
+  /// Rank the projects of the organization to pick the next migration targets
+  Org,
+
+  /// Analyse the base and migration jobs of a GitHub Actions workflow
+  Github(Github),
+
+  /// Analyse the base and migration jobs of GitLab CI pipelines
+  Gitlab(Gitlab),
+
+  /// Analyse the builds of the base and migration Jenkins jobs
+  Jenkins(Jenkins),
+
+  /// Analyse the base and migration jobs of Buildkite builds
+  Buildkite(Buildkite),
+
+  /// Compare a base and a migration job running on different CI systems
+  Compare(Compare),
+
+  /// Analyse the files written by Bazel builds
+  Bazel(Bazel),
+
+  /// Analyse the files written by Ninja builds of CMake projects
+  Ninja(Ninja),
+
+  /// Rank the targets and translation units Bazel is slowest on relative to CMake
+  Targets(Targets),
 }
 
 #[derive(Debug, Args)]
+#[clap(group(ArgGroup::new("window").required(true).args(["reporting_window", "since"])))]
 pub struct Analysis {
   /// Set reporting window
+  #[clap(short,long,value_enum)]
+  pub reporting_window: Option<ReportingWindow>,
+
+  /// Set the start of a custom range e.g. (2023-10-01), analysed from the runs of its pipelines
+  #[clap(long,value_parser = parse_timestamp,conflicts_with = "reporting_window")]
+  pub since: Option<DateTime<Utc>>,
+
+  /// Set the end of the custom range, defaults to now
+  #[clap(long,value_parser = parse_timestamp,requires = "since")]
+  pub until: Option<DateTime<Utc>>,
+
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Add the summary of a Bazel build of the migration job, written with --build_event_json_file
+  #[clap(long)]
+  pub bep: Option<String>,
+}
+
+/// Reporting windows supported by the insights API.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportingWindow {
+  #[clap(name = "last-24-hours")]
+  Last24Hours,
+  #[clap(name = "last-7-days")]
+  Last7Days,
+  #[clap(name = "last-30-days")]
+  Last30Days,
+  #[clap(name = "last-60-days")]
+  Last60Days,
+  #[clap(name = "last-90-days")]
+  Last90Days,
+}
+
+impl ReportingWindow {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ReportingWindow::Last24Hours => "last-24-hours",
+      ReportingWindow::Last7Days => "last-7-days",
+      ReportingWindow::Last30Days => "last-30-days",
+      ReportingWindow::Last60Days => "last-60-days",
+      ReportingWindow::Last90Days => "last-90-days",
+    }
+  }
+}
+
+// Dates are the start of the day in UTC.
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>, String> {
+  if let Ok(date) = NaiveDate::parse_from_str(timestamp, "%Y-%m-%d") {
+    return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+  }
+  DateTime::parse_from_rfc3339(timestamp)
+    .map(|t| t.with_timezone(&Utc))
+    .map_err(|_| format!("invalid timestamp {}, expected a date (2023-10-01) or an RFC 3339 timestamp", timestamp))
+}
+
+#[derive(Debug, Args)]
+pub struct Github {
+  /// Set the repository e.g. (COVESA/vsomeip), defaults to the one of the config
+  #[clap(short,long)]
+  pub repository: Option<String>,
+
+  /// Set the file name of the workflow e.g. (c-cpp.yml)
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Set the branch of the workflow runs
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  #[clap(flatten)]
+  pub jobs: ProviderJobs,
+}
+
+#[derive(Debug, Args)]
+pub struct Gitlab {
+  /// Set the path of the project e.g. (group/vsomeip), defaults to the one of the config
+  #[clap(short,long)]
+  pub project: Option<String>,
+
+  /// Set the branch or tag of the pipelines
+  #[clap(short,long = "ref",default_value = "main")]
+  pub reference: String,
+
+  #[clap(flatten)]
+  pub jobs: ProviderJobs,
+}
+
+#[derive(Debug, Args)]
+pub struct Jenkins {
+  /// Set the URL of the Jenkins controller, defaults to the one of the config
+  #[clap(short,long)]
+  pub url: Option<String>,
+
+  #[clap(flatten)]
+  pub jobs: ProviderJobs,
+}
+
+#[derive(Debug, Args)]
+pub struct Buildkite {
+  /// Set the slug of the organization, defaults to the one of the config
   #[clap(short,long)]
-  pub reporting_window: String,
+  pub organization: Option<String>,
 
+  /// Set the slug of the pipeline e.g. (vsomeip)
+  #[clap(short,long)]
+  pub pipeline: String,
+
+  /// Set the branch of the builds
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  #[clap(flatten)]
+  pub jobs: ProviderJobs,
+}
+
+#[derive(Debug, Args)]
+pub struct Compare {
+  /// Set the CI system of the base job
+  #[clap(long,value_enum)]
+  pub base_provider: Provider,
+
+  /// Set the workflow, project, URL or pipeline of the base job, depending on its CI system
+  #[clap(long)]
+  pub base_pipeline: Option<String>,
+
+  /// Set the branch of the base job runs
+  #[clap(long,default_value = "main")]
+  pub base_branch: String,
+
+  /// Set the name of the base job
+  #[clap(long,default_value = "cmake_build")]
+  pub base_job: String,
+
+  /// Set the start of the range of the base job, defaults to the one of both jobs
+  #[clap(long,value_parser = parse_timestamp)]
+  pub base_since: Option<DateTime<Utc>>,
+
+  /// Set the end of the range of the base job, defaults to the one of both jobs
+  #[clap(long,value_parser = parse_timestamp)]
+  pub base_until: Option<DateTime<Utc>>,
+
+  /// Set the CI system of the migration job
+  #[clap(long,value_enum)]
+  pub migration_provider: Provider,
+
+  /// Set the workflow, project, URL or pipeline of the migration job, depending on its CI system
+  #[clap(long)]
+  pub migration_pipeline: Option<String>,
+
+  /// Set the branch of the migration job runs
+  #[clap(long,default_value = "main")]
+  pub migration_branch: String,
+
+  /// Set the name of the migration job
+  #[clap(long,default_value = "bazel_build")]
+  pub migration_job: String,
+
+  /// Set the start of the range of the migration job, defaults to the one of both jobs
+  #[clap(long,value_parser = parse_timestamp)]
+  pub migration_since: Option<DateTime<Utc>>,
+
+  /// Set the end of the range of the migration job, defaults to the one of both jobs
+  #[clap(long,value_parser = parse_timestamp)]
+  pub migration_until: Option<DateTime<Utc>>,
+
+  /// Set the start of the range of both jobs e.g. (2023-10-01)
+  #[clap(long,value_parser = parse_timestamp)]
+  pub since: Option<DateTime<Utc>>,
+
+  /// Set the end of the range of both jobs, defaults to now
+  #[clap(long,value_parser = parse_timestamp)]
+  pub until: Option<DateTime<Utc>>,
+
+  /// Compare the same number of latest runs of both jobs
+  #[clap(long)]
+  pub balance: bool
+}
+
+/// CI systems the base and migration jobs can run on.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Provider {
+  Circleci,
+  Github,
+  Gitlab,
+  Jenkins,
+  Buildkite,
+}
+
+impl Provider {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Provider::Circleci => "circleci",
+      Provider::Github => "github",
+      Provider::Gitlab => "gitlab",
+      Provider::Jenkins => "jenkins",
+      Provider::Buildkite => "buildkite",
+    }
+  }
+}
+
+/// Jobs and range analysed on CI systems other than CircleCI.
+#[derive(Debug, Args)]
+pub struct ProviderJobs {
+  /// Set the name of the base job
+  #[clap(long,default_value = "cmake_build")]
+  pub base_job: String,
+
+  /// Set the name of the migration job
+  #[clap(long,default_value = "bazel_build")]
+  pub migration_job: String,
+
+  /// Set the start of the range e.g. (2023-10-01)
+  #[clap(long,value_parser = parse_timestamp)]
+  pub since: DateTime<Utc>,
+
+  /// Set the end of the range, defaults to now
+  #[clap(long,value_parser = parse_timestamp)]
+  pub until: Option<DateTime<Utc>>
+}
+
+#[derive(Debug, Args)]
+pub struct Flaky {
   /// Set name of the workflow
   #[clap(short,long)]
   pub workflow: String
 }
 
+#[derive(Debug, Args)]
+pub struct Runs {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Set the branch of the pipelines
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  /// Set the maximum number of pipelines to retrieve
+  #[clap(short,long,default_value_t = 20)]
+  pub limit: usize
+}
+
+#[derive(Debug, Args)]
+pub struct Timing {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Set the branch of the pipelines
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  /// Set the maximum number of pipelines to retrieve
+  #[clap(short,long,default_value_t = 20)]
+  pub limit: usize
+}
+
+#[derive(Debug, Args)]
+pub struct Steps {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Set the branch of the pipelines
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  /// Set the maximum number of pipelines to retrieve
+  #[clap(short,long,default_value_t = 5)]
+  pub limit: usize
+}
+
+#[derive(Debug, Args)]
+pub struct Parity {
+  #[clap(subcommand)]
+  pub command: ParityActions,
+}
+
+#[derive(Debug,Subcommand)]
+pub enum ParityActions {
+  /// Compare the test cases executed by the base and migration jobs
+  Tests(ParityTests),
+
+  /// Compare the artifacts produced by the base and migration jobs
+  Artifacts(ParityJobs),
+
+  /// Compare the test results stored by a base and a migration job
+  JobTests(ParityJobs),
+}
+
+#[derive(Debug, Args)]
+pub struct ParityTests {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String
+}
+
+#[derive(Debug, Args)]
+pub struct ParityJobs {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Set the branch of the pipelines
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  /// Set the number of the base job, both job numbers default to the latest successful jobs
+  #[clap(long,requires = "migration_job_number")]
+  pub base_job_number: Option<u64>,
+
+  /// Set the number of the migration job, both job numbers default to the latest successful jobs
+  #[clap(long,requires = "base_job_number")]
+  pub migration_job_number: Option<u64>
+}
+
+#[derive(Debug, Args)]
+pub struct List {
+  #[clap(subcommand)]
+  pub command: ListActions,
+}
+
+#[derive(Debug,Subcommand)]
+pub enum ListActions {
+  /// List the workflows of the project
+  Workflows,
+
+  /// List the jobs of a workflow
+  Jobs(ListJobs),
+}
+
+#[derive(Debug, Args)]
+pub struct ListJobs {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String
+}
+
+#[derive(Debug, Args)]
+pub struct Bazel {
+  #[clap(subcommand)]
+  pub command: BazelActions,
+}
+
+#[derive(Debug,Subcommand)]
+pub enum BazelActions {
+  /// Summarise a build from the file written with --build_event_json_file
+  Bep(BazelBep),
+
+  /// Break down the time of a command from the profile written with --profile
+  Profile(BazelProfile),
+}
+
+#[derive(Debug, Args)]
+pub struct BazelBep {
+  /// Set the build event JSON file
+  pub file: String,
+}
+
+#[derive(Debug, Args)]
+pub struct BazelProfile {
+  /// Set the JSON trace profile, optionally gzipped
+  pub file: String,
+
+  /// Print the summary as JSON
+  #[clap(long)]
+  pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct Ninja {
+  #[clap(subcommand)]
+  pub command: NinjaActions,
+}
+
+#[derive(Debug,Subcommand)]
+pub enum NinjaActions {
+  /// Profile the last build recorded in the .ninja_log of a build directory
+  Log(NinjaLog),
+}
+
+#[derive(Debug, Args)]
+pub struct NinjaLog {
+  /// Set the .ninja_log file
+  pub file: String,
+
+  /// Print the summary as JSON
+  #[clap(long)]
+  pub json: bool,
+}
+
+#[derive(Debug, Args)]
+#[clap(group(ArgGroup::new("bazel").required(true).args(["bep", "profile"])))]
+pub struct Targets {
+  /// Set the .ninja_log of the CMake build
+  #[clap(long)]
+  pub ninja_log: String,
+
+  /// Set the build event JSON file of the Bazel build, written with --build_event_publish_all_actions
+  #[clap(long)]
+  pub bep: Option<String>,
+
+  /// Set the JSON trace profile of the Bazel build, optionally gzipped
+  #[clap(long)]
+  pub profile: Option<String>,
+
+  /// Set the JSON file mapping the units and targets of the CMake build to the Bazel ones
+  #[clap(short,long)]
+  pub mapping: Option<String>,
+
+  /// Print the comparison as JSON
+  #[clap(long)]
+  pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct Bench {
+  #[clap(subcommand)]
+  pub command: BenchActions,
+}
+
+#[derive(Debug,Subcommand)]
+pub enum BenchActions {
+  /// Trigger pipelines and analyse the base and migration jobs they run
+  Trigger(BenchTrigger),
+}
+
+#[derive(Debug, Args)]
+pub struct BenchTrigger {
+  /// Set name of the workflow
+  #[clap(short,long)]
+  pub workflow: String,
+
+  /// Set the branch of the pipelines
+  #[clap(short,long,default_value = "main")]
+  pub branch: String,
+
+  /// Set the number of pipelines to trigger
+  #[clap(short,long,default_value_t = 3)]
+  pub pipelines: usize,
+
+  /// Set a pipeline parameter, values are parsed as JSON when possible e.g. (run_bazel=true)
+  #[clap(long = "parameter",value_parser = parse_parameter)]
+  pub parameters: Vec<(String, Value)>,
+
+  /// Set the seconds between polls of the pipelines
+  #[clap(long,default_value_t = 30)]
+  pub poll_interval: u64,
+
+  /// Set the seconds to wait for the pipelines to finish
+  #[clap(long,default_value_t = 7200)]
+  pub timeout: u64
+}
+
+fn parse_parameter(parameter: &str) -> Result<(String, Value), String> {
+  let (key, value) = parameter.split_once('=')
+    .ok_or_else(|| format!("invalid parameter {}, expected key=value", parameter))?;
+  let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+  Ok((key.to_string(), value))
+}
+
 #[derive(Debug, Args)]
 pub struct Config {
     /// Set the authorization field (circleci username)
@@ -53,16 +590,38 @@ mod tests {
     #[allow(dead_code)]
     const EXPECTED_HELP: &str = r#"A CLI to get circleci jobs insights
 
-Usage: mig <COMMAND>
+Usage: mig [OPTIONS] <COMMAND>
 
 Commands:
-  analysis  Retrieve migration information
-  config    Manage the configuration
-  help      Print this message or the help of the given subcommand(s)
+  analysis   Retrieve migration information
+  config     Manage the configuration
+  parity     Compare what the base and migration jobs execute
+  flaky      Report flaky tests of the base and migration jobs
+  runs       Compare the base and migration jobs commit by commit
+  timing     Separate queue time from execution time of the base and migration jobs
+  steps      Compare the time the base and migration jobs spend in each phase
+  bench      Benchmark the base and migration jobs on demand
+  list       List the workflows and jobs known by the insights API
+  org        Rank the projects of the organization to pick the next migration targets
+  github     Analyse the base and migration jobs of a GitHub Actions workflow
+  gitlab     Analyse the base and migration jobs of GitLab CI pipelines
+  jenkins    Analyse the builds of the base and migration Jenkins jobs
+  buildkite  Analyse the base and migration jobs of Buildkite builds
+  compare    Compare a base and a migration job running on different CI systems
+  bazel      Analyse the files written by Bazel builds
+  ninja      Analyse the files written by Ninja builds of CMake projects
+  targets    Rank the targets and translation units Bazel is slowest on relative to CMake
+  help       Print this message or the help of the given subcommand(s)
 
 Options:
-  -h, --help     Print help
-  -V, --version  Print version
+  -v, --verbose                    Print where settings such as the token come from
+      --proxy <PROXY>              Set the proxy of HTTP(S) requests e.g. (http://proxy.example.com:3128)
+      --no-proxy <NO_PROXY>        Set the comma separated hosts reached without the proxy
+      --ca-cert <CA_CERT>          Add a PEM file of CA certificates to trust
+      --client-cert <CLIENT_CERT>  Set the PEM file of the client certificate
+      --client-key <CLIENT_KEY>    Set the PEM file of the private key of the client certificate
+  -h, --help                       Print help
+  -V, --version                    Print version
 "#;
 
     #[test]
@@ -97,12 +656,21 @@ Options:
 
     const EXPECTED_ANALYSIS_HELP: &str = r#"Retrieve migration information
 
-Usage: analysis --reporting-window <REPORTING_WINDOW> --workflow <WORKFLOW>
+Usage: analysis [OPTIONS] --workflow <WORKFLOW> <--reporting-window <REPORTING_WINDOW>|--since <SINCE>>
 
 Options:
-  -r, --reporting-window <REPORTING_WINDOW>  Set reporting window
-  -w, --workflow <WORKFLOW>                  Set name of the workflow
-  -h, --help                                 Print help
+  -r, --reporting-window <REPORTING_WINDOW>
+          Set reporting window [possible values: last-24-hours, last-7-days, last-30-days, last-60-days, last-90-days]
+      --since <SINCE>
+          Set the start of a custom range e.g. (2023-10-01), analysed from the runs of its pipelines
+      --until <UNTIL>
+          Set the end of the custom range, defaults to now
+  -w, --workflow <WORKFLOW>
+          Set name of the workflow
+      --bep <BEP>
+          Add the summary of a Bazel build of the migration job, written with --build_event_json_file
+  -h, --help
+          Print help
 "#;
 
     #[test]
@@ -114,4 +682,555 @@ Options:
         let help = String::from_utf8(cursor.into_inner()).unwrap();
         assert_eq!(help, EXPECTED_ANALYSIS_HELP);
     }
+
+    const EXPECTED_PARITY_TESTS_HELP: &str = r#"Compare the test cases executed by the base and migration jobs
+
+Usage: tests --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>  Set name of the workflow
+  -h, --help                 Print help
+"#;
+
+    #[test]
+    fn test_parity_tests_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("parity").unwrap().find_subcommand_mut("tests").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_PARITY_TESTS_HELP);
+    }
+
+    const EXPECTED_FLAKY_HELP: &str = r#"Report flaky tests of the base and migration jobs
+
+Usage: flaky --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>  Set name of the workflow
+  -h, --help                 Print help
+"#;
+
+    #[test]
+    fn test_flaky_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("flaky").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_FLAKY_HELP);
+    }
+
+    const EXPECTED_RUNS_HELP: &str = r#"Compare the base and migration jobs commit by commit
+
+Usage: runs [OPTIONS] --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>  Set name of the workflow
+  -b, --branch <BRANCH>      Set the branch of the pipelines [default: main]
+  -l, --limit <LIMIT>        Set the maximum number of pipelines to retrieve [default: 20]
+  -h, --help                 Print help
+"#;
+
+    #[test]
+    fn test_runs_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("runs").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_RUNS_HELP);
+    }
+
+    const EXPECTED_PARITY_ARTIFACTS_HELP: &str = r#"Compare the artifacts produced by the base and migration jobs
+
+Usage: artifacts [OPTIONS] --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>
+          Set name of the workflow
+  -b, --branch <BRANCH>
+          Set the branch of the pipelines [default: main]
+      --base-job-number <BASE_JOB_NUMBER>
+          Set the number of the base job, both job numbers default to the latest successful jobs
+      --migration-job-number <MIGRATION_JOB_NUMBER>
+          Set the number of the migration job, both job numbers default to the latest successful jobs
+  -h, --help
+          Print help
+"#;
+
+    #[test]
+    fn test_parity_artifacts_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("parity").unwrap().find_subcommand_mut("artifacts").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_PARITY_ARTIFACTS_HELP);
+        assert!(App::try_parse_from(["mig", "parity", "artifacts", "-w", "build", "--base-job-number", "1"]).is_err());
+        assert!(App::try_parse_from(["mig", "parity", "artifacts", "-w", "build", "--base-job-number", "1", "--migration-job-number", "2"]).is_ok());
+    }
+
+    const EXPECTED_PARITY_JOB_TESTS_HELP: &str = r#"Compare the test results stored by a base and a migration job
+
+Usage: job-tests [OPTIONS] --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>
+          Set name of the workflow
+  -b, --branch <BRANCH>
+          Set the branch of the pipelines [default: main]
+      --base-job-number <BASE_JOB_NUMBER>
+          Set the number of the base job, both job numbers default to the latest successful jobs
+      --migration-job-number <MIGRATION_JOB_NUMBER>
+          Set the number of the migration job, both job numbers default to the latest successful jobs
+  -h, --help
+          Print help
+"#;
+
+    #[test]
+    fn test_parity_job_tests_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("parity").unwrap().find_subcommand_mut("job-tests").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_PARITY_JOB_TESTS_HELP);
+    }
+
+    const EXPECTED_TIMING_HELP: &str = r#"Separate queue time from execution time of the base and migration jobs
+
+Usage: timing [OPTIONS] --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>  Set name of the workflow
+  -b, --branch <BRANCH>      Set the branch of the pipelines [default: main]
+  -l, --limit <LIMIT>        Set the maximum number of pipelines to retrieve [default: 20]
+  -h, --help                 Print help
+
+The durations compared by analysis come from the insights API, which doesn't split them.
+Queue and execution times need the details of every job run, one request each,
+so they are only compared over the last pipelines of the branch.
+"#;
+
+    #[test]
+    fn test_timing_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("timing").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_TIMING_HELP);
+    }
+
+    const EXPECTED_STEPS_HELP: &str = r#"Compare the time the base and migration jobs spend in each phase
+
+Usage: steps [OPTIONS] --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>  Set name of the workflow
+  -b, --branch <BRANCH>      Set the branch of the pipelines [default: main]
+  -l, --limit <LIMIT>        Set the maximum number of pipelines to retrieve [default: 5]
+  -h, --help                 Print help
+"#;
+
+    #[test]
+    fn test_steps_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("steps").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_STEPS_HELP);
+    }
+
+    const EXPECTED_BENCH_TRIGGER_HELP: &str = r#"Trigger pipelines and analyse the base and migration jobs they run
+
+Usage: trigger [OPTIONS] --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>            Set name of the workflow
+  -b, --branch <BRANCH>                Set the branch of the pipelines [default: main]
+  -p, --pipelines <PIPELINES>          Set the number of pipelines to trigger [default: 3]
+      --parameter <PARAMETERS>         Set a pipeline parameter, values are parsed as JSON when possible e.g. (run_bazel=true)
+      --poll-interval <POLL_INTERVAL>  Set the seconds between polls of the pipelines [default: 30]
+      --timeout <TIMEOUT>              Set the seconds to wait for the pipelines to finish [default: 7200]
+  -h, --help                           Print help
+"#;
+
+    #[test]
+    fn test_bench_trigger_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("bench").unwrap().find_subcommand_mut("trigger").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_BENCH_TRIGGER_HELP);
+    }
+
+    const EXPECTED_BAZEL_BEP_HELP: &str = r#"Summarise a build from the file written with --build_event_json_file
+
+Usage: bep <FILE>
+
+Arguments:
+  <FILE>  Set the build event JSON file
+
+Options:
+  -h, --help  Print help
+"#;
+
+    #[test]
+    fn test_bazel_bep_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("bazel").unwrap().find_subcommand_mut("bep").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_BAZEL_BEP_HELP);
+    }
+
+    const EXPECTED_BAZEL_PROFILE_HELP: &str = r#"Break down the time of a command from the profile written with --profile
+
+Usage: profile [OPTIONS] <FILE>
+
+Arguments:
+  <FILE>  Set the JSON trace profile, optionally gzipped
+
+Options:
+      --json  Print the summary as JSON
+  -h, --help  Print help
+"#;
+
+    #[test]
+    fn test_bazel_profile_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("bazel").unwrap().find_subcommand_mut("profile").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_BAZEL_PROFILE_HELP);
+    }
+
+    const EXPECTED_NINJA_LOG_HELP: &str = r#"Profile the last build recorded in the .ninja_log of a build directory
+
+Usage: log [OPTIONS] <FILE>
+
+Arguments:
+  <FILE>  Set the .ninja_log file
+
+Options:
+      --json  Print the summary as JSON
+  -h, --help  Print help
+"#;
+
+    #[test]
+    fn test_ninja_log_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("ninja").unwrap().find_subcommand_mut("log").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_NINJA_LOG_HELP);
+    }
+
+    const EXPECTED_TARGETS_HELP: &str = r#"Rank the targets and translation units Bazel is slowest on relative to CMake
+
+Usage: targets [OPTIONS] --ninja-log <NINJA_LOG> <--bep <BEP>|--profile <PROFILE>>
+
+Options:
+      --ninja-log <NINJA_LOG>  Set the .ninja_log of the CMake build
+      --bep <BEP>              Set the build event JSON file of the Bazel build, written with --build_event_publish_all_actions
+      --profile <PROFILE>      Set the JSON trace profile of the Bazel build, optionally gzipped
+  -m, --mapping <MAPPING>      Set the JSON file mapping the units and targets of the CMake build to the Bazel ones
+      --json                   Print the comparison as JSON
+  -h, --help                   Print help
+"#;
+
+    #[test]
+    fn test_targets_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("targets").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_TARGETS_HELP);
+    }
+
+    #[test]
+    fn targets_requires_one_bazel_file() {
+        assert!(App::try_parse_from(["mig", "targets", "--ninja-log", ".ninja_log"]).is_err());
+        assert!(App::try_parse_from(["mig", "targets", "--ninja-log", ".ninja_log", "--bep", "bep.json", "--profile", "profile.gz"]).is_err());
+        assert!(App::try_parse_from(["mig", "targets", "--ninja-log", ".ninja_log", "--profile", "profile.gz"]).is_ok());
+    }
+
+    const EXPECTED_LIST_WORKFLOWS_HELP: &str = r#"List the workflows of the project
+
+Usage: workflows
+
+Options:
+  -h, --help  Print help
+"#;
+
+    #[test]
+    fn test_list_workflows_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("list").unwrap().find_subcommand_mut("workflows").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_LIST_WORKFLOWS_HELP);
+    }
+
+    const EXPECTED_LIST_JOBS_HELP: &str = r#"List the jobs of a workflow
+
+Usage: jobs --workflow <WORKFLOW>
+
+Options:
+  -w, --workflow <WORKFLOW>  Set name of the workflow
+  -h, --help                 Print help
+"#;
+
+    #[test]
+    fn test_list_jobs_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("list").unwrap().find_subcommand_mut("jobs").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_LIST_JOBS_HELP);
+    }
+
+    const EXPECTED_ORG_HELP: &str = r#"Rank the projects of the organization to pick the next migration targets
+
+Usage: org
+
+Options:
+  -h, --help  Print help
+"#;
+
+    #[test]
+    fn test_org_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("org").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_ORG_HELP);
+    }
+
+    const EXPECTED_GITHUB_HELP: &str = r#"Analyse the base and migration jobs of a GitHub Actions workflow
+
+Usage: github [OPTIONS] --workflow <WORKFLOW> --since <SINCE>
+
+Options:
+  -r, --repository <REPOSITORY>        Set the repository e.g. (COVESA/vsomeip), defaults to the one of the config
+  -w, --workflow <WORKFLOW>            Set the file name of the workflow e.g. (c-cpp.yml)
+  -b, --branch <BRANCH>                Set the branch of the workflow runs [default: main]
+      --base-job <BASE_JOB>            Set the name of the base job [default: cmake_build]
+      --migration-job <MIGRATION_JOB>  Set the name of the migration job [default: bazel_build]
+      --since <SINCE>                  Set the start of the range e.g. (2023-10-01)
+      --until <UNTIL>                  Set the end of the range, defaults to now
+  -h, --help                           Print help
+"#;
+
+    #[test]
+    fn test_github_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("github").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_GITHUB_HELP);
+    }
+
+    const EXPECTED_GITLAB_HELP: &str = r#"Analyse the base and migration jobs of GitLab CI pipelines
+
+Usage: gitlab [OPTIONS] --since <SINCE>
+
+Options:
+  -p, --project <PROJECT>              Set the path of the project e.g. (group/vsomeip), defaults to the one of the config
+  -r, --ref <REFERENCE>                Set the branch or tag of the pipelines [default: main]
+      --base-job <BASE_JOB>            Set the name of the base job [default: cmake_build]
+      --migration-job <MIGRATION_JOB>  Set the name of the migration job [default: bazel_build]
+      --since <SINCE>                  Set the start of the range e.g. (2023-10-01)
+      --until <UNTIL>                  Set the end of the range, defaults to now
+  -h, --help                           Print help
+"#;
+
+    #[test]
+    fn test_gitlab_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("gitlab").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_GITLAB_HELP);
+    }
+
+    const EXPECTED_JENKINS_HELP: &str = r#"Analyse the builds of the base and migration Jenkins jobs
+
+Usage: jenkins [OPTIONS] --since <SINCE>
+
+Options:
+  -u, --url <URL>                      Set the URL of the Jenkins controller, defaults to the one of the config
+      --base-job <BASE_JOB>            Set the name of the base job [default: cmake_build]
+      --migration-job <MIGRATION_JOB>  Set the name of the migration job [default: bazel_build]
+      --since <SINCE>                  Set the start of the range e.g. (2023-10-01)
+      --until <UNTIL>                  Set the end of the range, defaults to now
+  -h, --help                           Print help
+"#;
+
+    #[test]
+    fn test_jenkins_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("jenkins").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_JENKINS_HELP);
+    }
+
+    const EXPECTED_BUILDKITE_HELP: &str = r#"Analyse the base and migration jobs of Buildkite builds
+
+Usage: buildkite [OPTIONS] --pipeline <PIPELINE> --since <SINCE>
+
+Options:
+  -o, --organization <ORGANIZATION>    Set the slug of the organization, defaults to the one of the config
+  -p, --pipeline <PIPELINE>            Set the slug of the pipeline e.g. (vsomeip)
+  -b, --branch <BRANCH>                Set the branch of the builds [default: main]
+      --base-job <BASE_JOB>            Set the name of the base job [default: cmake_build]
+      --migration-job <MIGRATION_JOB>  Set the name of the migration job [default: bazel_build]
+      --since <SINCE>                  Set the start of the range e.g. (2023-10-01)
+      --until <UNTIL>                  Set the end of the range, defaults to now
+  -h, --help                           Print help
+"#;
+
+    #[test]
+    fn test_buildkite_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("buildkite").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_BUILDKITE_HELP);
+    }
+
+    const EXPECTED_COMPARE_HELP: &str = r#"Compare a base and a migration job running on different CI systems
+
+Usage: compare [OPTIONS] --base-provider <BASE_PROVIDER> --migration-provider <MIGRATION_PROVIDER>
+
+Options:
+      --base-provider <BASE_PROVIDER>
+          Set the CI system of the base job [possible values: circleci, github, gitlab, jenkins, buildkite]
+      --base-pipeline <BASE_PIPELINE>
+          Set the workflow, project, URL or pipeline of the base job, depending on its CI system
+      --base-branch <BASE_BRANCH>
+          Set the branch of the base job runs [default: main]
+      --base-job <BASE_JOB>
+          Set the name of the base job [default: cmake_build]
+      --base-since <BASE_SINCE>
+          Set the start of the range of the base job, defaults to the one of both jobs
+      --base-until <BASE_UNTIL>
+          Set the end of the range of the base job, defaults to the one of both jobs
+      --migration-provider <MIGRATION_PROVIDER>
+          Set the CI system of the migration job [possible values: circleci, github, gitlab, jenkins, buildkite]
+      --migration-pipeline <MIGRATION_PIPELINE>
+          Set the workflow, project, URL or pipeline of the migration job, depending on its CI system
+      --migration-branch <MIGRATION_BRANCH>
+          Set the branch of the migration job runs [default: main]
+      --migration-job <MIGRATION_JOB>
+          Set the name of the migration job [default: bazel_build]
+      --migration-since <MIGRATION_SINCE>
+          Set the start of the range of the migration job, defaults to the one of both jobs
+      --migration-until <MIGRATION_UNTIL>
+          Set the end of the range of the migration job, defaults to the one of both jobs
+      --since <SINCE>
+          Set the start of the range of both jobs e.g. (2023-10-01)
+      --until <UNTIL>
+          Set the end of the range of both jobs, defaults to now
+      --balance
+          Compare the same number of latest runs of both jobs
+  -h, --help
+          Print help
+"#;
+
+    #[test]
+    fn test_compare_help() {
+        let mut app = App::command();
+        let mut cursor: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let read_cmd = app.find_subcommand_mut("compare").unwrap();
+        read_cmd.write_help(&mut cursor).unwrap();
+        let help = String::from_utf8(cursor.into_inner()).unwrap();
+        assert_eq!(help, EXPECTED_COMPARE_HELP);
+    }
+
+    #[test]
+    fn provider_as_str_matches_possible_values() {
+        for provider in Provider::value_variants() {
+            assert_eq!(provider.to_possible_value().unwrap().get_name(), provider.as_str());
+        }
+    }
+
+    #[test]
+    fn compare_requires_both_providers() {
+        assert!(App::try_parse_from(["mig", "compare", "--base-provider", "github", "--since", "2023-10-01"]).is_err());
+        assert!(App::try_parse_from(["mig", "compare", "--base-provider", "github", "--migration-provider", "travis"]).is_err());
+
+        let app = App::try_parse_from(["mig", "compare", "--base-provider", "github", "--migration-provider", "circleci", "--since", "2023-10-01", "--balance"]).unwrap();
+        match app.command {
+            Actions::Compare(compare) => {
+                assert_eq!(compare.base_provider, Provider::Github);
+                assert_eq!(compare.migration_provider, Provider::Circleci);
+                assert!(compare.balance);
+            },
+            _ => panic!("expected the compare command"),
+        }
+    }
+
+    #[test]
+    fn parse_parameter_parses_json_values() {
+        assert_eq!(parse_parameter("run_bazel=true").unwrap(), (String::from("run_bazel"), Value::Bool(true)));
+        assert_eq!(parse_parameter("jobs=8").unwrap(), (String::from("jobs"), serde_json::json!(8)));
+        assert_eq!(parse_parameter("target=//...").unwrap(), (String::from("target"), Value::String(String::from("//..."))));
+    }
+
+    #[test]
+    fn parse_parameter_fails_without_value() {
+        assert!(parse_parameter("run_bazel").is_err());
+    }
+
+    #[test]
+    fn analysis_rejects_unsupported_reporting_window() {
+        let result = App::try_parse_from(["mig", "analysis", "-w", "build", "-r", "last-3-days"]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn analysis_requires_reporting_window_or_since() {
+        assert!(App::try_parse_from(["mig", "analysis", "-w", "build"]).is_err());
+        assert!(App::try_parse_from(["mig", "analysis", "-w", "build", "-r", "last-7-days", "--since", "2023-10-01"]).is_err());
+        assert!(App::try_parse_from(["mig", "analysis", "-w", "build", "--until", "2023-10-01"]).is_err());
+    }
+
+    #[test]
+    fn analysis_parses_custom_range() {
+        let app = App::try_parse_from(["mig", "analysis", "-w", "build", "--since", "2023-10-01", "--until", "2023-10-08T12:00:00Z"]).unwrap();
+
+        match app.command {
+            Actions::Analysis(analysis) => {
+                assert_eq!(analysis.since.unwrap().to_rfc3339(), "2023-10-01T00:00:00+00:00");
+                assert_eq!(analysis.until.unwrap().to_rfc3339(), "2023-10-08T12:00:00+00:00");
+                assert_eq!(analysis.reporting_window, None);
+            },
+            _ => panic!("expected the analysis command"),
+        }
+    }
+
+    #[test]
+    fn reporting_window_as_str_matches_possible_values() {
+        for window in ReportingWindow::value_variants() {
+            assert_eq!(window.to_possible_value().unwrap().get_name(), window.as_str());
+        }
+    }
 }
\ No newline at end of file
@@ -1,17 +1,62 @@
+use crate::artifacts::rules::ArtifactRule;
+use crate::http::HttpSettings;
+use crate::providers::buildkite::BuildkiteSettings;
+use crate::providers::github::GithubSettings;
+use crate::providers::gitlab::GitlabSettings;
+use crate::providers::jenkins::JenkinsSettings;
+use super::secret::Secret;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::fs::File;
+use std::fs::OpenOptions;
 use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
 use std::path::Path;
 
 #[allow(dead_code)]
 const DEFAULT_CONFIG_PATH: &str =  ".mig.json";
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Config {
-    pub authorization: String,
+    /// Optional when the token comes from the environment, a token file or a credential helper
+    #[serde(default)]
+    pub authorization: Secret,
+    /// Empty in configs of other CI systems only
+    #[serde(default)]
     pub project: String,
-    pub slug: String
+    #[serde(default)]
+    pub slug: String,
+    /// Phase of job steps which can't be derived from their name, e.g. {"Install project dependencies": "dependencies"}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_mapping: Option<BTreeMap<String, String>>,
+    /// Rules pairing base and migration artifacts, artifacts are paired by file name without them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_rules: Option<Vec<ArtifactRule>>,
+    /// Base URL of the CircleCI API, e.g. a self-hosted server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// Path of a file containing the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<String>,
+    /// Command printing the token on stdout, e.g. "pass show circleci"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_helper: Option<String>,
+    /// Proxy and certificates of the network the requests go through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpSettings>,
+    /// GitHub Actions workflows, e.g. {"repository": "COVESA/vsomeip"}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<GithubSettings>,
+    /// GitLab CI pipelines, e.g. {"url": "https://gitlab.example.com", "project": "group/vsomeip"}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gitlab: Option<GitlabSettings>,
+    /// Jenkins jobs, e.g. {"url": "https://jenkins.example.com", "user": "ci"}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jenkins: Option<JenkinsSettings>,
+    /// Buildkite pipelines, e.g. {"organization": "org"}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buildkite: Option<BuildkiteSettings>
 }
 
 #[allow(dead_code)]
@@ -20,7 +65,7 @@ pub struct Manager{
 }
 
 impl Manager {
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::needless_return)]
     pub fn new(custom_path: Option<&str>) -> Manager {
         match custom_path {
             Some(path) => {
@@ -36,7 +81,7 @@ impl Manager {
         } 
     }
 
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::needless_return)]
     pub fn config_exist(&self) -> bool {
         return Path::new(&self.config_path).exists();
     }
@@ -44,18 +89,61 @@ impl Manager {
     #[allow(dead_code)]
     pub fn read_config(&self) -> Config {
         let data = fs::read_to_string(&self.config_path).expect("failed to open config file");
-        return serde_json::from_str(&data).unwrap();
+        let config: Config = serde_json::from_str(&data).unwrap();
+        if let Some(warning) = self.permissions_warning(&config) {
+            eprintln!("warning: {}", warning);
+        }
+        config
+    }
+
+    /// Other users must not be able to read a token stored in the config file.
+    pub fn permissions_warning(&self, config: &Config) -> Option<String> {
+        let provider_token = config.github.as_ref().is_some_and(|github| !github.token.is_empty())
+            || config.gitlab.as_ref().is_some_and(|gitlab| !gitlab.token.is_empty())
+            || config.jenkins.as_ref().is_some_and(|jenkins| !jenkins.token.is_empty())
+            || config.buildkite.as_ref().is_some_and(|buildkite| !buildkite.token.is_empty());
+        if (config.authorization.is_empty() && !provider_token) || !is_world_readable(&self.config_path) {
+            return None;
+        }
+        Some(format!("{} contains a token and is readable by other users, restrict it with chmod 600 {}", self.config_path, self.config_path))
     }
 
     #[allow(dead_code)]
     pub fn write_config(&self, authorization: String, project: String, slug: String) {
-        let config = Config{authorization,project,slug};
+        // Settings which can only be edited in the file are kept.
+        let mut config = if self.config_exist() { self.read_config() } else { Config::default() };
+        config.authorization = Secret::from(&authorization);
+        config.project = project;
+        config.slug = slug;
         let content = serde_json::to_string(&config).unwrap();
-        let mut f = File::create(&self.config_path).expect("Unable to create file");
+        let mut f = create_private_file(&self.config_path).expect("Unable to create file");
         f.write_all(content.as_bytes()).expect("Unable to write data");
     }
 }
 
+#[cfg(unix)]
+fn create_private_file(path: &str) -> std::io::Result<fs::File> {
+    let file = OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // The mode only applies to new files, existing ones may have been readable by anyone.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create_private_file(path: &str) -> std::io::Result<fs::File> {
+    OpenOptions::new().write(true).create(true).truncate(true).open(path)
+}
+
+#[cfg(unix)]
+fn is_world_readable(path: &str) -> bool {
+    fs::metadata(path).map(|m| m.permissions().mode() & 0o004 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_world_readable(_path: &str) -> bool {
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -119,6 +207,23 @@ mod tests {
     }
 
     #[test]
+    fn write_config_keeps_step_mapping() {
+        let custom_path = "test_config_4.json";
+        let mut f = File::create(custom_path).expect("Unable to create file");
+        f.write_all(b"{\"authorization\":\"a\",\"project\":\"p\",\"slug\":\"s\",\"step_mapping\":{\"step\":\"build\"}}").expect("Unable to write data");
+        let manager = Manager::new(Some(custom_path));
+
+        manager.write_config(String::from("auth"), String::from("project"), String::from("slug"));
+
+        let config = manager.read_config();
+        fs::remove_file(custom_path).unwrap();
+
+        assert_eq!(config.authorization.expose(), "auth");
+        assert_eq!(config.step_mapping.unwrap().get("step").unwrap(), "build");
+    }
+
+    #[test]
+    #[allow(clippy::needless_borrows_for_generic_args)]
     fn read_config_file_returns_config() {
         let custom_path = "test_config_3.json";
         let mut f = File::create(&custom_path).expect("Unable to create file");
@@ -132,10 +237,71 @@ mod tests {
 
         let config = manager.read_config();
 
-        assert_eq!(auth,config.authorization);
+        assert_eq!(auth,config.authorization.expose());
         assert_eq!(project,config.project);
         assert_eq!(slug,config.slug);
 
         fs::remove_file(custom_path).unwrap();
     } 
+
+    #[cfg(unix)]
+    #[test]
+    fn write_config_restricts_permissions_of_config_file() {
+        let custom_path = "test_config_5.json";
+        fs::write(custom_path, "{\"project\":\"p\",\"slug\":\"s\"}").unwrap();
+        fs::set_permissions(custom_path, fs::Permissions::from_mode(0o644)).unwrap();
+        let manager = Manager::new(Some(custom_path));
+
+        manager.write_config(String::from("auth"), String::from("project"), String::from("slug"));
+
+        let mode = fs::metadata(custom_path).unwrap().permissions().mode();
+        fs::remove_file(custom_path).unwrap();
+
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions_warning_flags_world_readable_config_with_token() {
+        let custom_path = "test_config_6.json";
+        let mut f = File::create(custom_path).expect("Unable to create file");
+        f.write_all(b"{\"authorization\":\"token\",\"project\":\"p\",\"slug\":\"s\"}").expect("Unable to write data");
+        fs::set_permissions(custom_path, fs::Permissions::from_mode(0o644)).unwrap();
+        let manager = Manager::new(Some(custom_path));
+        let config = manager.read_config();
+
+        let world_readable = manager.permissions_warning(&config);
+        fs::set_permissions(custom_path, fs::Permissions::from_mode(0o600)).unwrap();
+        let private = manager.permissions_warning(&config);
+        fs::remove_file(custom_path).unwrap();
+
+        assert!(world_readable.unwrap().contains("readable by other users"));
+        assert!(private.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permissions_warning_flags_world_readable_config_with_github_token() {
+        let custom_path = "test_config_7.json";
+        let mut f = File::create(custom_path).expect("Unable to create file");
+        f.write_all(b"{\"github\":{\"repository\":\"COVESA/vsomeip\",\"token\":\"ghp\"}}").expect("Unable to write data");
+        fs::set_permissions(custom_path, fs::Permissions::from_mode(0o644)).unwrap();
+        let manager = Manager::new(Some(custom_path));
+        let config = manager.read_config();
+
+        let warning = manager.permissions_warning(&config);
+        fs::remove_file(custom_path).unwrap();
+
+        let github = config.github.unwrap();
+        assert_eq!(github.repository.unwrap(), "COVESA/vsomeip");
+        assert_eq!(github.token.expose(), "ghp");
+        assert!(warning.is_some());
+    }
+
+    #[test]
+    fn debug_config_redacts_authorization() {
+        let config = Config { authorization: Secret::from("very-secret-token"), ..Config::default() };
+
+        assert!(!format!("{:?}", config).contains("very-secret-token"));
+    }
 }
\ No newline at end of file
@@ -1 +1,3 @@
-pub mod manager;
\ No newline at end of file
+pub mod manager;
+pub mod secret;
+pub mod token;
\ No newline at end of file
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const REDACTED: &str = "***";
+
+/// A value such as a token which must not show up in Debug or Display output,
+/// so that logs and error messages can't leak it. Serialized as is.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn from(value: &str) -> Secret {
+        Secret(value.to_string())
+    }
+
+    /// The actual value, only to be used where it is sent or stored.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Secret({})", REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_is_redacted_in_debug_and_display() {
+        let secret = Secret::from("token");
+
+        assert_eq!(format!("{:?}", secret), "Secret(***)");
+        assert_eq!(format!("{}", secret), "***");
+        assert_eq!(secret.expose(), "token");
+    }
+
+    #[test]
+    fn secret_is_serialized_as_is() {
+        let secret = Secret::from("token");
+
+        let json = serde_json::to_string(&secret).unwrap();
+
+        assert_eq!(json, "\"token\"");
+        assert_eq!(serde_json::from_str::<Secret>(&json).unwrap(), secret);
+    }
+}
@@ -0,0 +1,164 @@
+use super::manager::Config;
+use super::secret::Secret;
+use std::fmt;
+use std::fs;
+use std::io::{Error,ErrorKind};
+use std::process::Command;
+
+/// Environment variables holding a token, the first one set wins.
+pub const TOKEN_VARIABLES: [&str; 2] = ["CIRCLECI_TOKEN", "MIG_TOKEN"];
+
+#[derive(Debug, PartialEq)]
+pub enum TokenSource {
+    Environment(String),
+    File(String),
+    Helper(String),
+    Config,
+}
+
+impl fmt::Display for TokenSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenSource::Environment(variable) => write!(f, "environment variable {}", variable),
+            TokenSource::File(path) => write!(f, "token file {}", path),
+            TokenSource::Helper(command) => write!(f, "credential helper `{}`", command),
+            TokenSource::Config => write!(f, "config file authorization"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ResolvedToken {
+    pub token: Secret,
+    pub source: TokenSource,
+}
+
+/// Resolve the token from the environment, the token file, the credential helper
+/// and finally the config file, in that order. The token only lives in memory.
+pub fn resolve_token(config: &Config, env: impl Fn(&str) -> Option<String>) -> Result<ResolvedToken, Error> {
+    for variable in TOKEN_VARIABLES {
+        if let Some(token) = env(variable).filter(|t| !t.trim().is_empty()) {
+            return Ok(ResolvedToken { token: Secret::from(token.trim()), source: TokenSource::Environment(variable.to_string()) });
+        }
+    }
+
+    if let Some(path) = &config.token_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::new(e.kind(), format!("failed to read token file {}: {}", path, e)))?;
+        return non_empty(content, TokenSource::File(path.to_string()));
+    }
+
+    if let Some(command) = &config.credential_helper {
+        return non_empty(run_helper(command)?, TokenSource::Helper(command.to_string()));
+    }
+
+    if !config.authorization.is_empty() {
+        return Ok(ResolvedToken { token: config.authorization.clone(), source: TokenSource::Config });
+    }
+
+    Err(Error::new(ErrorKind::NotFound, format!(
+        "no CircleCI token found, set {} or one of token_file, credential_helper and authorization in the config file",
+        TOKEN_VARIABLES.join(" or "))))
+}
+
+// Like git credential helpers, the command runs in a shell and prints the token on stdout.
+fn run_helper(command: &String) -> Result<String, Error> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .map_err(|e| Error::new(e.kind(), format!("failed to run credential helper `{}`: {}", command, e)))?;
+
+    if !output.status.success() {
+        return Err(Error::other(format!("credential helper `{}` failed with {}: {}", command, output.status, String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| Error::new(ErrorKind::InvalidData, format!("credential helper `{}` printed a token which isn't UTF-8", command)))
+}
+
+fn non_empty(token: String, source: TokenSource) -> Result<ResolvedToken, Error> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidData, format!("{} provided an empty token", source)));
+    }
+    Ok(ResolvedToken { token: Secret::from(token), source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn resolve_token_prefers_environment_variables() {
+        let config = config_with_authorization("from-config");
+        let env = env(&[("MIG_TOKEN", "mig"), ("CIRCLECI_TOKEN", "circleci")]);
+
+        let resolved = resolve_token(&config, |v| env.get(v).cloned()).unwrap();
+
+        assert_eq!(resolved.token.expose(), "circleci");
+        assert_eq!(resolved.source, TokenSource::Environment(String::from("CIRCLECI_TOKEN")));
+    }
+
+    #[test]
+    fn resolve_token_reads_token_file() {
+        let path = "test_token_file";
+        fs::write(path, "from-file\n").unwrap();
+        let mut config = config_with_authorization("from-config");
+        config.token_file = Some(path.to_string());
+
+        let resolved = resolve_token(&config, |_| None);
+        fs::remove_file(path).unwrap();
+
+        let resolved = resolved.unwrap();
+        assert_eq!(resolved.token.expose(), "from-file");
+        assert_eq!(resolved.source, TokenSource::File(path.to_string()));
+    }
+
+    #[test]
+    fn resolve_token_runs_credential_helper() {
+        let mut config = config_with_authorization("from-config");
+        config.credential_helper = Some(String::from("echo from-helper"));
+
+        let resolved = resolve_token(&config, |_| None).unwrap();
+
+        assert_eq!(resolved.token.expose(), "from-helper");
+        assert_eq!(format!("{}", resolved.source), "credential helper `echo from-helper`");
+    }
+
+    #[test]
+    fn resolve_token_fails_when_credential_helper_fails() {
+        let mut config = config_with_authorization("from-config");
+        config.credential_helper = Some(String::from("echo denied >&2; exit 1"));
+
+        let actual_err = resolve_token(&config, |_| None).unwrap_err();
+
+        assert!(actual_err.to_string().contains("denied"));
+    }
+
+    #[test]
+    fn resolve_token_falls_back_to_config_authorization() {
+        let resolved = resolve_token(&config_with_authorization("from-config"), |_| None).unwrap();
+
+        assert_eq!(resolved.token.expose(), "from-config");
+        assert_eq!(resolved.source, TokenSource::Config);
+    }
+
+    #[test]
+    fn resolve_token_fails_without_token() {
+        let actual_err = resolve_token(&config_with_authorization(""), |_| None).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::NotFound);
+    }
+
+    fn config_with_authorization(authorization: &str) -> Config {
+        Config {
+            authorization: Secret::from(authorization),
+            ..Config::default()
+        }
+    }
+
+    fn env(variables: &[(&str, &str)]) -> HashMap<String, String> {
+        variables.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+}
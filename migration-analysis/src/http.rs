@@ -0,0 +1,295 @@
+use reqwest::{Certificate, Identity, NoProxy, Proxy};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Error,ErrorKind};
+
+/// Network settings for corporate networks, e.g. an intercepting proxy with a private root CA.
+/// Without them the proxy environment variables and the system certificates are used.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HttpSettings {
+    /// Proxy of all HTTP and HTTPS requests, e.g. "http://proxy.example.com:3128"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Comma separated hosts reached without the proxy, e.g. "localhost,.internal.example.com"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// PEM files of CA certificates trusted in addition to the system ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ca_certificates: Vec<String>,
+    /// PEM file of the certificate presented to servers requiring client authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate: Option<String>,
+    /// PEM file of the PKCS#8 private key of the client certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+}
+
+impl HttpSettings {
+    /// Settings given on the command line take precedence over the ones of the config file,
+    /// CA certificates of both are trusted.
+    pub fn merge(&self, overrides: &HttpSettings) -> HttpSettings {
+        HttpSettings {
+            proxy: overrides.proxy.clone().or_else(|| self.proxy.clone()),
+            no_proxy: overrides.no_proxy.clone().or_else(|| self.no_proxy.clone()),
+            ca_certificates: self.ca_certificates.iter().chain(&overrides.ca_certificates).cloned().collect(),
+            client_certificate: overrides.client_certificate.clone().or_else(|| self.client_certificate.clone()),
+            client_key: overrides.client_key.clone().or_else(|| self.client_key.clone()),
+        }
+    }
+
+    pub fn build_client(&self) -> Result<reqwest::Client, Error> {
+        let mut builder = reqwest::Client::builder();
+
+        if let Some(proxy) = &self.proxy {
+            // The proxy URL may contain credentials, it is left out of errors.
+            let proxy = Proxy::all(proxy)
+                .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid proxy: {}", e.without_url())))?
+                .no_proxy(self.no_proxy.as_deref().and_then(NoProxy::from_string));
+            builder = builder.proxy(proxy);
+        }
+
+        for path in &self.ca_certificates {
+            let certificate = Certificate::from_pem(&read_pem(path)?)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, format!("invalid CA certificate {}: {}", path, e)))?;
+            builder = builder.add_root_certificate(certificate);
+        }
+
+        match (&self.client_certificate, &self.client_key) {
+            (Some(certificate), Some(key)) => {
+                let identity = Identity::from_pkcs8_pem(&read_pem(certificate)?, &read_pem(key)?)
+                    .map_err(|e| Error::new(ErrorKind::InvalidData, format!("invalid client certificate {}: {}", certificate, e)))?;
+                builder = builder.identity(identity);
+            },
+            (None, None) => {},
+            _ => return Err(Error::new(ErrorKind::InvalidInput, "client certificate and client key must be set together")),
+        }
+
+        builder.build()
+            .map_err(|e| Error::other(format!("failed to create HTTP client: {}", e)))
+    }
+}
+
+/// Encode a value of a query parameter, e.g. a branch name containing & or +.
+pub fn query_value(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+fn read_pem(path: &String) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|e| Error::new(e.kind(), format!("failed to read {}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
+    use std::io::{Read, Write};
+    use std::net::SocketAddr;
+    use std::thread;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TLS_DIR: &str = "testdata/tls";
+
+    #[test]
+    fn merge_prefers_overrides_and_keeps_all_ca_certificates() {
+        let config = HttpSettings {
+            proxy: Some(String::from("http://config:3128")),
+            no_proxy: Some(String::from("localhost")),
+            ca_certificates: vec![String::from("config.pem")],
+            ..HttpSettings::default()
+        };
+        let overrides = HttpSettings {
+            proxy: Some(String::from("http://cli:3128")),
+            ca_certificates: vec![String::from("cli.pem")],
+            ..HttpSettings::default()
+        };
+
+        let merged = config.merge(&overrides);
+
+        assert_eq!(merged.proxy.unwrap(), "http://cli:3128");
+        assert_eq!(merged.no_proxy.unwrap(), "localhost");
+        assert_eq!(merged.ca_certificates, vec![String::from("config.pem"), String::from("cli.pem")]);
+    }
+
+    #[test]
+    fn query_value_encodes_reserved_characters() {
+        assert_eq!(query_value("feature/a&b#c+d"), "feature%2Fa%26b%23c%2Bd");
+        assert_eq!(query_value("main"), "main");
+    }
+
+    #[tokio::test]
+    async fn client_trusts_extra_ca_certificate() {
+        let address = serve_tls_once().await;
+        let settings = HttpSettings {
+            ca_certificates: vec![format!("{}/ca.pem", TLS_DIR)],
+            ..HttpSettings::default()
+        };
+
+        let body = settings.build_client().unwrap()
+            .get(format!("https://localhost:{}/v2/me", address.port()))
+            .send().await.unwrap()
+            .text().await.unwrap();
+
+        assert_eq!(body, "{\"login\":\"mig\"}");
+    }
+
+    #[tokio::test]
+    async fn client_rejects_server_signed_by_unknown_ca() {
+        let address = serve_tls_once().await;
+
+        let result = HttpSettings::default().build_client().unwrap()
+            .get(format!("https://localhost:{}/v2/me", address.port()))
+            .send().await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn client_sends_requests_through_proxy() {
+        let proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v2/me"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("proxied"))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+        let settings = HttpSettings { proxy: Some(proxy.uri()), ..HttpSettings::default() };
+
+        let body = settings.build_client().unwrap()
+            .get("http://circleci.example.com/api/v2/me")
+            .send().await.unwrap()
+            .text().await.unwrap();
+
+        assert_eq!(body, "proxied");
+    }
+
+    #[tokio::test]
+    async fn client_bypasses_proxy_for_no_proxy_hosts() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("direct"))
+            .mount(&server)
+            .await;
+        let settings = HttpSettings {
+            // Nothing listens on the proxy, requests through it would fail.
+            proxy: Some(String::from("http://127.0.0.1:9")),
+            no_proxy: Some(String::from("127.0.0.1")),
+            ..HttpSettings::default()
+        };
+
+        let body = settings.build_client().unwrap()
+            .get(format!("{}/api/v2/me", server.uri()))
+            .send().await.unwrap()
+            .text().await.unwrap();
+
+        assert_eq!(body, "direct");
+    }
+
+    #[tokio::test]
+    async fn client_authenticates_with_client_certificate() {
+        let address = serve_tls_once_requiring_client_certificate();
+        let settings = HttpSettings {
+            ca_certificates: vec![format!("{}/ca.pem", TLS_DIR)],
+            client_certificate: Some(format!("{}/client.pem", TLS_DIR)),
+            client_key: Some(format!("{}/client-key.pem", TLS_DIR)),
+            ..HttpSettings::default()
+        };
+
+        let body = settings.build_client().unwrap()
+            .get(format!("https://localhost:{}/v2/me", address.port()))
+            .send().await.unwrap()
+            .text().await.unwrap();
+
+        assert_eq!(body, "{\"login\":\"mig\"}");
+    }
+
+    #[tokio::test]
+    async fn client_without_client_certificate_is_rejected() {
+        let address = serve_tls_once_requiring_client_certificate();
+        let settings = HttpSettings {
+            ca_certificates: vec![format!("{}/ca.pem", TLS_DIR)],
+            ..HttpSettings::default()
+        };
+
+        let result = async {
+            settings.build_client().unwrap()
+                .get(format!("https://localhost:{}/v2/me", address.port()))
+                .send().await?
+                .text().await
+        }.await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn build_client_fails_on_client_certificate_without_key() {
+        let settings = HttpSettings {
+            client_certificate: Some(format!("{}/client.pem", TLS_DIR)),
+            ..HttpSettings::default()
+        };
+
+        assert_eq!(settings.build_client().unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn build_client_fails_on_missing_ca_certificate() {
+        let settings = HttpSettings {
+            ca_certificates: vec![String::from("testdata/tls/missing.pem")],
+            ..HttpSettings::default()
+        };
+
+        assert_eq!(settings.build_client().unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    // Serves a single HTTPS request with a certificate signed by the test CA.
+    async fn serve_tls_once() -> SocketAddr {
+        let certificate = fs::read(format!("{}/server.pem", TLS_DIR)).unwrap();
+        let key = fs::read(format!("{}/server-key.pem", TLS_DIR)).unwrap();
+        let identity = native_tls::Identity::from_pkcs8(&certificate, &key).unwrap();
+        let acceptor = tokio_native_tls::TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            // Handshakes fail when the client doesn't trust the CA.
+            if let Ok(mut stream) = acceptor.accept(stream).await {
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await.unwrap();
+                let body = "{\"login\":\"mig\"}";
+                let response = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+            }
+        });
+        address
+    }
+    // Serves a single HTTPS request of a client presenting a certificate signed by the test CA,
+    // native-tls can't request client certificates so the stub uses OpenSSL directly.
+    fn serve_tls_once_requiring_client_certificate() -> SocketAddr {
+        let mut acceptor = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls()).unwrap();
+        acceptor.set_certificate_chain_file(format!("{}/server.pem", TLS_DIR)).unwrap();
+        acceptor.set_private_key_file(format!("{}/server-key.pem", TLS_DIR), SslFiletype::PEM).unwrap();
+        acceptor.set_ca_file(format!("{}/ca.pem", TLS_DIR)).unwrap();
+        acceptor.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        let acceptor = acceptor.build();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            // Handshakes fail when the client doesn't present a trusted certificate.
+            if let Ok(mut stream) = acceptor.accept(stream) {
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).unwrap();
+                let body = "{\"login\":\"mig\"}";
+                let response = format!("HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).unwrap();
+                stream.shutdown().unwrap();
+            }
+        });
+        address
+    }
+}
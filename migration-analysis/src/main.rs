@@ -1,13 +1,59 @@
+mod artifacts;
+mod bazel;
 mod circleci;
 mod cli;
 mod config;
+mod http;
+mod ninja;
+mod providers;
+mod report;
+mod stats;
+mod targets;
 
+use artifacts::comparison::artifact_parity;
+use artifacts::rules::PathRules;
+use bazel::bep::BuildSummary;
+use bazel::profile::ProfileSummary;
+use circleci::bench::{run_bench, BenchSettings};
 use circleci::client::Client;
 use circleci::migration::{Analysis, Insight};
 use circleci::jobs::Jobs;
+use circleci::listing::Listing;
+use circleci::flakiness::FlakyReport;
+use circleci::org::{collect_project_jobs, OrgReport};
+use circleci::parity::TestParity;
+use circleci::phases::{collect_job_steps, StepMapping, StepsInsight};
+use circleci::suggestions::suggestion;
+use circleci::runs::{aggregate_jobs, collect_runs, collect_runs_between, successful_job_number, RunsReport};
+use circleci::test_diff::TestRunDiff;
+use circleci::window::Window;
+use circleci::timing::{collect_timed_jobs, TimingInsight};
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use cli::app::{Actions,App};
-use config::manager::Manager;
+use cli::app::{Actions,App,BazelActions,BenchActions,Http,ListActions,NinjaActions,ParityActions,Provider,ProviderJobs};
+use config::manager::{Config, Manager};
+use config::token::resolve_token;
+use http::HttpSettings;
+use ninja::log::NinjaBuild;
+use providers::{collect_jobs, JobRunSource};
+use providers::{buildkite, github, gitlab, jenkins};
+use providers::circleci::PipelineJobRuns;
+use providers::compare::{align_jobs, JobSource};
+use targets::comparison::{bep_units, build_warnings, ninja_units, profile_units, TargetTimes};
+use targets::mapping::TargetMapping;
+use std::env;
+use std::io::{Error,ErrorKind};
+use std::process;
+use std::time::Duration;
+
+const BASE_JOB: &str = "cmake_build";
+const MIGRATION_JOB: &str = "bazel_build";
+// Only the insights jobs endpoint uses a reporting window, the client still requires one.
+const DEFAULT_REPORTING_WINDOW: &str = "last-90-days";
+// Branch the insights jobs endpoint reports on, custom ranges use the same one.
+const DEFAULT_BRANCH: &str = "main";
+// Listing workflows and organization insights aren't scoped to a workflow, the client still requires one.
+const ANY_WORKFLOW: &str = "any";
 
 
 #[tokio::main]
@@ -31,34 +77,433 @@ async fn main() {
             config_manager.write_config(authorization, project, slug);
         },
         Actions::Analysis(arg) => {
-            let config = config_manager.read_config();
-            let jobs = retrieve_jobs(&config.slug, &config.project, &arg.workflow, &arg.reporting_window, &config.authorization).await;
-            let insights = get_jobs_insights(jobs);
+            let config = load_config(&config_manager, &cli);
+            let insights = match (&arg.reporting_window, arg.since) {
+                (Some(reporting_window), _) => {
+                    let client = circleci_client(&config, &arg.workflow, reporting_window.as_str());
+                    retrieve_jobs(&client).await
+                },
+                (None, Some(since)) => {
+                    let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+                    collect_runs_between(&client, DEFAULT_BRANCH, &arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), since, arg.until.unwrap_or_else(Utc::now))
+                        .await
+                        .and_then(|runs| aggregate_jobs(&runs, &String::from(MIGRATION_JOB), &String::from(BASE_JOB)))
+                },
+                (None, None) => unreachable!("clap requires a reporting window or a range"),
+            }
+            .and_then(get_jobs_insights)
+            .and_then(|insight| match &arg.bep {
+                Some(path) => BuildSummary::read(path).map(|summary| insight.with_bazel_build(summary)),
+                None => Ok(insight),
+            })
+            .unwrap_or_else(|e| exit_with_error(e));
             println!("{}", insights);
+        },
+        Actions::Parity(parity) => {
+            let config = load_config(&config_manager, &cli);
+            match &parity.command {
+                ParityActions::Tests(arg) => {
+                    let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+                    let parity = client.get_test_metrics().await
+                        .and_then(|metrics| TestParity::from(&String::from(MIGRATION_JOB), &String::from(BASE_JOB), &metrics))
+                        .unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", parity);
+                    if parity.migration_runs_fewer_tests() {
+                        eprintln!("error: {} runs fewer test cases than {}", MIGRATION_JOB, BASE_JOB);
+                        process::exit(1);
+                    }
+                },
+                ParityActions::Artifacts(arg) => {
+                    let rules = PathRules::from(&config.artifact_rules.clone().unwrap_or_default()).unwrap_or_else(|e| exit_with_error(e));
+                    let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+                    let (base_job_number, migration_job_number) = match (arg.base_job_number, arg.migration_job_number) {
+                        (Some(base), Some(migration)) => (base, migration),
+                        _ => latest_job_numbers(&client, &arg.branch, &arg.workflow).await.unwrap_or_else(|e| exit_with_error(e)),
+                    };
+                    let parity = artifact_parity(&client, &rules, base_job_number, migration_job_number)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", parity);
+                    if !parity.is_equivalent() {
+                        eprintln!("error: artifacts of {} are not equivalent to the ones of {}", MIGRATION_JOB, BASE_JOB);
+                        process::exit(1);
+                    }
+                },
+                ParityActions::JobTests(arg) => {
+                    let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+                    let (base_job_number, migration_job_number) = match (arg.base_job_number, arg.migration_job_number) {
+                        (Some(base), Some(migration)) => (base, migration),
+                        _ => latest_job_numbers(&client, &arg.branch, &arg.workflow).await.unwrap_or_else(|e| exit_with_error(e)),
+                    };
+                    let base_tests = client.get_job_tests(base_job_number).await.unwrap_or_else(|e| exit_with_error(e));
+                    let migration_tests = client.get_job_tests(migration_job_number).await.unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", TestRunDiff::from(base_job_number, migration_job_number, &base_tests, &migration_tests));
+                }
+            }
+        },
+        Actions::Flaky(arg) => {
+            let config = load_config(&config_manager, &cli);
+            let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+            let flaky_tests = client.get_flaky_tests().await.unwrap_or_else(|e| exit_with_error(e));
+            let report = FlakyReport::from(&arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), &flaky_tests);
+            println!("{}", report);
+        },
+        Actions::Runs(arg) => {
+            let config = load_config(&config_manager, &cli);
+            let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+            let runs = collect_runs(&client, &arg.branch, &arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), arg.limit)
+                .await
+                .unwrap_or_else(|e| exit_with_error(e));
+            let report = RunsReport::from(&arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), &runs);
+            println!("{}", report);
+        },
+        Actions::Timing(arg) => {
+            let config = load_config(&config_manager, &cli);
+            let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+            let runs = collect_runs(&client, &arg.branch, &arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), arg.limit)
+                .await
+                .unwrap_or_else(|e| exit_with_error(e));
+            let (migration_jobs, base_jobs) = collect_timed_jobs(&client, &runs).await.unwrap_or_else(|e| exit_with_error(e));
+            let insight = TimingInsight::from(&String::from(MIGRATION_JOB), &String::from(BASE_JOB), &migration_jobs, &base_jobs)
+                .unwrap_or_else(|e| exit_with_error(e));
+            println!("{}", insight);
+        },
+        Actions::Steps(arg) => {
+            let config = load_config(&config_manager, &cli);
+            let mapping = StepMapping::from(&config.step_mapping.clone().unwrap_or_default()).unwrap_or_else(|e| exit_with_error(e));
+            let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+            let runs = collect_runs(&client, &arg.branch, &arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), arg.limit)
+                .await
+                .unwrap_or_else(|e| exit_with_error(e));
+            let (migration_jobs, base_jobs) = collect_job_steps(&client, &runs).await.unwrap_or_else(|e| exit_with_error(e));
+            let insight = StepsInsight::from(&String::from(MIGRATION_JOB), &String::from(BASE_JOB), &migration_jobs, &base_jobs, &mapping)
+                .unwrap_or_else(|e| exit_with_error(e));
+            println!("{}", insight);
+        },
+        Actions::Bench(bench) => {
+            let config = load_config(&config_manager, &cli);
+            match &bench.command {
+                BenchActions::Trigger(arg) => {
+                    let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+                    let settings = BenchSettings {
+                        branch: arg.branch.to_string(),
+                        parameters: arg.parameters.iter().cloned().collect(),
+                        pipelines: arg.pipelines,
+                        poll_interval: Duration::from_secs(arg.poll_interval),
+                        timeout: Duration::from_secs(arg.timeout),
+                    };
+                    let jobs = run_bench(&client, &settings, &arg.workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB))
+                        .await
+                        .unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", get_jobs_insights(jobs).unwrap_or_else(|e| exit_with_error(e)));
+                }
+            }
+        },
+        Actions::List(list) => {
+            let config = load_config(&config_manager, &cli);
+            match &list.command {
+                ListActions::Workflows => {
+                    let client = circleci_client(&config, &String::from(ANY_WORKFLOW), DEFAULT_REPORTING_WINDOW);
+                    let workflows = client.get_workflows().await.unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", Listing::from_workflows(&workflows));
+                },
+                ListActions::Jobs(arg) => {
+                    let client = circleci_client(&config, &arg.workflow, DEFAULT_REPORTING_WINDOW);
+                    let jobs = retrieve_jobs(&client).await.unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", Listing::from_jobs(&arg.workflow, &jobs.items));
+                }
+            }
+        },
+        Actions::Org => {
+            let config = load_config(&config_manager, &cli);
+            let client = circleci_client(&config, &String::from(ANY_WORKFLOW), DEFAULT_REPORTING_WINDOW);
+            let summary = client.get_org_summary().await.unwrap_or_else(|e| exit_with_error(e));
+            let projects: Vec<String> = summary.org_project_data.iter().map(|p| p.project_name.to_string()).collect();
+            let project_jobs = collect_project_jobs(&client, &projects).await;
+            println!("{}", OrgReport::from(&config.slug, &summary, &project_jobs));
+        },
+        Actions::Github(arg) => {
+            let config = load_provider_config(&config_manager, &cli);
+            let client = github_client(&config, &arg.repository, &arg.workflow, &arg.branch);
+            println!("{}", provider_insights(&client, &arg.jobs).await.unwrap_or_else(|e| exit_with_error(e)));
+        },
+        Actions::Gitlab(arg) => {
+            let config = load_provider_config(&config_manager, &cli);
+            let client = gitlab_client(&config, &arg.project, &arg.reference);
+            println!("{}", provider_insights(&client, &arg.jobs).await.unwrap_or_else(|e| exit_with_error(e)));
+        },
+        Actions::Jenkins(arg) => {
+            let config = load_provider_config(&config_manager, &cli);
+            let client = jenkins_client(&config, &arg.url, &[&arg.jobs.base_job, &arg.jobs.migration_job]);
+            println!("{}", provider_insights(&client, &arg.jobs).await.unwrap_or_else(|e| exit_with_error(e)));
+        },
+        Actions::Buildkite(arg) => {
+            let config = load_provider_config(&config_manager, &cli);
+            let client = buildkite_client(&config, &arg.organization, &arg.pipeline, &arg.branch);
+            println!("{}", provider_insights(&client, &arg.jobs).await.unwrap_or_else(|e| exit_with_error(e)));
+        },
+        Actions::Compare(arg) => {
+            // Only CircleCI requires its token, the other providers may read public projects.
+            let config = if arg.base_provider == Provider::Circleci || arg.migration_provider == Provider::Circleci {
+                load_config(&config_manager, &cli)
+            } else {
+                load_provider_config(&config_manager, &cli)
+            };
+            let until = arg.until.unwrap_or_else(Utc::now);
+            let base_source = job_run_source(&config, arg.base_provider, &arg.base_pipeline, &arg.base_branch, &arg.base_job);
+            let migration_source = job_run_source(&config, arg.migration_provider, &arg.migration_pipeline, &arg.migration_branch, &arg.migration_job);
+            let base = JobSource {
+                source: base_source.as_ref(),
+                label: format!("{}/{}", arg.base_provider.as_str(), arg.base_job),
+                job_name: arg.base_job.to_string(),
+                window: compared_window("base", arg.base_since.or(arg.since), arg.base_until.unwrap_or(until)),
+            };
+            let migration = JobSource {
+                source: migration_source.as_ref(),
+                label: format!("{}/{}", arg.migration_provider.as_str(), arg.migration_job),
+                job_name: arg.migration_job.to_string(),
+                window: compared_window("migration", arg.migration_since.or(arg.since), arg.migration_until.unwrap_or(until)),
+            };
+            let aligned = align_jobs(&base, &migration, arg.balance).await.unwrap_or_else(|e| exit_with_error(e));
+            eprintln!("comparing runs started in {}", aligned.window);
+            let mut insight = Analysis {}.get_insights(&migration.label, &base.label, &aligned.jobs).unwrap_or_else(|e| exit_with_error(e));
+            insight.warnings.extend(aligned.warnings);
+            println!("{}", insight);
+        },
+        Actions::Bazel(bazel) => {
+            match &bazel.command {
+                BazelActions::Bep(arg) => {
+                    let summary = BuildSummary::read(&arg.file).unwrap_or_else(|e| exit_with_error(e));
+                    println!("{}", summary);
+                },
+                BazelActions::Profile(arg) => {
+                    let summary = ProfileSummary::read(&arg.file).unwrap_or_else(|e| exit_with_error(e));
+                    if arg.json {
+                        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+                    } else {
+                        println!("{}", summary);
+                    }
+                }
+            }
+        },
+        Actions::Ninja(ninja) => {
+            match &ninja.command {
+                NinjaActions::Log(arg) => {
+                    let build = NinjaBuild::read(&arg.file).unwrap_or_else(|e| exit_with_error(e));
+                    if arg.json {
+                        println!("{}", serde_json::to_string_pretty(&build).unwrap());
+                    } else {
+                        println!("{}", build);
+                    }
+                }
+            }
+        },
+        Actions::Targets(arg) => {
+            let mapping = match &arg.mapping {
+                Some(path) => TargetMapping::read(path).unwrap_or_else(|e| exit_with_error(e)),
+                None => TargetMapping::default(),
+            };
+            let ninja = NinjaBuild::read(&arg.ninja_log).unwrap_or_else(|e| exit_with_error(e));
+            let (migration_units, bep) = match (&arg.bep, &arg.profile) {
+                (Some(path), _) => {
+                    let bep = BuildSummary::read(path).unwrap_or_else(|e| exit_with_error(e));
+                    (bep_units(&bep), Some(bep))
+                },
+                (None, Some(path)) => {
+                    let profile = ProfileSummary::read(path).unwrap_or_else(|e| exit_with_error(e));
+                    (profile_units(&profile), None)
+                },
+                (None, None) => unreachable!("clap requires a build event file or a profile"),
+            };
+            let times = TargetTimes::from(&ninja_units(&ninja), &migration_units, &mapping)
+                .unwrap_or_else(|e| exit_with_error(e))
+                .with_warnings(build_warnings(&ninja, bep.as_ref()));
+            if arg.json {
+                println!("{}", serde_json::to_string_pretty(&times).unwrap());
+            } else {
+                println!("{}", times);
+            }
         }
     }
 }
 
-async fn retrieve_jobs(slug: &String,project:&String,workflow:&String,reporting_window:&String,auth:&String) -> Jobs {
-    let client = Client::from(
-        slug,
-        project,
-        workflow,
-        reporting_window,
-        auth,
-    )
-    .unwrap();
+/// Read the config with the token resolved from its sources, the config must never be written back.
+fn load_config(config_manager: &Manager, cli: &App) -> Config {
+    let mut config = config_manager.read_config();
+    if config.slug.is_empty() || config.project.is_empty() {
+        exit_with_error(Error::new(ErrorKind::InvalidInput, "the CircleCI project is not set, run mig config --slug <SLUG> --project <PROJECT>"));
+    }
+    let resolved = resolve_token(&config, |variable| env::var(variable).ok()).unwrap_or_else(|e| exit_with_error(e));
+    if cli.verbose {
+        eprintln!("using CircleCI token from {}", resolved.source);
+    }
+    config.authorization = resolved.token;
+    config.http = Some(config.http.unwrap_or_default().merge(&http_settings(&cli.http)));
+    config
+}
 
-    return client.get_jobs().await;
+/// Read the config of commands which don't use CircleCI, they don't need a config file nor a CircleCI token.
+fn load_provider_config(config_manager: &Manager, cli: &App) -> Config {
+    let mut config = if config_manager.config_exist() { config_manager.read_config() } else { Config::default() };
+    config.http = Some(config.http.unwrap_or_default().merge(&http_settings(&cli.http)));
+    config
+}
+
+fn http_settings(http: &Http) -> HttpSettings {
+    HttpSettings {
+        proxy: http.proxy.clone(),
+        no_proxy: http.no_proxy.clone(),
+        ca_certificates: http.ca_cert.clone(),
+        client_certificate: http.client_cert.clone(),
+        client_key: http.client_key.clone(),
+    }
+}
+
+fn http_client(config: &Config) -> reqwest::Client {
+    config.http.clone().unwrap_or_default().build_client().unwrap_or_else(|e| exit_with_error(e))
+}
+
+fn circleci_client(config: &Config, workflow: &String, reporting_window: &str) -> Client {
+    let http = http_client(config);
+    let client = Client::from(&config.slug, &config.project, workflow, &String::from(reporting_window), config.authorization.expose())
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_http_client(http);
+    match &config.api_url {
+        Some(api_url) => client.with_api_url(api_url),
+        None => client,
+    }
+}
+
+fn github_client(config: &Config, repository: &Option<String>, workflow: &str, branch: &str) -> github::Client {
+    let settings = config.github.clone().unwrap_or_default();
+    let repository = repository.clone().or(settings.repository.clone())
+        .unwrap_or_else(|| exit_with_error(Error::new(ErrorKind::InvalidInput, "repository is not set, pass --repository or set github.repository in the config")));
+    let token = providers::resolve_token(&github::TOKEN_VARIABLES, &settings.token, |variable| env::var(variable).ok());
+    let client = github::Client::from(&repository, workflow, branch, token.expose())
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_http_client(http_client(config));
+    match &settings.api_url {
+        Some(api_url) => client.with_api_url(api_url),
+        None => client,
+    }
+}
+
+fn gitlab_client(config: &Config, project: &Option<String>, reference: &str) -> gitlab::Client {
+    let settings = config.gitlab.clone().unwrap_or_default();
+    let project = project.clone().or(settings.project.clone())
+        .unwrap_or_else(|| exit_with_error(Error::new(ErrorKind::InvalidInput, "project is not set, pass --project or set gitlab.project in the config")));
+    let token = providers::resolve_token(&gitlab::TOKEN_VARIABLES, &settings.token, |variable| env::var(variable).ok());
+    let client = gitlab::Client::from(&project, reference, token.expose())
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_http_client(http_client(config));
+    match &settings.url {
+        Some(url) => client.with_url(url),
+        None => client,
+    }
+}
+
+fn jenkins_client(config: &Config, url: &Option<String>, jobs: &[&String]) -> jenkins::Client {
+    let settings = config.jenkins.clone().unwrap_or_default();
+    let url = url.clone().or(settings.url.clone())
+        .unwrap_or_else(|| exit_with_error(Error::new(ErrorKind::InvalidInput, "url is not set, pass --url or set jenkins.url in the config")));
+    let (user, token) = settings.resolve_credentials(|variable| env::var(variable).ok());
+    jenkins::Client::from(&url, jobs, user.as_deref(), token.expose())
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_http_client(http_client(config))
+}
+
+fn buildkite_client(config: &Config, organization: &Option<String>, pipeline: &str, branch: &str) -> buildkite::Client {
+    let settings = config.buildkite.clone().unwrap_or_default();
+    let organization = organization.clone().or(settings.organization.clone())
+        .unwrap_or_else(|| exit_with_error(Error::new(ErrorKind::InvalidInput, "organization is not set, pass --organization or set buildkite.organization in the config")));
+    let token = providers::resolve_token(&buildkite::TOKEN_VARIABLES, &settings.token, |variable| env::var(variable).ok());
+    let client = buildkite::Client::from(&organization, pipeline, branch, token.expose())
+        .unwrap_or_else(|e| exit_with_error(e))
+        .with_http_client(http_client(config));
+    match &settings.api_url {
+        Some(api_url) => client.with_api_url(api_url),
+        None => client,
+    }
+}
+
+/// The runs of a job on any CI system, the pipeline locates the job depending on the system.
+fn job_run_source(config: &Config, provider: Provider, pipeline: &Option<String>, branch: &str, job: &String) -> Box<dyn JobRunSource + Sync> {
+    let required_pipeline = |meaning: &str| pipeline.clone()
+        .unwrap_or_else(|| exit_with_error(Error::new(ErrorKind::InvalidInput, format!("the pipeline of {} jobs is not set, it is {}", provider.as_str(), meaning))));
+    match provider {
+        Provider::Circleci => {
+            let workflow = required_pipeline("the name of the workflow");
+            let client = circleci_client(config, &workflow, DEFAULT_REPORTING_WINDOW);
+            Box::new(PipelineJobRuns::from(client, branch, &workflow))
+        },
+        Provider::Github => {
+            // Workflows of another repository than the one of the config are prefixed with it.
+            let location = required_pipeline("the file name of the workflow e.g. (c-cpp.yml) or (COVESA/vsomeip/c-cpp.yml)");
+            let (repository, workflow) = match location.rsplit_once('/') {
+                Some((repository, workflow)) => (Some(repository.to_string()), workflow.to_string()),
+                None => (None, location),
+            };
+            Box::new(github_client(config, &repository, &workflow, branch))
+        },
+        Provider::Gitlab => Box::new(gitlab_client(config, pipeline, branch)),
+        Provider::Jenkins => Box::new(jenkins_client(config, pipeline, &[job])),
+        Provider::Buildkite => Box::new(buildkite_client(config, &None, &required_pipeline("the slug of the pipeline"), branch)),
+    }
+}
+
+fn compared_window(job: &str, since: Option<DateTime<Utc>>, until: DateTime<Utc>) -> Window {
+    let since = since
+        .unwrap_or_else(|| exit_with_error(Error::new(ErrorKind::InvalidInput, format!("the start of the range of the {} job is not set, pass --since or --{}-since", job, job))));
+    if since >= until {
+        exit_with_error(Error::new(ErrorKind::InvalidInput, format!("start of the range of the {} job {} is not before its end {}", job, since.to_rfc3339(), until.to_rfc3339())));
+    }
+    Window::from(since, until)
+}
+
+async fn provider_insights(source: &(dyn JobRunSource + Sync), jobs: &ProviderJobs) -> Result<Insight, Error> {
+    let collected = collect_jobs(source, &jobs.migration_job, &jobs.base_job, jobs.since, jobs.until.unwrap_or_else(Utc::now)).await?;
+    Analysis {}.get_insights(&jobs.migration_job, &jobs.base_job, &collected)
+}
+
+async fn latest_job_numbers(client: &Client, branch: &str, workflow: &String) -> Result<(u64, u64), Error> {
+    let runs = collect_runs(client, branch, workflow, &String::from(MIGRATION_JOB), &String::from(BASE_JOB), 10).await?;
+    runs.iter()
+        .find_map(|run| Some((successful_job_number(&run.base_job)?, successful_job_number(&run.migration_job)?)))
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("failed to find a run of workflow {} where {} and {} succeeded", workflow, BASE_JOB, MIGRATION_JOB)))
+}
+
+fn exit_with_error(error: std::io::Error) -> ! {
+    eprintln!("error: {}", error);
+    process::exit(1);
+}
+
+async fn retrieve_jobs(client: &Client) -> Result<Jobs, Error> {
+    let jobs = client.get_jobs().await;
+    if let Ok(jobs) = &jobs {
+        for failure in &jobs.decode_failures {
+            eprintln!("warning: {}", failure);
+        }
+    }
+    match jobs {
+        Ok(jobs) if !jobs.items.is_empty() => Ok(jobs),
+        Ok(_) => Err(Error::new(ErrorKind::NotFound, format!("failed to find jobs of workflow {}{}", &client.workflow, workflow_suggestion(client).await))),
+        Err(e) => Err(Error::new(e.kind(), format!("{}{}", e, workflow_suggestion(client).await))),
+    }
+}
+
+// Suggest the closest workflow when the given one may be mistyped.
+async fn workflow_suggestion(client: &Client) -> String {
+    match client.get_workflows().await {
+        Ok(workflows) => suggestion(&client.workflow, workflows.iter().map(|w| &w.name)),
+        Err(_) => String::new(),
+    }
 }
 
-fn get_jobs_insights(jobs: Jobs) -> Insight {
-    let insights = Analysis {}
+fn get_jobs_insights(jobs: Jobs) -> Result<Insight, Error> {
+    Analysis {}
         .get_insights(
-            &String::from("bazel_build"),
-            &String::from("cmake_build"),
+            &String::from(MIGRATION_JOB),
+            &String::from(BASE_JOB),
             &jobs,
         )
-        .unwrap();
-    return insights;
 }
\ No newline at end of file
@@ -0,0 +1,309 @@
+use serde::Serialize;
+use crate::report::format_lines;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::io::{Error,ErrorKind};
+
+const HEADER_PREFIX: &str = "# ninja log v";
+// Version 4 added the restat mtime column every later version still has.
+const MIN_VERSION: u32 = 4;
+// Directory of the object files of a target in a CMake build tree, e.g. CMakeFiles/vsomeip3.dir/
+const CMAKE_FILES: &str = "CMakeFiles/";
+const CMAKE_TARGET_SUFFIX: &str = ".dir/";
+
+/// An edge of the build graph which ran, its outputs are logged on separate lines with the same times.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Edge {
+    pub outputs: Vec<String>,
+    /// Milliseconds since the start of the build, as logged
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+impl Edge {
+    /// Seconds
+    pub fn duration(&self) -> f64 {
+        (self.end_ms - self.start_ms) as f64 / 1000.0
+    }
+
+    /// CMake target the edge builds, objects are in its directory and libraries are named after it.
+    pub fn target(&self) -> String {
+        target_of(&self.outputs[0])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TargetTime {
+    pub target: String,
+    pub edges: usize,
+    /// Seconds of all the edges of the target
+    pub duration: f64,
+}
+
+/// The last build recorded in a .ninja_log.
+///
+/// Ninja appends the edges of every build to the log with times relative to the start of the build,
+/// a build starts where the times go back. Outputs already up to date aren't logged again, so the
+/// last build of an incremental log only covers what was rebuilt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NinjaBuild {
+    pub version: u32,
+    /// Builds recorded in the log, the log is shared by every build of the build directory
+    pub builds: usize,
+    /// Outputs of all the builds of the log
+    pub known_outputs: usize,
+    /// Edges of the last build, in completion order
+    pub edges: Vec<Edge>,
+    /// Seconds from the start of the first edge to the end of the last one
+    pub wall_time: f64,
+    /// Seconds of all the edges, above the wall time when edges ran in parallel
+    pub cumulative_time: f64,
+    /// Targets from the one whose edges took the longest
+    pub targets: Vec<TargetTime>,
+}
+
+impl NinjaBuild {
+    pub fn from(content: &str) -> Result<NinjaBuild, Error> {
+        let mut lines = content.lines().enumerate();
+        let version = match lines.next() {
+            Some((_, header)) => header.strip_prefix(HEADER_PREFIX)
+                .and_then(|version| version.trim().parse::<u32>().ok())
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("not a ninja log, it starts with {:?}", header)))?,
+            None => return Err(Error::new(ErrorKind::InvalidData, "ninja log is empty")),
+        };
+        if version < MIN_VERSION {
+            return Err(Error::new(ErrorKind::InvalidData, format!("ninja log v{} isn't supported, it needs to be v{} or later", version, MIN_VERSION)));
+        }
+
+        let mut builds = 0;
+        let mut known_outputs = BTreeSet::new();
+        let mut last_end = None;
+        // Edges of the current build by start, end and command hash, and the edge each output was last logged for.
+        let mut edges: BTreeMap<(u64, u64, String), Edge> = BTreeMap::new();
+        let mut latest: BTreeMap<String, (u64, u64, String)> = BTreeMap::new();
+        for (index, line) in lines {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (start, end, output, hash) = parse_entry(line)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("invalid ninja log entry on line {}: {}", index + 1, line)))?;
+            if last_end.is_none_or(|last_end| end < last_end) {
+                builds += 1;
+                edges.clear();
+                latest.clear();
+            }
+            last_end = Some(end);
+            known_outputs.insert(output.to_string());
+
+            let key = (start, end, hash.to_string());
+            // An output rebuilt later in the same build only keeps its last edge.
+            if let Some(previous) = latest.insert(output.to_string(), key.clone()) {
+                if let Some(edge) = edges.get_mut(&previous) {
+                    edge.outputs.retain(|o| o != output);
+                }
+            }
+            edges.entry(key)
+                .or_insert_with(|| Edge { outputs: vec![], start_ms: start, end_ms: end })
+                .outputs.push(output.to_string());
+        }
+
+        let mut edges: Vec<Edge> = edges.into_values().filter(|e| !e.outputs.is_empty()).collect();
+        edges.sort_by_key(|e| (e.end_ms, e.start_ms));
+        let wall_time = match (edges.iter().map(|e| e.start_ms).min(), edges.iter().map(|e| e.end_ms).max()) {
+            (Some(start), Some(end)) => (end - start) as f64 / 1000.0,
+            _ => 0.0,
+        };
+        Ok(NinjaBuild {
+            version,
+            builds,
+            known_outputs: known_outputs.len(),
+            wall_time,
+            cumulative_time: edges.iter().map(Edge::duration).sum(),
+            targets: target_times(&edges),
+            edges,
+        })
+    }
+
+    pub fn read(path: &str) -> Result<NinjaBuild, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::new(e.kind(), format!("failed to read {}: {}", path, e)))?;
+        NinjaBuild::from(&content)
+            .map_err(|e| Error::new(e.kind(), format!("failed to parse ninja log {}: {}", path, e)))
+    }
+
+    pub fn rebuilt_outputs(&self) -> usize {
+        self.edges.iter().map(|e| e.outputs.len()).sum()
+    }
+
+    /// The last build left outputs of earlier builds untouched, its times don't profile a clean build.
+    pub fn is_incremental(&self) -> bool {
+        self.rebuilt_outputs() < self.known_outputs
+    }
+}
+
+// Columns are the start and end in milliseconds, the restat mtime, the output and the command hash.
+fn parse_entry(line: &str) -> Option<(u64, u64, &str, &str)> {
+    let mut columns = line.splitn(5, '\t');
+    let start = columns.next()?.parse().ok()?;
+    let end = columns.next()?.parse().ok()?;
+    let _mtime = columns.next()?;
+    let output = columns.next().filter(|output| !output.is_empty())?;
+    let hash = columns.next().unwrap_or_default();
+    if end < start {
+        return None;
+    }
+    Some((start, end, output, hash))
+}
+
+/// Target of an output of a CMake build tree, e.g. vsomeip3 for CMakeFiles/vsomeip3.dir/src/a.cpp.o or libvsomeip3.so.3.
+pub fn target_of(output: &str) -> String {
+    if let Some(directory) = output.find(CMAKE_FILES).map(|index| &output[index + CMAKE_FILES.len()..]) {
+        if let Some(target) = directory.split_once(CMAKE_TARGET_SUFFIX).map(|(target, _)| target) {
+            return target.to_string();
+        }
+    }
+    let file_name = output.rsplit('/').next().unwrap_or(output);
+    library_name(file_name).unwrap_or(file_name).to_string()
+}
+
+// Name of a shared or static library, e.g. vsomeip3 for libvsomeip3.so.3.4.10.
+fn library_name(file_name: &str) -> Option<&str> {
+    let stem = match file_name.find(".so.") {
+        Some(index) => &file_name[..index],
+        None => file_name.strip_suffix(".so")
+            .or_else(|| file_name.strip_suffix(".dylib"))
+            .or_else(|| file_name.strip_suffix(".a"))?,
+    };
+    stem.strip_prefix("lib").filter(|name| !name.is_empty())
+}
+
+fn target_times(edges: &[Edge]) -> Vec<TargetTime> {
+    let mut by_target: BTreeMap<String, TargetTime> = BTreeMap::new();
+    for edge in edges {
+        let target = edge.target();
+        let time = by_target.entry(target.to_string()).or_insert_with(|| TargetTime { target, edges: 0, duration: 0.0 });
+        time.edges += 1;
+        time.duration += edge.duration();
+    }
+    let mut targets: Vec<TargetTime> = by_target.into_values().collect();
+    targets.sort_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap().then(a.target.cmp(&b.target)));
+    targets
+}
+
+impl fmt::Display for NinjaBuild {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let targets: Vec<String> = self.targets.iter()
+            .map(|t| format!("{}: {} edge(s) in {:.1}s", t.target, t.edges, t.duration))
+            .collect();
+        let mut edges: Vec<&Edge> = self.edges.iter().collect();
+        edges.sort_by(|a, b| b.duration().partial_cmp(&a.duration()).unwrap());
+        let outputs: Vec<String> = edges.iter()
+            .map(|e| format!("{}: {:.1}s", e.outputs.join(", "), e.duration()))
+            .collect();
+        let warning = if self.is_incremental() {
+            format!("\n        !!! WARNING: the last build only rebuilt {} of the {} outputs of the log, clean the build directory to profile a full build !!!\n", self.rebuilt_outputs(), self.known_outputs)
+        } else {
+            String::new()
+        };
+
+        let display = format!(r#"
+        Ninja build summary:
+
+        Details:
+
+            log version: {}
+            builds in log: {}
+            rebuilt outputs: {} of {}
+            wall time: {:.1}s
+            cumulative time: {:.1}s
+
+        Slowest targets:
+{}
+        Slowest outputs:
+{}{}"#,
+        self.version,
+        self.builds,
+        self.rebuilt_outputs(),
+        self.known_outputs,
+        self.wall_time,
+        self.cumulative_time,
+        format_lines(&targets),
+        format_lines(&outputs),
+        warning);
+        write!(f, "{}", display)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INCREMENTAL_LOG: &str = include_str!("../../testdata/ninja/incremental.ninja_log");
+
+    fn first_build() -> String {
+        INCREMENTAL_LOG.lines().take(11).map(|l| format!("{}\n", l)).collect()
+    }
+
+    #[test]
+    fn from_reconstructs_durations_of_full_build() {
+        let build = NinjaBuild::from(&first_build()).unwrap();
+
+        assert_eq!(build.version, 5);
+        assert_eq!(build.builds, 1);
+        assert!(!build.is_incremental());
+        assert_eq!(build.wall_time, 3.695);
+        assert_eq!(build.edges.len(), 7);
+        assert_eq!(build.rebuilt_outputs(), 10);
+        let link = build.edges.iter().find(|e| e.outputs[0] == "libvsomeip3.so.3.4.10").unwrap();
+        assert_eq!(link.outputs, vec!["libvsomeip3.so.3.4.10", "libvsomeip3.so.3", "libvsomeip3.so"]);
+        assert_eq!(link.duration(), 0.8);
+    }
+
+    #[test]
+    fn from_sums_edges_of_targets() {
+        let build = NinjaBuild::from(&first_build()).unwrap();
+
+        assert_eq!(build.targets[0], TargetTime { target: String::from("vsomeip3"), edges: 3, duration: 4.859 });
+        let targets: Vec<&str> = build.targets.iter().map(|t| t.target.as_str()).collect();
+        assert_eq!(targets, vec!["vsomeip3", "vsomeip3-sd", "vsomeip3-cfg"]);
+    }
+
+    #[test]
+    fn from_keeps_last_build_of_incremental_log() {
+        let build = NinjaBuild::from(INCREMENTAL_LOG).unwrap();
+
+        assert_eq!(build.builds, 2);
+        assert_eq!(build.known_outputs, 10);
+        assert_eq!(build.edges.len(), 2);
+        assert_eq!(build.wall_time, 1.702);
+        assert!(build.is_incremental());
+        assert!(format!("{}", build).contains("!!! WARNING: the last build only rebuilt 4 of the 10 outputs of the log"));
+    }
+
+    #[test]
+    fn from_keeps_last_edge_of_output_rebuilt_in_same_build() {
+        let build = NinjaBuild::from("# ninja log v6\n0\t100\t0\tgenerated.h\taa\n150\t300\t0\tgenerated.h\tbb\n").unwrap();
+
+        assert_eq!(build.builds, 1);
+        assert_eq!(build.edges, vec![Edge { outputs: vec![String::from("generated.h")], start_ms: 150, end_ms: 300 }]);
+    }
+
+    #[test]
+    fn from_fails_on_invalid_log() {
+        assert_eq!(NinjaBuild::from("").unwrap_err().kind(), ErrorKind::InvalidData);
+        assert!(NinjaBuild::from("0\t100\t0\ta.o\n").unwrap_err().to_string().starts_with("not a ninja log"));
+        assert!(NinjaBuild::from("# ninja log v3\n").unwrap_err().to_string().contains("isn't supported"));
+        assert!(NinjaBuild::from("# ninja log v5\n100\t0\t0\ta.o\taa\n").unwrap_err().to_string().starts_with("invalid ninja log entry on line 2"));
+    }
+
+    #[test]
+    fn target_of_reads_cmake_target() {
+        assert_eq!(target_of("CMakeFiles/vsomeip3.dir/implementation/runtime/src/application_impl.cpp.o"), "vsomeip3");
+        assert_eq!(target_of("test/network_tests/CMakeFiles/routing_test.dir/routing_test.cpp.o"), "routing_test");
+        assert_eq!(target_of("libvsomeip3-sd.so.3.4.10"), "vsomeip3-sd");
+        assert_eq!(target_of("lib/libvsomeip3-e2e.a"), "vsomeip3-e2e");
+        assert_eq!(target_of("examples/hello_world_service"), "hello_world_service");
+        assert_eq!(target_of("libs.txt"), "libs.txt");
+    }
+}
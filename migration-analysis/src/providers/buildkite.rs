@@ -0,0 +1,263 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::io::{Error,ErrorKind};
+
+use super::{decode, send, JobRunSource, NamedJobRun};
+use crate::circleci::jobs::JobRun;
+use crate::config::secret::Secret;
+use crate::http::query_value;
+
+pub const DEFAULT_API_URL: &str = "https://api.buildkite.com/v2";
+/// Environment variables holding a Buildkite API access token, in order of precedence.
+pub const TOKEN_VARIABLES: [&str; 2] = ["BUILDKITE_API_TOKEN", "BUILDKITE_TOKEN"];
+const PER_PAGE: usize = 100;
+
+/// Settings of the Buildkite provider in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BuildkiteSettings {
+    /// Base URL of the REST API, e.g. a local stub
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// Slug of the organization of the pipelines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// API access token with the read_builds scope, optional when one of the token environment variables is set
+    #[serde(default, skip_serializing_if = "Secret::is_empty")]
+    pub token: Secret,
+}
+
+#[derive(Debug, Deserialize)]
+struct Build {
+    #[serde(default)]
+    jobs: Vec<BuildJob>,
+}
+
+/// A job of a build, wait and block steps are jobs without timings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BuildJob {
+    #[serde(rename = "type")]
+    pub job_type: String,
+    pub name: Option<String>,
+    pub step_key: Option<String>,
+    pub state: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl BuildJob {
+    /// Jobs without a name e.g. of a command step with only a key are named after their key.
+    pub fn name(&self) -> Option<&String> {
+        self.name.as_ref().filter(|name| !name.is_empty()).or(self.step_key.as_ref())
+    }
+
+    /// The run of a command job which ran to completion, canceled or skipped jobs have none.
+    pub fn job_run(&self) -> Option<JobRun> {
+        if self.job_type != "script" {
+            return None;
+        }
+        let success = match self.state.as_deref()? {
+            "passed" => true,
+            "failed" | "timed_out" => false,
+            _ => return None,
+        };
+        Some(JobRun {
+            started_at: self.started_at?,
+            stopped_at: self.finished_at?,
+            success,
+        })
+    }
+}
+
+/// Client of the Buildkite REST API for the builds of a single pipeline.
+#[derive(Debug)]
+pub struct Client {
+    pub organization: String,
+    pub pipeline: String,
+    pub branch: String,
+    api_url: String,
+    token: Secret,
+    http: reqwest::Client,
+}
+
+impl Client {
+    pub fn from(organization: &str, pipeline: &str, branch: &str, token: &str) -> Result<Client, Error> {
+        if organization.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "organization is empty"))
+        }
+
+        if pipeline.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "pipeline is empty"))
+        }
+
+        Ok(Client {
+            organization: organization.to_string(),
+            pipeline: pipeline.to_string(),
+            branch: branch.to_string(),
+            api_url: String::from(DEFAULT_API_URL),
+            token: Secret::from(token),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn with_api_url(mut self, api_url: &str) -> Client {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Client {
+        self.http = http;
+        self
+    }
+
+    fn builds_url(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+        format!(
+            "{}/organizations/{}/pipelines/{}/builds?branch={}&state[]=passed&state[]=failed&created_from={}&created_to={}&per_page={}",
+            self.api_url,
+            self.organization,
+            self.pipeline,
+            query_value(&self.branch),
+            since.to_rfc3339_opts(SecondsFormat::Secs, true),
+            until.to_rfc3339_opts(SecondsFormat::Secs, true),
+            PER_PAGE,
+        )
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let mut authorization: HeaderValue = format!("Bearer {}", self.token.expose()).parse().unwrap();
+        authorization.set_sensitive(true);
+        headers.insert("authorization", authorization);
+        headers
+    }
+
+    /// Retrieve the jobs of the finished builds of the branch created in the range.
+    pub async fn get_build_jobs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<BuildJob>, Error> {
+        let mut jobs = vec![];
+        let mut next_url = Some(self.builds_url(since, until));
+        while let Some(url) = next_url {
+            let response = send(self.http.get(&url).headers(self.headers()), &url).await?;
+            next_url = response.headers()
+                .get("link")
+                .and_then(|value| value.to_str().ok())
+                .and_then(next_link);
+            let builds: Vec<Build> = decode(response, &url).await?;
+            jobs.extend(builds.into_iter().flat_map(|b| b.jobs));
+        }
+        Ok(jobs)
+    }
+}
+
+// URL of the next page in a link header e.g. <https://api.buildkite.com/v2/...?page=2>; rel="next"
+fn next_link(link: &str) -> Option<String> {
+    link.split(',')
+        .find(|part| part.split(';').skip(1).any(|parameter| parameter.trim() == "rel=\"next\""))
+        .and_then(|part| part.split(';').next())
+        .map(|url| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+}
+
+#[async_trait]
+impl JobRunSource for Client {
+    async fn get_job_runs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<NamedJobRun>, Error> {
+        Ok(self.get_build_jobs(since, until).await?
+            .iter()
+            .filter_map(|job| Some(NamedJobRun { name: job.name()?.to_string(), run: job.job_run()? }))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::Value;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn stub_client(server: &MockServer) -> Client {
+        Client::from("org", "vsomeip", "main", "token")
+            .unwrap()
+            .with_api_url(&server.uri())
+    }
+
+    fn build_job_json(name: &str, state: &str, started_at: &str, finished_at: &str) -> Value {
+        serde_json::json!({
+            "id": format!("{}-id", name),
+            "type": "script",
+            "name": name,
+            "step_key": null,
+            "state": state,
+            "soft_failed": false,
+            "started_at": started_at,
+            "finished_at": finished_at
+        })
+    }
+
+    #[test]
+    fn next_link_finds_next_page() {
+        let link = "<https://api.buildkite.com/v2/builds?page=3>; rel=\"next\", <https://api.buildkite.com/v2/builds?page=9>; rel=\"last\"";
+
+        assert_eq!(next_link(link).unwrap(), "https://api.buildkite.com/v2/builds?page=3");
+        assert!(next_link("<https://api.buildkite.com/v2/builds?page=1>; rel=\"prev\"").is_none());
+    }
+
+    #[test]
+    fn job_run_skips_wait_steps_and_canceled_jobs() {
+        let job = |job_type: &str, state: &str| BuildJob {
+            job_type: String::from(job_type),
+            name: None,
+            step_key: Some(String::from("bazel-build")),
+            state: Some(String::from(state)),
+            started_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 10, 0, 0).unwrap()),
+            finished_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 10, 5, 0).unwrap()),
+        };
+
+        assert!(job("script", "passed").job_run().unwrap().success);
+        assert!(!job("script", "timed_out").job_run().unwrap().success);
+        assert!(job("script", "canceled").job_run().is_none());
+        assert!(job("waiter", "passed").job_run().is_none());
+        assert_eq!(job("script", "passed").name().unwrap(), "bazel-build");
+    }
+
+    #[tokio::test]
+    async fn get_build_jobs_follows_link_header() {
+        let server = MockServer::start().await;
+        let second_page = format!("{}/organizations/org/pipelines/vsomeip/builds?page=2", server.uri());
+        Mock::given(method("GET"))
+            .and(path("/organizations/org/pipelines/vsomeip/builds"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([{
+                "number": 1,
+                "state": "failed",
+                "jobs": [build_job_json("bazel_build", "failed", "2023-10-02T10:00:00Z", "2023-10-02T10:01:00Z")]
+            }])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/organizations/org/pipelines/vsomeip/builds"))
+            .and(query_param("branch", "main"))
+            .and(query_param("created_from", "2023-10-01T00:00:00Z"))
+            .and(header("authorization", "Bearer token"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("link", format!("<{}>; rel=\"next\"", second_page).as_str())
+                .set_body_json(serde_json::json!([{
+                    "number": 2,
+                    "state": "passed",
+                    "jobs": [
+                        {"id": "wait", "type": "waiter"},
+                        build_job_json("bazel_build", "passed", "2023-10-01T10:00:00Z", "2023-10-01T10:04:00Z")
+                    ]
+                }])))
+            .mount(&server)
+            .await;
+
+        let jobs = stub_client(&server).get_build_jobs(
+            Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2023, 10, 8, 0, 0, 0).unwrap(),
+        ).await.unwrap();
+
+        let states: Vec<Option<&str>> = jobs.iter().map(|j| j.state.as_deref()).collect();
+        assert_eq!(states, vec![None, Some("passed"), Some("failed")]);
+    }
+}
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::io::Error;
+
+use super::{JobRunSource, NamedJobRun};
+use crate::circleci::runs::{collect_workflow_jobs, pipelines_between, RunSource};
+
+/// Runs of the jobs of a CircleCI workflow, read from its pipelines since the insights
+/// endpoint only reports on fixed windows.
+pub struct PipelineJobRuns<S> {
+    source: S,
+    branch: String,
+    workflow: String,
+}
+
+impl<S: RunSource + Sync + Send> PipelineJobRuns<S> {
+    pub fn from(source: S, branch: &str, workflow: &str) -> PipelineJobRuns<S> {
+        PipelineJobRuns {
+            source,
+            branch: branch.to_string(),
+            workflow: workflow.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: RunSource + Sync + Send> JobRunSource for PipelineJobRuns<S> {
+    async fn get_job_runs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<NamedJobRun>, Error> {
+        let pipelines = pipelines_between(&self.source, &self.branch, since, until).await?;
+        Ok(collect_workflow_jobs(&self.source, &pipelines, &self.workflow).await?
+            .into_iter()
+            .flat_map(|(_, jobs)| jobs)
+            .filter_map(|job| Some(NamedJobRun { run: job.job_run()?, name: job.name }))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circleci::runs::MockRunSource;
+    use crate::circleci::runs::tests::{job, pipeline, workflow};
+    use chrono::{Duration, TimeZone};
+    use mockall::predicate::*;
+
+    #[tokio::test]
+    async fn get_job_runs_reads_jobs_of_workflow_in_range() {
+        let since = Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap();
+        let until = Utc.with_ymd_and_hms(2023, 10, 8, 0, 0, 0).unwrap();
+        let mut source = MockRunSource::new();
+        source.expect_get_pipelines_since()
+            .with(function(|branch: &str| branch == "main"), eq(since))
+            .returning(move |_, _| {
+                let mut in_range = pipeline("pipeline-1", 1, "abc");
+                in_range.created_at = since + Duration::hours(10);
+                let mut after_range = pipeline("pipeline-2", 2, "def");
+                after_range.created_at = until + Duration::hours(10);
+                Ok(vec![in_range, after_range])
+            });
+        source.expect_get_pipeline_workflows()
+            .with(function(|id: &str| id == "pipeline-1"))
+            .times(1)
+            .returning(|_| Ok(vec![workflow("workflow-1", "build"), workflow("workflow-2", "deploy")]));
+        source.expect_get_workflow_jobs()
+            .with(function(|id: &str| id == "workflow-1"))
+            .returning(|_| Ok(vec![job("bazel_build", "success", 240), job("cmake_build", "canceled", 100)]));
+
+        let runs = PipelineJobRuns::from(source, "main", "build").get_job_runs(since, until).await.unwrap();
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].name, "bazel_build");
+        assert_eq!(runs[0].run.duration(), 240.0);
+    }
+}
@@ -0,0 +1,275 @@
+use std::io::{Error,ErrorKind};
+
+use super::{JobRunSource, NamedJobRun};
+use crate::circleci::jobs::{Item, JobRun, Jobs};
+use crate::circleci::suggestions::suggestion;
+use crate::circleci::window::Window;
+
+// Below this number of successful runs the durations of a job say little.
+const MIN_SUCCESSFUL_RUNS: usize = 5;
+// Above this ratio between the numbers of successful runs the samples are lopsided.
+const MAX_SAMPLE_RATIO: f64 = 2.0;
+
+/// A job read from its own CI system, with its own credentials and window.
+pub struct JobSource<'a> {
+    pub source: &'a (dyn JobRunSource + Sync),
+    /// Name of the job in the aligned jobs, e.g. "github/build", the job names of both systems may be the same.
+    pub label: String,
+    pub job_name: String,
+    pub window: Window,
+}
+
+/// Base and migration jobs aggregated over the window both CI systems have runs for.
+#[derive(Debug)]
+pub struct AlignedJobs {
+    pub jobs: Jobs,
+    pub window: Window,
+    pub warnings: Vec<String>,
+}
+
+/// Align the runs of jobs from different CI systems so that their metrics are comparable.
+///
+/// Runs are kept where the requested windows overlap and both jobs ran. With `balance`, the same
+/// number of latest runs is kept for both jobs, otherwise lopsided or small samples are warned about.
+pub async fn align_jobs(base: &JobSource<'_>, migration: &JobSource<'_>, balance: bool) -> Result<AlignedJobs, Error> {
+    let requested = base.window.intersection(&migration.window)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("windows of {} ({}) and {} ({}) don't overlap, their durations can't be compared",
+            base.label, base.window, migration.label, migration.window)))?;
+    let base_runs = job_runs(base, &requested).await?;
+    let migration_runs = job_runs(migration, &requested).await?;
+
+    let window = span(&base_runs).intersection(&span(&migration_runs))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("runs of {} ({}) and {} ({}) don't overlap, their durations can't be compared",
+            base.label, span(&base_runs), migration.label, span(&migration_runs))))?;
+    let mut base_runs = within(base_runs, &window);
+    let mut migration_runs = within(migration_runs, &window);
+
+    let mut warnings = vec![];
+    if balance {
+        let runs = base_runs.len().min(migration_runs.len());
+        keep_latest(&mut base_runs, runs);
+        keep_latest(&mut migration_runs, runs);
+    }
+    for (source, runs) in [(base, &base_runs), (migration, &migration_runs)] {
+        let successful_runs = successful(runs);
+        if successful_runs < MIN_SUCCESSFUL_RUNS {
+            warnings.push(format!("{} only has {} successful run(s) in the aligned window, durations may not be representative", source.label, successful_runs));
+        }
+    }
+    let (base_successful, migration_successful) = (successful(&base_runs), successful(&migration_runs));
+    let ratio = base_successful.max(migration_successful) as f64 / base_successful.min(migration_successful).max(1) as f64;
+    if !balance && ratio > MAX_SAMPLE_RATIO {
+        warnings.push(format!("{} has {} successful runs and {} has {}, balance them to compare the same number of latest runs",
+            base.label, base_successful, migration.label, migration_successful));
+    }
+
+    let mut items = vec![];
+    for (source, runs) in [(base, &base_runs), (migration, &migration_runs)] {
+        let item = Item::from_runs(&source.label, runs)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("failed to find successful runs of {} in the aligned window {}", source.label, window)))?;
+        items.push(item);
+    }
+    Ok(AlignedJobs {
+        jobs: Jobs { next_page_token: None, items, decode_failures: vec![] },
+        window,
+        warnings,
+    })
+}
+
+async fn job_runs(source: &JobSource<'_>, window: &Window) -> Result<Vec<JobRun>, Error> {
+    let runs: Vec<NamedJobRun> = source.source.get_job_runs(window.start, window.end).await?;
+    let mut names: Vec<String> = runs.iter().map(|r| r.name.to_string()).collect();
+    names.sort();
+    names.dedup();
+
+    let job_runs: Vec<JobRun> = within(
+        runs.into_iter().filter(|r| r.name == source.job_name).map(|r| r.run).collect(),
+        window,
+    );
+    if job_runs.is_empty() {
+        return Err(Error::new(ErrorKind::NotFound, format!("failed to find runs of {} in {}{}", source.label, window, suggestion(&source.job_name, &names))));
+    }
+    Ok(job_runs)
+}
+
+// From the start of the first run to the end of the last one.
+fn span(runs: &[JobRun]) -> Window {
+    let start = runs.iter().map(|r| r.started_at).min().unwrap();
+    let end = runs.iter().map(|r| r.stopped_at).max().unwrap();
+    Window::from(start, end)
+}
+
+fn within(runs: Vec<JobRun>, window: &Window) -> Vec<JobRun> {
+    let mut runs: Vec<JobRun> = runs.into_iter()
+        .filter(|r| r.started_at >= window.start && r.started_at < window.end)
+        .collect();
+    runs.sort_by_key(|r| r.started_at);
+    runs
+}
+
+// Runs are sorted by start time.
+fn keep_latest(runs: &mut Vec<JobRun>, count: usize) {
+    let dropped = runs.len().saturating_sub(count);
+    runs.drain(..dropped);
+}
+
+fn successful(runs: &[JobRun]) -> usize {
+    runs.iter().filter(|r| r.success).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::MockJobRunSource;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    fn origin() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap()
+    }
+
+    fn days(start: i64, end: i64) -> Window {
+        Window::from(origin() + Duration::days(start), origin() + Duration::days(end))
+    }
+
+    // A successful run of the job every day of the range.
+    fn daily_runs(name: &str, first_day: i64, last_day: i64, seconds: i64) -> Vec<NamedJobRun> {
+        (first_day..=last_day)
+            .map(|day| {
+                let started_at = origin() + Duration::days(day) + Duration::hours(10);
+                NamedJobRun { name: String::from(name), run: JobRun { started_at, stopped_at: started_at + Duration::seconds(seconds), success: true } }
+            })
+            .collect()
+    }
+
+    fn mock_source(runs: Vec<NamedJobRun>) -> MockJobRunSource {
+        let mut source = MockJobRunSource::new();
+        source.expect_get_job_runs().returning(move |_, _| Ok(runs.clone()));
+        source
+    }
+
+    fn job_source<'a>(source: &'a MockJobRunSource, label: &str, job_name: &str, window: Window) -> JobSource<'a> {
+        JobSource { source, label: String::from(label), job_name: String::from(job_name), window }
+    }
+
+    #[tokio::test]
+    async fn align_jobs_keeps_runs_where_both_jobs_ran() {
+        // GitHub keeps runs of the whole range, the Bazel job only started running on day 10.
+        let github = mock_source(daily_runs("build", 0, 29, 600));
+        let circleci = mock_source(daily_runs("bazel_build", 10, 29, 240));
+
+        let aligned = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 30)),
+            &job_source(&circleci, "circleci/bazel_build", "bazel_build", days(0, 30)),
+            false,
+        ).await.unwrap();
+
+        assert_eq!(aligned.window.start, origin() + Duration::days(10) + Duration::hours(10));
+        assert_eq!(aligned.jobs.items[0].name, "github/build");
+        assert_eq!(aligned.jobs.items[0].metrics.total_runs, 20);
+        assert_eq!(aligned.jobs.items[1].metrics.total_runs, 20);
+        assert!(aligned.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn align_jobs_uses_overlap_of_requested_windows() {
+        let github = mock_source(daily_runs("build", 0, 29, 600));
+        let circleci = mock_source(daily_runs("build", 0, 29, 240));
+
+        let aligned = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 20)),
+            &job_source(&circleci, "circleci/build", "build", days(10, 30)),
+            false,
+        ).await.unwrap();
+
+        assert_eq!(aligned.jobs.items[0].metrics.total_runs, 10);
+        assert_eq!(aligned.jobs.items[1].metrics.total_runs, 10);
+        assert_eq!(aligned.jobs.items[1].metrics.duration_metrics.as_ref().unwrap().mean, 240);
+    }
+
+    #[tokio::test]
+    async fn align_jobs_warns_about_lopsided_samples() {
+        let mut base_runs = daily_runs("build", 0, 9, 600);
+        // A second run on every day
+        base_runs.extend(daily_runs("build", 0, 9, 600).into_iter().map(|mut r| {
+            r.run.started_at += Duration::hours(5);
+            r.run.stopped_at += Duration::hours(5);
+            r
+        }));
+        let github = mock_source(base_runs);
+        let circleci = mock_source(daily_runs("bazel_build", 0, 9, 240));
+
+        let aligned = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 10)),
+            &job_source(&circleci, "circleci/bazel_build", "bazel_build", days(0, 10)),
+            false,
+        ).await.unwrap();
+
+        assert_eq!(aligned.warnings.len(), 0);
+
+        let github = mock_source(daily_runs("build", 0, 9, 600));
+        let circleci = mock_source(daily_runs("bazel_build", 7, 9, 240).into_iter().chain(daily_runs("bazel_build", 0, 0, 240)).collect());
+
+        let aligned = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 10)),
+            &job_source(&circleci, "circleci/bazel_build", "bazel_build", days(0, 10)),
+            false,
+        ).await.unwrap();
+
+        assert_eq!(aligned.warnings, vec![
+            String::from("circleci/bazel_build only has 4 successful run(s) in the aligned window, durations may not be representative"),
+            String::from("github/build has 10 successful runs and circleci/bazel_build has 4, balance them to compare the same number of latest runs"),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn align_jobs_balances_latest_runs() {
+        let github = mock_source(daily_runs("build", 0, 19, 600).into_iter().enumerate().map(|(day, mut r)| {
+            // Builds got slower over time.
+            r.run.stopped_at += Duration::seconds(day as i64 * 10);
+            r
+        }).collect());
+        let circleci = mock_source(daily_runs("bazel_build", 0, 0, 240).into_iter().chain(daily_runs("bazel_build", 15, 19, 240)).collect());
+
+        let aligned = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 20)),
+            &job_source(&circleci, "circleci/bazel_build", "bazel_build", days(0, 20)),
+            true,
+        ).await.unwrap();
+
+        let base = &aligned.jobs.items[0];
+        assert_eq!(base.metrics.total_runs, 6);
+        assert_eq!(base.metrics.duration_metrics.as_ref().unwrap().min, 740);
+        assert_eq!(aligned.jobs.items[1].metrics.total_runs, 6);
+        assert!(aligned.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn align_jobs_fails_on_disjoint_windows() {
+        let github = mock_source(daily_runs("build", 0, 9, 600));
+        let circleci = mock_source(daily_runs("bazel_build", 20, 29, 240));
+
+        let actual_err = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 30)),
+            &job_source(&circleci, "circleci/bazel_build", "bazel_build", days(0, 30)),
+            false,
+        ).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+        assert!(actual_err.to_string().starts_with("runs of github/build"));
+    }
+
+    #[tokio::test]
+    async fn align_jobs_suggests_job_name() {
+        let github = mock_source(daily_runs("build", 0, 9, 600));
+        let circleci = mock_source(daily_runs("bazel_build", 0, 9, 240));
+
+        let actual_err = align_jobs(
+            &job_source(&github, "github/build", "build", days(0, 10)),
+            &job_source(&circleci, "circleci/bazel-build", "bazel-build", days(0, 10)),
+            false,
+        ).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::NotFound);
+        assert!(actual_err.to_string().ends_with(", did you mean bazel_build?"));
+    }
+}
@@ -0,0 +1,304 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{Error,ErrorKind};
+
+use super::{decode, send, JobRunSource, NamedJobRun};
+use crate::circleci::jobs::JobRun;
+use crate::config::secret::Secret;
+use crate::http::query_value;
+
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+/// Environment variables holding a GitHub token, in order of precedence.
+pub const TOKEN_VARIABLES: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+const PER_PAGE: usize = 100;
+
+/// Settings of the GitHub Actions provider in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GithubSettings {
+    /// Base URL of the REST API, e.g. "https://github.example.com/api/v3" for GitHub Enterprise Server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// Repository of the workflows, e.g. "COVESA/vsomeip"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// Optional when one of the token environment variables is set, public repositories don't need one
+    #[serde(default, skip_serializing_if = "Secret::is_empty")]
+    pub token: Secret,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRuns {
+    total_count: usize,
+    workflow_runs: Vec<WorkflowRun>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkflowRun {
+    id: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunJobs {
+    total_count: usize,
+    jobs: Vec<RunJob>,
+}
+
+/// A job of a workflow run, its name includes the matrix values e.g. "build (ubuntu-22.04)".
+#[derive(Debug, Clone, Deserialize)]
+pub struct RunJob {
+    pub name: String,
+    pub conclusion: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl RunJob {
+    /// The run of a job which ran to completion, canceled or skipped jobs have none.
+    pub fn job_run(&self) -> Option<JobRun> {
+        let success = match self.conclusion.as_deref()? {
+            "success" => true,
+            "failure" | "timed_out" => false,
+            _ => return None,
+        };
+        Some(JobRun {
+            started_at: self.started_at?,
+            stopped_at: self.completed_at?,
+            success,
+        })
+    }
+}
+
+/// Client of the GitHub Actions REST API for the runs of a single workflow.
+#[derive(Debug)]
+pub struct Client {
+    pub repository: String,
+    pub workflow: String,
+    pub branch: String,
+    api_url: String,
+    token: Secret,
+    http: reqwest::Client,
+}
+
+impl Client {
+    /// The workflow is its file name e.g. "c-cpp.yml" or its numeric id.
+    pub fn from(repository: &str, workflow: &str, branch: &str, token: &str) -> Result<Client, Error> {
+        match repository.split_once('/') {
+            Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {},
+            _ => return Err(Error::new(ErrorKind::InvalidInput, format!("invalid repository {}, expected owner/name", repository))),
+        }
+
+        if workflow.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "workflow is empty"))
+        }
+
+        Ok(Client {
+            repository: repository.to_string(),
+            workflow: workflow.to_string(),
+            branch: branch.to_string(),
+            api_url: String::from(DEFAULT_API_URL),
+            token: Secret::from(token),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn with_api_url(mut self, api_url: &str) -> Client {
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Client {
+        self.http = http;
+        self
+    }
+
+    fn runs_url(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+        format!(
+            "{}/repos/{}/actions/workflows/{}/runs?branch={}&status=completed&created={}..{}",
+            self.api_url,
+            self.repository,
+            self.workflow,
+            query_value(&self.branch),
+            since.to_rfc3339_opts(SecondsFormat::Secs, true),
+            until.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+    }
+
+    fn run_jobs_url(&self, run_id: u64) -> String {
+        format!("{}/repos/{}/actions/runs/{}/jobs", self.api_url, self.repository, run_id)
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", HeaderValue::from_static("application/vnd.github+json"));
+        headers.insert("x-github-api-version", HeaderValue::from_static("2022-11-28"));
+        // Requests without a user agent are rejected.
+        headers.insert("user-agent", HeaderValue::from_static("mig"));
+        if !self.token.is_empty() {
+            let mut authorization: HeaderValue = format!("Bearer {}", self.token.expose()).parse().unwrap();
+            authorization.set_sensitive(true);
+            headers.insert("authorization", authorization);
+        }
+        headers
+    }
+
+    /// Retrieve the ids of the completed runs of the workflow created in the range.
+    pub async fn get_workflow_run_ids(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<u64>, Error> {
+        let runs = self.get_pages(self.runs_url(since, until), |page: WorkflowRuns| (page.total_count, page.workflow_runs)).await?;
+        Ok(runs.iter().map(|r| r.id).collect())
+    }
+
+    pub async fn get_run_jobs(&self, run_id: u64) -> Result<Vec<RunJob>, Error> {
+        self.get_pages(self.run_jobs_url(run_id), |page: RunJobs| (page.total_count, page.jobs)).await
+    }
+
+    /// Follow the numbered pages until all the items counted by the first page are retrieved.
+    async fn get_pages<P: DeserializeOwned, T>(&self, url: String, items_of: impl Fn(P) -> (usize, Vec<T>)) -> Result<Vec<T>, Error> {
+        let mut items = vec![];
+        let separator = if url.contains('?') { '&' } else { '?' };
+        for page_number in 1.. {
+            let (total_count, page_items) = items_of(self.get(format!("{}{}per_page={}&page={}", url, separator, PER_PAGE, page_number)).await?);
+            let last_page = page_items.len() < PER_PAGE;
+            items.extend(page_items);
+            if last_page || items.len() >= total_count {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: String) -> Result<T, Error> {
+        let response = send(self.http.get(&url).headers(self.headers()), &url).await?;
+        decode(response, &url).await
+    }
+}
+
+#[async_trait]
+impl JobRunSource for Client {
+    async fn get_job_runs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<NamedJobRun>, Error> {
+        let mut runs = vec![];
+        for run_id in self.get_workflow_run_ids(since, until).await? {
+            for job in self.get_run_jobs(run_id).await? {
+                if let Some(run) = job.job_run() {
+                    runs.push(NamedJobRun { name: job.name, run });
+                }
+            }
+        }
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::Value;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn stub_client(server: &MockServer) -> Client {
+        Client::from("COVESA/vsomeip", "c-cpp.yml", "master", "token")
+            .unwrap()
+            .with_api_url(&server.uri())
+    }
+
+    fn run_job_json(name: &str, conclusion: &str, started_at: &str, completed_at: &str) -> Value {
+        serde_json::json!({
+            "id": 1,
+            "run_id": 10,
+            "name": name,
+            "status": "completed",
+            "conclusion": conclusion,
+            "started_at": started_at,
+            "completed_at": completed_at,
+            "steps": []
+        })
+    }
+
+    #[test]
+    fn from_rejects_invalid_repository() {
+        for repository in ["vsomeip", "COVESA/", "/vsomeip", "COVESA/vsomeip/extra"] {
+            let actual_err = Client::from(repository, "c-cpp.yml", "master", "").unwrap_err();
+
+            assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn job_run_skips_canceled_and_unfinished_jobs() {
+        let job = |conclusion: Option<&str>| RunJob {
+            name: String::from("build"),
+            conclusion: conclusion.map(String::from),
+            started_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 10, 0, 0).unwrap()),
+            completed_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 10, 5, 0).unwrap()),
+        };
+
+        assert!(job(Some("success")).job_run().unwrap().success);
+        assert!(!job(Some("timed_out")).job_run().unwrap().success);
+        assert!(job(Some("cancelled")).job_run().is_none());
+        assert!(job(Some("skipped")).job_run().is_none());
+        assert!(job(None).job_run().is_none());
+    }
+
+    #[tokio::test]
+    async fn get_run_jobs_follows_pages() {
+        let server = MockServer::start().await;
+        let first_page: Vec<Value> = (0..PER_PAGE)
+            .map(|_| run_job_json("build", "success", "2023-10-01T10:00:00Z", "2023-10-01T10:05:00Z"))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/repos/COVESA/vsomeip/actions/runs/10/jobs"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": PER_PAGE + 1,
+                "jobs": [run_job_json("test", "success", "2023-10-01T10:05:00Z", "2023-10-01T10:06:00Z")]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/COVESA/vsomeip/actions/runs/10/jobs"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": PER_PAGE + 1,
+                "jobs": first_page
+            })))
+            .mount(&server)
+            .await;
+
+        let jobs = stub_client(&server).get_run_jobs(10).await.unwrap();
+
+        assert_eq!(jobs.len(), PER_PAGE + 1);
+        assert_eq!(jobs.last().unwrap().name, "test");
+    }
+
+    #[tokio::test]
+    async fn get_workflow_run_ids_filters_completed_runs_of_branch() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/COVESA/vsomeip/actions/workflows/c-cpp.yml/runs"))
+            .and(query_param("branch", "master"))
+            .and(query_param("status", "completed"))
+            .and(query_param("created", "2023-10-01T00:00:00Z..2023-10-08T00:00:00Z"))
+            .and(header("authorization", "Bearer token"))
+            .and(header("user-agent", "mig"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "total_count": 2,
+                "workflow_runs": [
+                    {"id": 1, "name": "C/C++ CI", "head_sha": "abc", "status": "completed", "conclusion": "success"},
+                    {"id": 2, "name": "C/C++ CI", "head_sha": "def", "status": "completed", "conclusion": "failure"}
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let run_ids = stub_client(&server).get_workflow_run_ids(
+            Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2023, 10, 8, 0, 0, 0).unwrap(),
+        ).await.unwrap();
+
+        assert_eq!(run_ids, vec![1, 2]);
+    }
+}
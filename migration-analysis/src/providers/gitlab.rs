@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{Error,ErrorKind};
+
+use super::{decode, send, JobRunSource, NamedJobRun};
+use crate::circleci::jobs::JobRun;
+use crate::config::secret::Secret;
+use crate::http::query_value;
+
+pub const DEFAULT_URL: &str = "https://gitlab.com";
+/// Environment variables holding a GitLab token, in order of precedence.
+pub const TOKEN_VARIABLES: [&str; 1] = ["GITLAB_TOKEN"];
+const PER_PAGE: usize = 100;
+
+/// Settings of the GitLab CI provider in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GitlabSettings {
+    /// URL of the instance, e.g. "https://gitlab.example.com" for a self-managed one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Path or numeric id of the project, e.g. "group/vsomeip"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Optional when one of the token environment variables is set, public projects don't need one
+    #[serde(default, skip_serializing_if = "Secret::is_empty")]
+    pub token: Secret,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pipeline {
+    id: u64,
+}
+
+/// A job of a pipeline, retried jobs are left out by the API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PipelineJob {
+    pub name: String,
+    pub status: String,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl PipelineJob {
+    /// The run of a job which ran to completion, canceled, skipped or manual jobs have none.
+    pub fn job_run(&self) -> Option<JobRun> {
+        let success = match self.status.as_str() {
+            "success" => true,
+            "failed" => false,
+            _ => return None,
+        };
+        Some(JobRun {
+            started_at: self.started_at?,
+            stopped_at: self.finished_at?,
+            success,
+        })
+    }
+}
+
+/// Client of the GitLab REST API for the pipelines of a single ref of a project.
+#[derive(Debug)]
+pub struct Client {
+    pub project: String,
+    pub reference: String,
+    url: String,
+    token: Secret,
+    http: reqwest::Client,
+}
+
+impl Client {
+    pub fn from(project: &str, reference: &str, token: &str) -> Result<Client, Error> {
+        if project.trim_matches('/').is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "project is empty"))
+        }
+
+        if reference.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "ref is empty"))
+        }
+
+        Ok(Client {
+            project: project.trim_matches('/').to_string(),
+            reference: reference.to_string(),
+            url: String::from(DEFAULT_URL),
+            token: Secret::from(token),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn with_url(mut self, url: &str) -> Client {
+        self.url = url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Client {
+        self.http = http;
+        self
+    }
+
+    // Project paths are URL encoded in place of the id.
+    fn project_url(&self) -> String {
+        format!("{}/api/v4/projects/{}", self.url, self.project.replace('/', "%2F"))
+    }
+
+    fn pipelines_url(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+        format!(
+            "{}/pipelines?ref={}&scope=finished&updated_after={}&updated_before={}",
+            self.project_url(),
+            query_value(&self.reference),
+            since.to_rfc3339_opts(SecondsFormat::Secs, true),
+            until.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )
+    }
+
+    fn pipeline_jobs_url(&self, pipeline_id: u64) -> String {
+        format!("{}/pipelines/{}/jobs", self.project_url(), pipeline_id)
+    }
+
+    fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        if !self.token.is_empty() {
+            let mut token: HeaderValue = self.token.expose().parse().unwrap();
+            token.set_sensitive(true);
+            headers.insert("private-token", token);
+        }
+        headers
+    }
+
+    /// Retrieve the ids of the finished pipelines of the ref updated in the range.
+    pub async fn get_pipeline_ids(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<u64>, Error> {
+        let pipelines: Vec<Pipeline> = self.get_pages(self.pipelines_url(since, until)).await?;
+        Ok(pipelines.iter().map(|p| p.id).collect())
+    }
+
+    pub async fn get_pipeline_jobs(&self, pipeline_id: u64) -> Result<Vec<PipelineJob>, Error> {
+        self.get_pages(self.pipeline_jobs_url(pipeline_id)).await
+    }
+
+    /// Follow the numbered pages as long as the x-next-page header points to another one.
+    async fn get_pages<T: DeserializeOwned>(&self, url: String) -> Result<Vec<T>, Error> {
+        let mut items = vec![];
+        let separator = if url.contains('?') { '&' } else { '?' };
+        let mut page_number = String::from("1");
+        loop {
+            let page_url = format!("{}{}per_page={}&page={}", url, separator, PER_PAGE, page_number);
+            let response = send(self.http.get(&page_url).headers(self.headers()), &page_url).await?;
+            let next_page = response.headers()
+                .get("x-next-page")
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default();
+            let page_items: Vec<T> = decode(response, &page_url).await?;
+            items.extend(page_items);
+
+            if next_page.is_empty() {
+                return Ok(items);
+            }
+            page_number = next_page;
+        }
+    }
+}
+
+#[async_trait]
+impl JobRunSource for Client {
+    async fn get_job_runs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<NamedJobRun>, Error> {
+        let mut runs = vec![];
+        for pipeline_id in self.get_pipeline_ids(since, until).await? {
+            for job in self.get_pipeline_jobs(pipeline_id).await? {
+                if let Some(run) = job.job_run() {
+                    runs.push(NamedJobRun { name: job.name, run });
+                }
+            }
+        }
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::Value;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn stub_client(server: &MockServer) -> Client {
+        Client::from("group/vsomeip", "main", "token")
+            .unwrap()
+            .with_url(&server.uri())
+    }
+
+    fn pipeline_job_json(name: &str, status: &str, started_at: &str, finished_at: &str) -> Value {
+        serde_json::json!({
+            "id": 1,
+            "name": name,
+            "stage": "build",
+            "status": status,
+            "ref": "main",
+            "created_at": started_at,
+            "started_at": started_at,
+            "finished_at": finished_at,
+            "duration": 0.0,
+            "queued_duration": 1.5
+        })
+    }
+
+    #[test]
+    fn from_rejects_empty_project() {
+        let actual_err = Client::from("/", "main", "").unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn project_url_encodes_project_path() {
+        let client = Client::from("group/subgroup/vsomeip", "main", "").unwrap().with_url("https://gitlab.example.com/");
+
+        assert_eq!(client.project_url(), "https://gitlab.example.com/api/v4/projects/group%2Fsubgroup%2Fvsomeip");
+    }
+
+    #[test]
+    fn job_run_skips_canceled_and_manual_jobs() {
+        let job = |status: &str| PipelineJob {
+            name: String::from("build"),
+            status: String::from(status),
+            started_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 10, 0, 0).unwrap()),
+            finished_at: Some(Utc.with_ymd_and_hms(2023, 10, 1, 10, 5, 0).unwrap()),
+        };
+
+        assert!(job("success").job_run().unwrap().success);
+        assert!(!job("failed").job_run().unwrap().success);
+        assert!(job("canceled").job_run().is_none());
+        assert!(job("manual").job_run().is_none());
+    }
+
+    #[tokio::test]
+    async fn get_pipeline_jobs_follows_next_page_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fvsomeip/pipelines/7/jobs"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("x-next-page", "")
+                .set_body_json(serde_json::json!([pipeline_job_json("test", "success", "2023-10-01T10:05:00Z", "2023-10-01T10:06:00Z")])))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fvsomeip/pipelines/7/jobs"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200)
+                .insert_header("x-next-page", "2")
+                .set_body_json(serde_json::json!([pipeline_job_json("build", "success", "2023-10-01T10:00:00Z", "2023-10-01T10:05:00Z")])))
+            .mount(&server)
+            .await;
+
+        let jobs = stub_client(&server).get_pipeline_jobs(7).await.unwrap();
+
+        let names: Vec<String> = jobs.into_iter().map(|j| j.name).collect();
+        assert_eq!(names, vec![String::from("build"), String::from("test")]);
+    }
+
+    #[tokio::test]
+    async fn get_pipeline_ids_filters_finished_pipelines_of_ref() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v4/projects/group%2Fvsomeip/pipelines"))
+            .and(query_param("ref", "main"))
+            .and(query_param("scope", "finished"))
+            .and(query_param("updated_after", "2023-10-01T00:00:00Z"))
+            .and(query_param("updated_before", "2023-10-08T00:00:00Z"))
+            .and(header("private-token", "token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"id": 1, "ref": "main", "status": "success"},
+                {"id": 2, "ref": "main", "status": "failed"}
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let pipeline_ids = stub_client(&server).get_pipeline_ids(
+            Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2023, 10, 8, 0, 0, 0).unwrap(),
+        ).await.unwrap();
+
+        assert_eq!(pipeline_ids, vec![1, 2]);
+    }
+}
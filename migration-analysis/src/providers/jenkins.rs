@@ -0,0 +1,310 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{Error,ErrorKind};
+
+use super::{decode, resolve_token, send, JobRunSource, NamedJobRun};
+use crate::circleci::jobs::JobRun;
+use crate::config::secret::Secret;
+
+/// Environment variables holding a Jenkins API token or password, in order of precedence.
+pub const TOKEN_VARIABLES: [&str; 2] = ["JENKINS_API_TOKEN", "JENKINS_TOKEN"];
+/// Environment variable holding the Jenkins user the token belongs to.
+pub const USER_VARIABLE: &str = "JENKINS_USER";
+// Fields of the builds retrieved, the API returns a lot more without a tree.
+const BUILD_FIELDS: &str = "result,timestamp,duration,building";
+// Builds retrieved per request, newest first, so that the history isn't retrieved at once.
+const PAGE_SIZE: usize = 100;
+
+/// Settings of the Jenkins provider in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JenkinsSettings {
+    /// URL of the controller, e.g. "https://jenkins.example.com"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// User authenticating with the token, optional when the environment variable is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// API token or password of the user, optional when one of the token environment variables is set
+    #[serde(default, skip_serializing_if = "Secret::is_empty")]
+    pub token: Secret,
+}
+
+impl JenkinsSettings {
+    /// The user and token of the environment take precedence over the ones of the config file.
+    pub fn resolve_credentials(&self, env: impl Fn(&str) -> Option<String>) -> (Option<String>, Secret) {
+        let user = env(USER_VARIABLE)
+            .filter(|user| !user.trim().is_empty())
+            .map(|user| user.trim().to_string())
+            .or_else(|| self.user.clone());
+        (user, resolve_token(&TOKEN_VARIABLES, &self.token, env))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobBuilds {
+    all_builds: Vec<Build>,
+}
+
+/// A build of a job, times are in milliseconds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Build {
+    pub result: Option<String>,
+    pub timestamp: i64,
+    pub duration: i64,
+    #[serde(default)]
+    pub building: bool,
+}
+
+impl Build {
+    /// The run of a build which ran to completion, aborted or running builds have none.
+    /// Unstable builds completed with failing tests and count as failed.
+    pub fn job_run(&self) -> Option<JobRun> {
+        if self.building {
+            return None;
+        }
+        let success = match self.result.as_deref()? {
+            "SUCCESS" => true,
+            "FAILURE" | "UNSTABLE" => false,
+            _ => return None,
+        };
+        let started_at = DateTime::from_timestamp_millis(self.timestamp)?;
+        Some(JobRun {
+            started_at,
+            stopped_at: started_at + Duration::milliseconds(self.duration),
+            success,
+        })
+    }
+}
+
+/// Client of the Jenkins JSON API for the build history of jobs.
+#[derive(Debug)]
+pub struct Client {
+    pub url: String,
+    pub jobs: Vec<String>,
+    user: Option<String>,
+    token: Secret,
+    http: reqwest::Client,
+}
+
+impl Client {
+    /// Jobs are their full name, jobs in folders are separated by slashes e.g. "legacy/cmake_build".
+    pub fn from(url: &str, jobs: &[&String], user: Option<&str>, token: &str) -> Result<Client, Error> {
+        if url.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "url is empty"))
+        }
+
+        if reqwest::Url::parse(url).map_or(true, |url| url.cannot_be_a_base()) {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("url {} is invalid", url)))
+        }
+
+        if jobs.iter().any(|job| job.trim_matches('/').is_empty()) {
+            return Err(Error::new(ErrorKind::InvalidInput, "job is empty"))
+        }
+
+        if user.is_none() && !token.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "token is set without the user it belongs to"))
+        }
+
+        Ok(Client {
+            url: url.trim_end_matches('/').to_string(),
+            jobs: jobs.iter().map(|job| job.trim_matches('/').to_string()).collect(),
+            user: user.map(String::from),
+            token: Secret::from(token),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Client {
+        self.http = http;
+        self
+    }
+
+    // Every folder of the full name is a job of its own in the URL, the range selects builds from the newest.
+    fn builds_url(&self, job: &str, offset: usize) -> String {
+        let mut url = reqwest::Url::parse(&self.url).expect("url is validated by Client::from");
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.pop_if_empty();
+            for name in job.split('/') {
+                segments.push("job").push(name);
+            }
+            segments.push("api").push("json");
+        }
+        url.query_pairs_mut().append_pair("tree", &format!("allBuilds[{}]{{{},{}}}", BUILD_FIELDS, offset, offset + PAGE_SIZE));
+        url.to_string()
+    }
+
+    /// Builds of a job from the newest, up to the first one started before `since`.
+    pub async fn get_builds(&self, job: &str, since: DateTime<Utc>) -> Result<Vec<Build>, Error> {
+        let mut builds: Vec<Build> = vec![];
+        loop {
+            let page = self.get_builds_page(job, builds.len()).await?;
+            let last_page = page.len() < PAGE_SIZE || page.iter().any(|build| build.timestamp < since.timestamp_millis());
+            builds.extend(page);
+            if last_page {
+                return Ok(builds);
+            }
+        }
+    }
+
+    async fn get_builds_page(&self, job: &str, offset: usize) -> Result<Vec<Build>, Error> {
+        let url = self.builds_url(job, offset);
+        let mut request = self.http.get(&url);
+        if let Some(user) = &self.user {
+            request = request.basic_auth(user, Some(self.token.expose()));
+        }
+        let response = send(request, &url).await
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => Error::new(ErrorKind::NotFound, format!("failed to find Jenkins job {}", job)),
+                _ => e,
+            })?;
+        let builds: JobBuilds = decode(response, &url).await?;
+        Ok(builds.all_builds)
+    }
+}
+
+#[async_trait]
+impl JobRunSource for Client {
+    async fn get_job_runs(&self, since: DateTime<Utc>, _until: DateTime<Utc>) -> Result<Vec<NamedJobRun>, Error> {
+        // The API has no filter on time, builds outside of the range are left out by the caller.
+        let mut runs = vec![];
+        for job in &self.jobs {
+            for build in self.get_builds(job, since).await? {
+                if let Some(run) = build.job_run() {
+                    runs.push(NamedJobRun { name: job.to_string(), run });
+                }
+            }
+        }
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::Value;
+    use wiremock::matchers::{header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn build_json(number: u64, result: Option<&str>, started_at: DateTime<Utc>, seconds: i64) -> Value {
+        serde_json::json!({
+            "_class": "hudson.model.FreeStyleBuild",
+            "number": number,
+            "result": result,
+            "timestamp": started_at.timestamp_millis(),
+            "duration": seconds * 1000,
+            "building": result.is_none()
+        })
+    }
+
+    #[test]
+    fn from_rejects_token_without_user() {
+        let job = String::from("cmake_build");
+
+        let actual_err = Client::from("https://jenkins.example.com", &[&job], None, "token").unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn builds_url_nests_and_encodes_folders() {
+        let job = String::from("legacy/cmake build#1?");
+        let client = Client::from("https://jenkins.example.com/ci/", &[&job], None, "").unwrap();
+
+        assert_eq!(
+            client.builds_url(&client.jobs[0], 100),
+            "https://jenkins.example.com/ci/job/legacy/job/cmake%20build%231%3F/api/json?tree=allBuilds%5Bresult%2Ctimestamp%2Cduration%2Cbuilding%5D%7B100%2C200%7D");
+    }
+
+    #[test]
+    fn from_rejects_invalid_url() {
+        let job = String::from("cmake_build");
+
+        let actual_err = Client::from("jenkins.example.com", &[&job], None, "").unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn resolve_credentials_prefers_environment() {
+        let settings = JenkinsSettings { user: Some(String::from("config")), token: Secret::from("config"), ..JenkinsSettings::default() };
+
+        let (user, token) = settings.resolve_credentials(|variable| match variable {
+            "JENKINS_USER" => Some(String::from("ci")),
+            "JENKINS_TOKEN" => Some(String::from("api-token")),
+            _ => None,
+        });
+
+        assert_eq!(user.unwrap(), "ci");
+        assert_eq!(token.expose(), "api-token");
+    }
+
+    #[test]
+    fn job_run_counts_unstable_builds_as_failed_and_skips_aborted_ones() {
+        let started_at = Utc.with_ymd_and_hms(2023, 10, 1, 10, 0, 0).unwrap();
+        let build = |result: Option<&str>| Build { result: result.map(String::from), timestamp: started_at.timestamp_millis(), duration: 90_500, building: result.is_none() };
+
+        let run = build(Some("SUCCESS")).job_run().unwrap();
+        assert_eq!(run.started_at, started_at);
+        assert_eq!(run.duration(), 90.5);
+        assert!(!build(Some("UNSTABLE")).job_run().unwrap().success);
+        assert!(build(Some("ABORTED")).job_run().is_none());
+        assert!(build(None).job_run().is_none());
+    }
+
+    #[tokio::test]
+    async fn get_builds_fails_on_unknown_job() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let job = String::from("cmake-build");
+
+        let actual_err = Client::from(&server.uri(), &[&job], None, "").unwrap().get_builds(&job, Utc::now()).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn get_builds_pages_until_builds_before_since() {
+        let since = Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap();
+        let server = MockServer::start().await;
+        let recent: Vec<Value> = (0..PAGE_SIZE as u64)
+            .map(|index| build_json(200 - index, Some("SUCCESS"), since + Duration::days(2) - Duration::minutes(index as i64), 60))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/job/legacy/job/cmake_build/api/json"))
+            .and(query_param("tree", "allBuilds[result,timestamp,duration,building]{0,100}"))
+            // ci:api-token
+            .and(header("authorization", "Basic Y2k6YXBpLXRva2Vu"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "allBuilds": recent })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("tree", "allBuilds[result,timestamp,duration,building]{100,200}"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "allBuilds": [
+                    build_json(100, Some("SUCCESS"), since + Duration::hours(1), 60),
+                    build_json(99, Some("SUCCESS"), since - Duration::days(1), 60)
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("tree", "allBuilds[result,timestamp,duration,building]{200,300}"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "allBuilds": [] })))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let job = String::from("legacy/cmake_build");
+
+        let builds = Client::from(&server.uri(), &[&job], Some("ci"), "api-token").unwrap().get_builds(&job, since).await.unwrap();
+
+        assert_eq!(builds.len(), PAGE_SIZE + 2);
+    }
+}
@@ -0,0 +1,208 @@
+pub mod buildkite;
+pub mod circleci;
+pub mod compare;
+pub mod github;
+pub mod gitlab;
+pub mod jenkins;
+
+use async_trait::async_trait;
+#[cfg(test)]
+use mockall::automock;
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use std::io::{Error,ErrorKind};
+
+use crate::circleci::jobs::{Item, JobRun, Jobs};
+use crate::circleci::suggestions::suggestion;
+use crate::config::secret::Secret;
+
+/// The run of a job retrieved from a CI system other than CircleCI.
+#[derive(Debug, Clone)]
+pub struct NamedJobRun {
+    pub name: String,
+    pub run: JobRun,
+}
+
+/// CI systems whose job runs are aggregated locally, they have no insights endpoint.
+#[cfg_attr(test, automock)]
+#[async_trait]
+pub trait JobRunSource {
+    /// Runs of all jobs started in the range, canceled or skipped jobs are left out.
+    async fn get_job_runs(&self, since: DateTime<Utc>, until: DateTime<Utc>) -> Result<Vec<NamedJobRun>, Error>;
+}
+
+/// The token of the first environment variable set takes precedence over the one of the config file.
+pub fn resolve_token(variables: &[&str], configured: &Secret, env: impl Fn(&str) -> Option<String>) -> Secret {
+    variables.iter()
+        .filter_map(|variable| env(variable))
+        .find(|token| !token.trim().is_empty())
+        .map(|token| Secret::from(token.trim()))
+        .unwrap_or_else(|| configured.clone())
+}
+
+/// Send a request of a provider client, unknown resources fail with NotFound and other unsuccessful statuses with Other.
+pub async fn send(request: RequestBuilder, url: &str) -> Result<Response, Error> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Error::other(format!("failed to retrieve response from {}: {}", url, e)))?;
+
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND => Err(Error::new(ErrorKind::NotFound, format!("request to {} failed with status {}", url, response.status()))),
+        status => Err(Error::other(format!("request to {} failed with status {}", url, status))),
+    }
+}
+
+/// Decode the JSON body of the response to a request to the url.
+pub async fn decode<T: DeserializeOwned>(response: Response, url: &str) -> Result<T, Error> {
+    response.json::<T>()
+        .await
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("failed to deserialize response from {}: {}", url, e)))
+}
+
+/// Aggregate the runs of the base and migration jobs into the shape of the insights endpoint.
+pub async fn collect_jobs(
+    source: &(dyn JobRunSource + Sync),
+    migration_job_name: &String,
+    base_job_name: &String,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Jobs, Error> {
+    if since >= until {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("start of the range {} is not before its end {}", since.to_rfc3339(), until.to_rfc3339())));
+    }
+    let runs: Vec<NamedJobRun> = source.get_job_runs(since, until).await?
+        .into_iter()
+        .filter(|r| r.run.started_at >= since && r.run.started_at < until)
+        .collect();
+
+    let mut names: Vec<String> = runs.iter().map(|r| r.name.to_string()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut items = vec![];
+    for name in [base_job_name, migration_job_name] {
+        let job_runs: Vec<JobRun> = runs.iter().filter(|r| &r.name == name).map(|r| r.run.clone()).collect();
+        let item = Item::from_runs(name, &job_runs)
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("failed to find successful runs of job {}{}", name, suggestion(name, &names))))?;
+        items.push(item);
+    }
+    Ok(Jobs { next_page_token: None, items, decode_failures: vec![] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn named_run(name: &str, started_at: DateTime<Utc>, seconds: i64, success: bool) -> NamedJobRun {
+        NamedJobRun {
+            name: String::from(name),
+            run: JobRun { started_at, stopped_at: started_at + Duration::seconds(seconds), success },
+        }
+    }
+
+    #[tokio::test]
+    async fn collect_jobs_aggregates_runs_in_range() {
+        let since = Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap();
+        let until = Utc.with_ymd_and_hms(2023, 10, 8, 0, 0, 0).unwrap();
+        let mut source = MockJobRunSource::new();
+        source.expect_get_job_runs()
+            .returning(move |_, _| Ok(vec![
+                named_run("cmake_build", since + Duration::hours(1), 300, true),
+                named_run("cmake_build", since + Duration::hours(2), 500, true),
+                named_run("bazel_build", since + Duration::hours(1), 200, true),
+                named_run("bazel_build", since + Duration::hours(2), 20, false),
+                // Outside of the range
+                named_run("bazel_build", until + Duration::hours(1), 1000, true),
+            ]));
+
+        let jobs = collect_jobs(&source, &String::from("bazel_build"), &String::from("cmake_build"), since, until).await.unwrap();
+
+        assert_eq!(jobs.items[0].name, "cmake_build");
+        assert_eq!(jobs.items[0].metrics.duration_metrics.as_ref().unwrap().mean, 400);
+        assert_eq!(jobs.items[1].name, "bazel_build");
+        assert_eq!(jobs.items[1].metrics.total_runs, 2);
+        assert_eq!(jobs.items[1].metrics.duration_metrics.as_ref().unwrap().max, 200);
+    }
+
+    #[tokio::test]
+    async fn collect_jobs_suggests_job_name() {
+        let since = Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap();
+        let mut source = MockJobRunSource::new();
+        source.expect_get_job_runs()
+            .returning(move |_, _| Ok(vec![
+                named_run("cmake_build", since, 300, true),
+                named_run("bazel_build", since, 200, true),
+            ]));
+
+        let actual_err = collect_jobs(&source, &String::from("bazel-build"), &String::from("cmake_build"), since, since + Duration::days(1)).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::NotFound);
+        assert_eq!(actual_err.to_string(), "failed to find successful runs of job bazel-build, did you mean bazel_build?");
+    }
+
+    #[tokio::test]
+    async fn collect_jobs_rejects_empty_range() {
+        let since = Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap();
+        let source = MockJobRunSource::new();
+
+        let actual_err = collect_jobs(&source, &String::from("bazel_build"), &String::from("cmake_build"), since, since).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn resolve_token_prefers_environment() {
+        let configured = Secret::from("config");
+
+        let token = resolve_token(&["GITHUB_TOKEN", "GH_TOKEN"], &configured, |variable| (variable == "GH_TOKEN").then(|| String::from("gh ")));
+
+        assert_eq!(token.expose(), "gh");
+        assert_eq!(resolve_token(&["GITHUB_TOKEN", "GH_TOKEN"], &configured, |_| None).expose(), "config");
+    }
+
+    #[tokio::test]
+    async fn send_maps_unsuccessful_statuses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/forbidden"))
+            .respond_with(ResponseTemplate::new(403))
+            .mount(&server)
+            .await;
+        let http = reqwest::Client::new();
+
+        for (endpoint, kind) in [("missing", ErrorKind::NotFound), ("forbidden", ErrorKind::Other)] {
+            let url = format!("{}/{}", server.uri(), endpoint);
+
+            let actual_err = send(http.get(&url), &url).await.unwrap_err();
+
+            assert_eq!(actual_err.kind(), kind);
+            assert!(actual_err.to_string().starts_with(&format!("request to {} failed with status", url)));
+        }
+    }
+
+    #[tokio::test]
+    async fn decode_fails_on_unexpected_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("<html></html>"))
+            .mount(&server)
+            .await;
+        let url = server.uri();
+
+        let response = send(reqwest::Client::new().get(&url), &url).await.unwrap();
+        let actual_err = decode::<Vec<u64>>(response, &url).await.unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,35 @@
+// Number of lines listed in a section of a report, the rest is only counted.
+const MAX_LISTED: usize = 10;
+
+/// Lines of a section of a report indented under its header, "none" when there are none.
+pub fn format_lines(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::from("            none\n");
+    }
+    let mut listed: String = lines.iter().take(MAX_LISTED).map(|l| format!("            {}\n", l)).collect();
+    if lines.len() > MAX_LISTED {
+        listed.push_str(&format!("            ... and {} more\n", lines.len() - MAX_LISTED));
+    }
+    listed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_lines_shows_none_when_empty() {
+        assert_eq!(format_lines(&[]), "            none\n");
+    }
+
+    #[test]
+    fn format_lines_counts_lines_past_limit() {
+        let lines: Vec<String> = (0..MAX_LISTED + 2).map(|i| format!("line {}", i)).collect();
+
+        let listed = format_lines(&lines);
+
+        assert!(listed.starts_with("            line 0\n"));
+        assert!(!listed.contains("line 10"));
+        assert!(listed.ends_with("            line 9\n            ... and 2 more\n"));
+    }
+}
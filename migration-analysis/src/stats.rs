@@ -0,0 +1,73 @@
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let sorted = sorted(values);
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        return Some((sorted[middle - 1] + sorted[middle]) / 2.0);
+    }
+    Some(sorted[middle])
+}
+
+/// Nearest-rank percentile, `percentile` ranges from 0 to 100.
+pub fn percentile(values: &[f64], percentile: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let sorted = sorted(values);
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+pub fn standard_deviation(values: &[f64]) -> Option<f64> {
+    let mean = mean(values)?;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    Some(variance.sqrt())
+}
+
+fn sorted(values: &[f64]) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    sorted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statistics_of_empty_values_are_none() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(median(&[]), None);
+        assert_eq!(percentile(&[], 95.0), None);
+        assert_eq!(standard_deviation(&[]), None);
+    }
+
+    #[test]
+    fn median_of_even_number_of_values_is_mean_of_middle_values() {
+        assert_eq!(median(&[4.0, 1.0, 3.0, 2.0]), Some(2.5));
+        assert_eq!(median(&[3.0, 1.0, 2.0]), Some(2.0));
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let values: Vec<f64> = (1..=20).map(|v| v as f64).collect();
+
+        assert_eq!(percentile(&values, 95.0), Some(19.0));
+        assert_eq!(percentile(&values, 100.0), Some(20.0));
+        assert_eq!(percentile(&values, 0.0), Some(1.0));
+    }
+
+    #[test]
+    fn standard_deviation_is_population_standard_deviation() {
+        assert_eq!(standard_deviation(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]), Some(2.0));
+    }
+}
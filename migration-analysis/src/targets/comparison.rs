@@ -0,0 +1,358 @@
+use super::mapping::{TargetMapping, UnitRules};
+use crate::bazel::bep::BuildSummary;
+use crate::bazel::profile::ProfileSummary;
+use crate::ninja::log::NinjaBuild;
+use crate::report::format_lines;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Error,ErrorKind};
+
+
+/// An output of the CMake build or an action of the Bazel build with the time it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimedUnit {
+    pub name: String,
+    /// CMake target or Bazel label the unit belongs to
+    pub target: Option<String>,
+    /// Seconds
+    pub duration: f64,
+}
+
+/// Edges of the last build of a ninja log, named after their first output.
+pub fn ninja_units(build: &NinjaBuild) -> Vec<TimedUnit> {
+    build.edges.iter()
+        .map(|edge| TimedUnit { name: edge.outputs[0].to_string(), target: Some(edge.target()), duration: edge.duration() })
+        .collect()
+}
+
+/// Actions of a Bazel profile, named after their progress message e.g. "Compiling src/a.cpp".
+pub fn profile_units(profile: &ProfileSummary) -> Vec<TimedUnit> {
+    profile.actions.iter()
+        .map(|action| TimedUnit { name: action.description.to_string(), target: action.target.clone(), duration: action.duration })
+        .collect()
+}
+
+/// Timed actions of a Bazel build, named after their primary output.
+pub fn bep_units(build: &BuildSummary) -> Vec<TimedUnit> {
+    build.actions.iter()
+        .filter_map(|action| Some(TimedUnit {
+            name: action.primary_output.to_string(),
+            target: Some(action.label.to_string()).filter(|label| !label.is_empty()),
+            duration: action.duration?,
+        }))
+        .collect()
+}
+
+/// Seconds a target or unit took in the CMake and the Bazel build.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimeComparison {
+    pub name: String,
+    pub base: f64,
+    pub migration: f64,
+    /// Seconds Bazel took longer than CMake, negative when it was faster
+    pub difference: f64,
+}
+
+impl TimeComparison {
+    fn from(name: &str, base: f64, migration: f64) -> TimeComparison {
+        TimeComparison { name: name.to_string(), base, migration, difference: migration - base }
+    }
+
+    pub fn ratio(&self) -> Option<f64> {
+        if self.base > 0.0 {
+            Some(self.migration / self.base)
+        } else {
+            None
+        }
+    }
+}
+
+/// Targets and units of the CMake and Bazel builds, from the one Bazel is slowest on relative to CMake.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TargetTimes {
+    pub targets: Vec<TimeComparison>,
+    /// Translation units and libraries paired by the unit rules
+    pub units: Vec<TimeComparison>,
+    /// Units matched by a rule only found in the CMake build, or sharing their key with another unit of the build
+    pub base_unmatched: Vec<String>,
+    /// Units matched by a rule only found in the Bazel build, or sharing their key with another unit of the build
+    pub migration_unmatched: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl TargetTimes {
+    pub fn from(base: &[TimedUnit], migration: &[TimedUnit], mapping: &TargetMapping) -> Result<TargetTimes, Error> {
+        if base.is_empty() {
+            return Err(Error::new(ErrorKind::NotFound, "the CMake build has no timed outputs"));
+        }
+        if migration.is_empty() {
+            return Err(Error::new(ErrorKind::NotFound, "the Bazel build has no timed actions, they are only published with --build_event_publish_all_actions"));
+        }
+        let rules = UnitRules::from(mapping)?;
+
+        let (base_keys, mut base_unmatched) = keyed(base, |unit| rules.base_key(&unit.name, unit.target.as_deref()));
+        let (migration_keys, mut migration_unmatched) = keyed(migration, |unit| rules.migration_key(&unit.name, unit.target.as_deref()));
+        let mut warnings = vec![];
+        if !base_unmatched.is_empty() {
+            warnings.push(format!("{} units of the CMake build share their key with another one and aren't compared, the unit rules need to tell them apart", base_unmatched.len()));
+        }
+        if !migration_unmatched.is_empty() {
+            warnings.push(format!("{} units of the Bazel build share their key with another one and aren't compared, the unit rules need to tell them apart", migration_unmatched.len()));
+        }
+        let mut units = vec![];
+        for (key, unit) in &base_keys {
+            match migration_keys.get(key) {
+                Some(migration_unit) => units.push(TimeComparison::from(&key.1, unit.duration, migration_unit.duration)),
+                None => base_unmatched.push(unit.name.to_string()),
+            }
+        }
+        migration_unmatched.extend(migration_keys.iter()
+            .filter(|(key, _)| !base_keys.contains_key(key))
+            .map(|(_, unit)| unit.name.to_string()));
+        base_unmatched.sort();
+        migration_unmatched.sort();
+
+        let base_targets = by_target(base);
+        let migration_targets = by_target(migration);
+        let mut targets = vec![];
+        for (target, duration) in &base_targets {
+            let labels: Vec<(&String, &f64)> = migration_targets.iter()
+                .filter(|(label, _)| mapping.is_target_of(target, label))
+                .collect();
+            // Targets built by several Bazel targets need a mapping to one of them.
+            if let [(_, migration_duration)] = labels.as_slice() {
+                targets.push(TimeComparison::from(target, *duration, **migration_duration));
+            }
+        }
+
+        rank(&mut units);
+        rank(&mut targets);
+        Ok(TargetTimes { targets, units, base_unmatched, migration_unmatched, warnings })
+    }
+
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> TargetTimes {
+        self.warnings.extend(warnings);
+        self
+    }
+}
+
+/// Conditions under which the times of the builds don't compare what each build system does.
+pub fn build_warnings(ninja: &NinjaBuild, bep: Option<&BuildSummary>) -> Vec<String> {
+    let mut warnings = vec![];
+    if ninja.is_incremental() {
+        warnings.push(format!("the CMake build only rebuilt {} of the {} outputs of the ninja log, the other units are missing", ninja.rebuilt_outputs(), ninja.known_outputs));
+    }
+    if let Some(bep) = bep {
+        let hits = bep.remote_cache_hit_rate().unwrap_or(0.0) + bep.disk_cache_hit_rate().unwrap_or(0.0);
+        if hits > 0.0 {
+            warnings.push(format!("{:.0}% of the Bazel actions were cache hits, build without the cache to compare the time of every unit", hits * 100.0));
+        }
+    }
+    warnings
+}
+
+// Units matched by a rule by key, and the names of the units sharing their key which can't be paired.
+fn keyed(units: &[TimedUnit], key: impl Fn(&TimedUnit) -> Option<(usize, String)>) -> (BTreeMap<(usize, String), &TimedUnit>, Vec<String>) {
+    let mut keys: BTreeMap<(usize, String), Vec<&TimedUnit>> = BTreeMap::new();
+    for unit in units {
+        if let Some(key) = key(unit) {
+            keys.entry(key).or_default().push(unit);
+        }
+    }
+    let mut shared = vec![];
+    let mut unique = BTreeMap::new();
+    for (key, units) in keys {
+        match units.as_slice() {
+            [unit] => {
+                unique.insert(key, *unit);
+            },
+            _ => shared.extend(units.iter().map(|unit| unit.name.to_string())),
+        }
+    }
+    (unique, shared)
+}
+
+fn by_target(units: &[TimedUnit]) -> BTreeMap<String, f64> {
+    let mut targets: BTreeMap<String, f64> = BTreeMap::new();
+    for unit in units {
+        if let Some(target) = &unit.target {
+            *targets.entry(target.to_string()).or_default() += unit.duration;
+        }
+    }
+    targets
+}
+
+fn rank(comparisons: &mut [TimeComparison]) {
+    comparisons.sort_by(|a, b| b.difference.partial_cmp(&a.difference).unwrap().then(a.name.cmp(&b.name)));
+}
+
+impl fmt::Display for TargetTimes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let warnings: String = self.warnings.iter()
+            .map(|w| format!("\n        !!! WARNING: {} !!!\n", w))
+            .collect();
+
+        let display = format!(r#"
+        Build time comparison (CMake -> Bazel):
+
+        Targets:
+{}
+        Translation units and libraries:
+{}
+        Only built by CMake:
+{}
+        Only built by Bazel:
+{}{}"#,
+        format_comparisons(&self.targets),
+        format_comparisons(&self.units),
+        format_lines(&self.base_unmatched),
+        format_lines(&self.migration_unmatched),
+        warnings);
+        write!(f, "{}", display)
+    }
+}
+
+fn format_comparisons(comparisons: &[TimeComparison]) -> String {
+    let lines: Vec<String> = comparisons.iter()
+        .map(|c| format!("{}: {:.1}s -> {:.1}s ({:+.1}s{})",
+            c.name, c.base, c.migration, c.difference,
+            c.ratio().map(|r| format!(", x{:.1}", r)).unwrap_or_default()))
+        .collect();
+    format_lines(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLEAN_LOG: &str = include_str!("../../testdata/ninja/clean.ninja_log");
+    const INCREMENTAL_LOG: &str = include_str!("../../testdata/ninja/incremental.ninja_log");
+    const BEP: &str = include_str!("../../testdata/bazel/bep/build.json");
+    const PROFILE: &[u8] = include_bytes!("../../testdata/bazel/profile/command.profile.json");
+    const MAPPING: &str = "testdata/targets/mapping.json";
+
+    fn ninja_build() -> NinjaBuild {
+        NinjaBuild::from(CLEAN_LOG).unwrap()
+    }
+
+    fn names(comparisons: &[TimeComparison]) -> Vec<&str> {
+        comparisons.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    #[test]
+    fn from_ranks_units_of_profile_where_bazel_is_slowest() {
+        let profile = ProfileSummary::from(PROFILE).unwrap();
+
+        let times = TargetTimes::from(&ninja_units(&ninja_build()), &profile_units(&profile), &TargetMapping::default()).unwrap();
+
+        assert_eq!(names(&times.units), vec!["vsomeip3/routing_manager_impl", "vsomeip3/application_impl", "vsomeip3-sd/service_discovery_impl", "vsomeip3"]);
+        assert_eq!(times.units[0].base, 2.614);
+        assert_eq!(times.units[0].migration, 42.5);
+        assert_eq!(times.units[3].ratio(), Some(10.0));
+        assert_eq!(times.base_unmatched, vec![
+            "CMakeFiles/vsomeip3-cfg.dir/implementation/configuration/src/configuration_plugin_impl.cpp.o",
+            "libvsomeip3-cfg.so",
+            "libvsomeip3-sd.so.3.4.10",
+        ]);
+        assert_eq!(times.migration_unmatched, vec!["Compiling implementation/e2e_protection/src/crc/crc.cpp"]);
+    }
+
+    #[test]
+    fn from_compares_targets_by_name() {
+        let profile = ProfileSummary::from(PROFILE).unwrap();
+
+        let times = TargetTimes::from(&ninja_units(&ninja_build()), &profile_units(&profile), &TargetMapping::default()).unwrap();
+
+        assert_eq!(names(&times.targets), vec!["vsomeip3", "vsomeip3-sd"]);
+        assert_eq!(times.targets[0].base, 4.859);
+        assert_eq!(times.targets[0].migration, 80.5);
+    }
+
+    #[test]
+    fn from_pairs_actions_of_build_events() {
+        let bep = BuildSummary::from(BEP).unwrap();
+
+        let times = TargetTimes::from(&ninja_units(&ninja_build()), &bep_units(&bep), &TargetMapping::default()).unwrap()
+            .with_warnings(build_warnings(&ninja_build(), Some(&bep)));
+
+        assert_eq!(names(&times.units), vec!["vsomeip3/routing_manager_impl", "vsomeip3/application_impl", "vsomeip3-sd/service_discovery_impl", "vsomeip3"]);
+        assert_eq!(names(&times.targets), vec!["vsomeip3", "vsomeip3-sd"]);
+        assert!(times.migration_unmatched.is_empty());
+        assert_eq!(times.warnings, vec![String::from("75% of the Bazel actions were cache hits, build without the cache to compare the time of every unit")]);
+    }
+
+    #[test]
+    fn from_uses_mapping_file() {
+        let profile = ProfileSummary::from(PROFILE).unwrap();
+        let mapping = TargetMapping::read(MAPPING).unwrap();
+
+        let times = TargetTimes::from(&ninja_units(&ninja_build()), &profile_units(&profile), &mapping).unwrap();
+
+        // Only translation units are paired and vsomeip3 is mapped to a label the profile doesn't have.
+        assert_eq!(names(&times.units), vec!["routing_manager_impl", "application_impl", "service_discovery_impl"]);
+        assert_eq!(names(&times.targets), vec!["vsomeip3-sd"]);
+        assert_eq!(times.base_unmatched, vec!["CMakeFiles/vsomeip3-cfg.dir/implementation/configuration/src/configuration_plugin_impl.cpp.o"]);
+    }
+
+    #[test]
+    fn from_pairs_units_with_same_stem_by_target() {
+        let unit = |name: &str, target: &str, duration: f64| TimedUnit { name: name.to_string(), target: Some(target.to_string()), duration };
+        let base = vec![
+            unit("CMakeFiles/vsomeip3.dir/implementation/runtime/src/runtime_impl.cpp.o", "vsomeip3", 1.0),
+            unit("CMakeFiles/vsomeip3-sd.dir/implementation/service_discovery/src/runtime_impl.cpp.o", "vsomeip3-sd", 2.0),
+            unit("examples/CMakeFiles/hello.dir/main.cpp.o", "hello", 0.5),
+            unit("examples/CMakeFiles/hello.dir/other/main.cpp.o", "hello", 0.5),
+        ];
+        let migration = vec![
+            unit("Compiling implementation/runtime/src/runtime_impl.cpp", "//:vsomeip3", 4.0),
+            unit("Compiling implementation/service_discovery/src/runtime_impl.cpp", "//:vsomeip3-sd", 3.0),
+            unit("Compiling examples/main.cpp", "//examples:hello", 1.0),
+        ];
+
+        let times = TargetTimes::from(&base, &migration, &TargetMapping::default()).unwrap();
+
+        assert_eq!(names(&times.units), vec!["vsomeip3/runtime_impl", "vsomeip3-sd/runtime_impl"]);
+        assert_eq!(times.units[0].difference, 3.0);
+        assert_eq!(times.units[1].difference, 1.0);
+        // Units sharing a key within a build aren't added up.
+        assert_eq!(times.base_unmatched, vec!["examples/CMakeFiles/hello.dir/main.cpp.o", "examples/CMakeFiles/hello.dir/other/main.cpp.o"]);
+        assert_eq!(times.migration_unmatched, vec!["Compiling examples/main.cpp"]);
+        assert_eq!(times.warnings, vec![String::from("2 units of the CMake build share their key with another one and aren't compared, the unit rules need to tell them apart")]);
+    }
+
+    #[test]
+    fn from_fails_without_timed_bazel_actions() {
+        let bep = BuildSummary::default();
+
+        let actual_err = TargetTimes::from(&ninja_units(&ninja_build()), &bep_units(&bep), &TargetMapping::default()).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::NotFound);
+        assert!(actual_err.to_string().contains("--build_event_publish_all_actions"));
+    }
+
+    #[test]
+    fn build_warnings_flag_incremental_ninja_log() {
+        let warnings = build_warnings(&NinjaBuild::from(INCREMENTAL_LOG).unwrap(), None);
+
+        assert_eq!(warnings, vec![String::from("the CMake build only rebuilt 4 of the 10 outputs of the ninja log, the other units are missing")]);
+    }
+
+    #[test]
+    fn display_shows_difference_and_ratio() {
+        let times = TargetTimes {
+            targets: vec![TimeComparison::from("vsomeip3", 4.0, 10.0)],
+            units: vec![TimeComparison::from("crc", 0.0, 1.5)],
+            base_unmatched: vec![],
+            migration_unmatched: vec![String::from("Linking libvsomeip3-e2e.so")],
+            warnings: vec![],
+        };
+
+        let display = format!("{}", times);
+
+        assert!(display.contains("        Targets:\n            vsomeip3: 4.0s -> 10.0s (+6.0s, x2.5)\n"));
+        assert!(display.contains("            crc: 0.0s -> 1.5s (+1.5s)\n"));
+        assert!(display.contains("        Only built by CMake:\n            none\n"));
+        assert!(display.contains("        Only built by Bazel:\n            Linking libvsomeip3-e2e.so\n"));
+    }
+}
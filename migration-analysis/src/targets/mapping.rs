@@ -0,0 +1,178 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Error,ErrorKind};
+
+// Group of a rule capturing the target of a unit, the target of the unit itself is used when it doesn't match.
+const TARGET_GROUP: &str = "target";
+// Translation units are paired by target and file stem, e.g. CMakeFiles/vsomeip3.dir/src/a.cpp.o with
+// _objs/vsomeip3/a.pic.o or "Compiling src/a.cpp" of //:vsomeip3
+const DEFAULT_UNIT_RULES: [(&str, &str); 2] = [
+    (
+        r"(?:^|/)CMakeFiles/(?P<target>[^/]+)\.dir/(?:.*/)?([^/]+?)\.(?:c|cc|cpp|cxx)\.o(?:bj)?$",
+        r"^(?:Compiling (?:.*/)?|.*/_objs/(?P<target>[^/]+)/)([^/]+?)(?:\.pic)?\.(?:c|cc|cpp|cxx|o)$",
+    ),
+    // Libraries are paired by name, e.g. libvsomeip3.so.3.4.10 with "Linking libvsomeip3.so" or bazel-bin/libvsomeip3.so
+    (
+        r"(?:^|/)lib([^/]+?)\.(?:so[.0-9]*|a|dylib)$",
+        r"^(?:Linking (?:.*/)?|.*/)?lib([^/]+?)(?:\.pic)?\.(?:so|a|dylib)$",
+    ),
+];
+
+/// Pairs the outputs of the CMake build and the actions of the Bazel build whose names match the respective regex.
+/// Capture groups form the key used to pair units matched by the same rule, a group named `target` which doesn't
+/// match takes the CMake target of the unit, e.g. {"base": "CMakeFiles/vsomeip3\\.dir/.*/([^/]+)\\.cpp\\.o$", "migration": "_objs/vsomeip/([^/]+)\\.pic\\.o$"}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UnitRule {
+    pub base: String,
+    pub migration: String,
+}
+
+/// How the parts of the CMake build map to the ones of the Bazel build, read from the mapping file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TargetMapping {
+    /// Rules pairing units, they replace the default ones pairing translation units by target and file stem and libraries by name
+    #[serde(default)]
+    pub units: Vec<UnitRule>,
+    /// Bazel labels of CMake targets named differently, e.g. {"vsomeip3": "//:vsomeip"}
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+}
+
+impl TargetMapping {
+    pub fn read(path: &str) -> Result<TargetMapping, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::new(e.kind(), format!("failed to read {}: {}", path, e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("invalid mapping file {}: {}", path, e)))
+    }
+
+    /// CMake target built by a Bazel target, named after it without a mapping.
+    pub fn base_target_of(&self, bazel_target: &str) -> String {
+        self.targets.iter()
+            .find(|(_, label)| label_name(label) == bazel_target)
+            .map(|(target, _)| target.to_string())
+            .unwrap_or_else(|| bazel_target.to_string())
+    }
+
+    /// Whether a CMake target is built by a Bazel target, which is named after it without a mapping.
+    pub fn is_target_of(&self, base_target: &str, label: &str) -> bool {
+        match self.targets.get(base_target) {
+            Some(mapped) => mapped == label,
+            None => label_name(label) == base_target,
+        }
+    }
+}
+
+/// Name of the target of a label, e.g. vsomeip3 for //:vsomeip3 or @repo//lib:vsomeip3
+pub fn label_name(label: &str) -> &str {
+    match label.rsplit_once(':') {
+        Some((_, name)) => name,
+        None => label.rsplit('/').next().unwrap_or(label),
+    }
+}
+
+#[derive(Debug)]
+pub struct UnitRules {
+    rules: Vec<(Regex, Regex)>,
+    mapping: TargetMapping,
+}
+
+impl UnitRules {
+    pub fn from(mapping: &TargetMapping) -> Result<UnitRules, Error> {
+        let defaults: Vec<UnitRule> = DEFAULT_UNIT_RULES.iter()
+            .map(|(base, migration)| UnitRule { base: base.to_string(), migration: migration.to_string() })
+            .collect();
+        let rules = if mapping.units.is_empty() { &defaults } else { &mapping.units };
+        let mut compiled = vec![];
+        for rule in rules {
+            compiled.push((compile(&rule.base)?, compile(&rule.migration)?));
+        }
+        Ok(UnitRules { rules: compiled, mapping: mapping.clone() })
+    }
+
+    /// Key of an output of the CMake build of a target, from the first rule it matches.
+    pub fn base_key(&self, name: &str, target: Option<&str>) -> Option<(usize, String)> {
+        self.rules.iter().enumerate()
+            .find_map(|(index, (base, _))| Some((index, key(base, name, |captured| captured.or(target).map(String::from))?)))
+    }
+
+    /// Key of an action of the Bazel build of a label, from the first rule it matches.
+    pub fn migration_key(&self, name: &str, label: Option<&str>) -> Option<(usize, String)> {
+        let target = |captured: Option<&str>| captured.or(label.map(label_name)).map(|t| self.mapping.base_target_of(t));
+        self.rules.iter().enumerate()
+            .find_map(|(index, (_, migration))| Some((index, key(migration, name, target)?)))
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, Error> {
+    Regex::new(pattern)
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("invalid unit rule {}: {}", pattern, e)))
+}
+
+// Captured groups joined with "/", the target group is passed what it captured to get the target.
+fn key(regex: &Regex, name: &str, target: impl Fn(Option<&str>) -> Option<String>) -> Option<String> {
+    let captures = regex.captures(name)?;
+    let key: Vec<String> = regex.capture_names().zip(captures.iter()).skip(1)
+        .filter_map(|(group, capture)| match group {
+            Some(TARGET_GROUP) => target(capture.map(|c| c.as_str())),
+            _ => capture.map(|c| c.as_str().to_string()),
+        })
+        .collect();
+    Some(key.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_rules_pair_translation_units_by_target_and_stem() {
+        let rules = UnitRules::from(&TargetMapping::default()).unwrap();
+
+        assert_eq!(rules.base_key("CMakeFiles/vsomeip3.dir/implementation/routing/src/routing_manager_impl.cpp.o", Some("vsomeip3")).unwrap(), (0, String::from("vsomeip3/routing_manager_impl")));
+        assert_eq!(rules.migration_key("Compiling implementation/routing/src/routing_manager_impl.cpp", Some("//:vsomeip3")).unwrap(), (0, String::from("vsomeip3/routing_manager_impl")));
+        assert_eq!(rules.migration_key("bazel-out/k8-fastbuild/bin/_objs/vsomeip3/routing_manager_impl.pic.o", Some("//:vsomeip3")).unwrap(), (0, String::from("vsomeip3/routing_manager_impl")));
+        assert_eq!(rules.migration_key("Compiling src/crc.cpp", None).unwrap(), (0, String::from("crc")));
+    }
+
+    #[test]
+    fn migration_key_uses_cmake_target_of_mapped_label() {
+        let mapping = TargetMapping { targets: BTreeMap::from([(String::from("vsomeip3"), String::from("//:vsomeip"))]), ..TargetMapping::default() };
+        let rules = UnitRules::from(&mapping).unwrap();
+
+        assert_eq!(rules.migration_key("bazel-out/k8-fastbuild/bin/_objs/vsomeip/runtime_impl.pic.o", Some("//:vsomeip")).unwrap(), (0, String::from("vsomeip3/runtime_impl")));
+        assert_eq!(rules.migration_key("Compiling src/runtime_impl.cpp", Some("//:vsomeip")).unwrap(), (0, String::from("vsomeip3/runtime_impl")));
+    }
+
+    #[test]
+    fn default_rules_pair_libraries_by_name() {
+        let rules = UnitRules::from(&TargetMapping::default()).unwrap();
+
+        assert_eq!(rules.base_key("libvsomeip3-sd.so.3.4.10", Some("vsomeip3-sd")).unwrap(), (1, String::from("vsomeip3-sd")));
+        assert_eq!(rules.base_key("lib/libvsomeip3.a", Some("vsomeip3")).unwrap(), (1, String::from("vsomeip3")));
+        assert_eq!(rules.migration_key("Linking libvsomeip3-sd.so", Some("//:vsomeip3-sd")).unwrap(), (1, String::from("vsomeip3-sd")));
+        assert_eq!(rules.migration_key("bazel-out/k8-fastbuild/bin/libvsomeip3.pic.a", None).unwrap(), (1, String::from("vsomeip3")));
+        assert!(rules.migration_key("Executing genrule //:vsomeip_cfg_h", Some("//:vsomeip_cfg_h")).is_none());
+    }
+
+    #[test]
+    fn unit_rules_fail_on_invalid_regex() {
+        let mapping = TargetMapping { units: vec![UnitRule { base: String::from("("), migration: String::from("x") }], ..TargetMapping::default() };
+
+        let actual_err = UnitRules::from(&mapping).unwrap_err();
+
+        assert_eq!(actual_err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn is_target_of_uses_mapped_label() {
+        let mapping = TargetMapping { targets: BTreeMap::from([(String::from("vsomeip3"), String::from("//:vsomeip"))]), ..TargetMapping::default() };
+
+        assert!(mapping.is_target_of("vsomeip3", "//:vsomeip"));
+        assert!(!mapping.is_target_of("vsomeip3", "//:vsomeip3"));
+        assert!(mapping.is_target_of("vsomeip3-sd", "//:vsomeip3-sd"));
+        assert!(mapping.is_target_of("vsomeip3-e2e", "//e2e/vsomeip3-e2e"));
+    }
+}
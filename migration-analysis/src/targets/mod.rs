@@ -0,0 +1,2 @@
+pub mod comparison;
+pub mod mapping;
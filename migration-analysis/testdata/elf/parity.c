@@ -0,0 +1,15 @@
+/* Sources of the ELF fixtures used by the artifact parity tests:
+ *
+ *   gcc -shared -fPIC -s -Wl,-soname,libparity.so.1 -o libbase.elf parity.c
+ *   gcc -shared -fPIC -s -DMIGRATION -Wl,-soname,libparity.so -o libmigration.elf parity.c
+ */
+int shared_symbol(void) { return 1; }
+
+#ifdef MIGRATION
+int migration_symbol(void) { return 3; }
+#else
+int base_symbol(void) { return 2; }
+#endif
+
+static int hidden_symbol(void) { return 4; }
+int use_hidden(void) { return hidden_symbol(); }